@@ -74,6 +74,19 @@ else
     log "Cargo.toml → $VERSION"
 fi
 
+# talon-sys-derive/Cargo.toml 与 talon-sys 对它的依赖版本（发布 talon-sys 需要带 version）
+DERIVE_TOML="$SCRIPT_DIR/talon-sys-derive/Cargo.toml"
+if grep -q "^version = \"$VERSION\"" "$DERIVE_TOML"; then
+    log "talon-sys-derive Cargo.toml 已是 $VERSION"
+else
+    sed -i '' "s/^version = \".*\"/version = \"$VERSION\"/" "$DERIVE_TOML"
+    log "talon-sys-derive Cargo.toml → $VERSION"
+fi
+sed -i '' "s/^talon-sys-derive = { version = \"[^\"]*\"/talon-sys-derive = { version = \"$VERSION\"/" "$CARGO_TOML"
+grep -q "^talon-sys-derive = { version = \"$VERSION\"" "$CARGO_TOML" \
+    || err "未能更新 talon-sys 对 talon-sys-derive 的依赖版本"
+log "talon-sys-derive 依赖 → $VERSION"
+
 # talon-sys/build.rs (TALON_LIB_VERSION)
 BUILD_RS="$SCRIPT_DIR/talon-sys/build.rs"
 if grep -q "TALON_LIB_VERSION: &str = \"$VERSION\"" "$BUILD_RS"; then
@@ -0,0 +1,76 @@
+/*
+ * Copyright (c) 2026 Talon Contributors
+ * Author: dark.lijin@gmail.com
+ * Licensed under the Talon Community Dual License Agreement.
+ * See the LICENSE file in the project root for full license information.
+ */
+//! talon-sys 的派生宏，通过 `talon-sys` 的 `derive` feature 引入。
+//!
+//! `#[derive(FromRow)]` 按字段声明顺序依次映射结果行的列，列数须与字段数一致：
+//!
+//! ```ignore
+//! #[derive(talon_sys::FromRow)]
+//! struct User {
+//!     id: i64,          // 第 0 列
+//!     name: String,     // 第 1 列
+//!     email: Option<String>,
+//! }
+//! let users: Vec<User> = db.query_as("SELECT id, name, email FROM users", &[])?;
+//! ```
+
+use proc_macro::TokenStream;
+use proc_macro2::TokenStream as TokenStream2;
+use quote::quote;
+use syn::{parse_macro_input, Data, DeriveInput, Fields};
+
+/// 为结构体实现 `talon_sys::FromRow`（按列位置映射）。
+#[proc_macro_derive(FromRow)]
+pub fn derive_from_row(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    expand_from_row(&input)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
+
+fn expand_from_row(input: &DeriveInput) -> syn::Result<TokenStream2> {
+    let name = &input.ident;
+    let Data::Struct(data) = &input.data else {
+        return Err(syn::Error::new_spanned(
+            name,
+            "FromRow can only be derived for structs",
+        ));
+    };
+    let len = data.fields.len();
+    let body = match &data.fields {
+        Fields::Named(fields) => {
+            let columns = fields.named.iter().enumerate().map(|(index, field)| {
+                let ident = &field.ident;
+                quote! { #ident: ::talon_sys::row_take(&mut row, #index)? }
+            });
+            quote! { Self { #(#columns),* } }
+        }
+        Fields::Unnamed(fields) => {
+            let columns = (0..fields.unnamed.len())
+                .map(|index| quote! { ::talon_sys::row_take(&mut row, #index)? });
+            quote! { Self(#(#columns),*) }
+        }
+        Fields::Unit => {
+            return Err(syn::Error::new_spanned(
+                name,
+                "FromRow cannot be derived for unit structs",
+            ))
+        }
+    };
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+    Ok(quote! {
+        impl #impl_generics ::talon_sys::FromRow for #name #ty_generics #where_clause {
+            #[allow(unused_mut)]
+            fn from_row(
+                mut row: ::std::vec::Vec<::talon_sys::Value>,
+            ) -> ::std::result::Result<Self, ::talon_sys::TalonError> {
+                ::talon_sys::row_check_len(&row, #len)?;
+                ::std::result::Result::Ok(#body)
+            }
+        }
+    })
+}
@@ -0,0 +1,322 @@
+/*
+ * Copyright (c) 2026 Talon Contributors
+ * Author: dark.lijin@gmail.com
+ * Licensed under the Talon Community Dual License Agreement.
+ * See the LICENSE file in the project root for full license information.
+ */
+//! 批量写入 — 行按批编码为单个二进制载荷，每批一次调用。
+//!
+//! 载荷格式（单元格 TLV 同 `encode_value`）：
+//!
+//! ```text
+//! col_count: u32, 每列 name_len: u32, name
+//! row_count: u32, 行优先的 TLV 单元格
+//! ```
+//!
+//! 嵌入式：每批一次 `talon_bulk_insert_bin`。
+//! 远程：`{"module":"sql","action":"bulk_insert","params":{"table","payload"}}`，
+//! payload 为 base64；除行数上限外还按字节数拆批，保证单帧不超过 `MAX_REMOTE_FRAME_SIZE`。
+//!
+//! 每批在引擎内原子写入；某批失败或某行无效时之前的批次已生效，错误信息中带已写入行数。
+
+use std::ffi::CString;
+
+use base64::Engine as _;
+
+use crate::{
+    encode_value, ffi_error, raw_ffi, remote_error, remote_response_data, Talon, TalonError,
+    TalonRemoteClient, TalonRemoteErrorKind, Value, BASE64, MAX_REMOTE_FRAME_SIZE,
+};
+
+/// 每批默认行数。
+const BULK_BATCH_ROWS: usize = 1000;
+
+/// 远程单批载荷上限：为 JSON 外壳预留 64 KiB，再扣除 base64 膨胀。
+const REMOTE_BULK_PAYLOAD_LIMIT: usize = (MAX_REMOTE_FRAME_SIZE as usize - 64 * 1024) / 4 * 3;
+
+enum BulkTarget<'a> {
+    Embedded(&'a Talon),
+    Remote(&'a TalonRemoteClient),
+}
+
+/// 批量写入器（`Talon::bulk_inserter` / `TalonRemoteClient::bulk_inserter`）。
+pub struct BulkInsert<'a> {
+    target: BulkTarget<'a>,
+    table: String,
+    columns: Vec<String>,
+    batch_rows: usize,
+}
+
+impl<'a> BulkInsert<'a> {
+    fn new(target: BulkTarget<'a>, table: &str, columns: &[&str]) -> Self {
+        Self {
+            target,
+            table: table.to_string(),
+            columns: columns.iter().map(|c| c.to_string()).collect(),
+            batch_rows: BULK_BATCH_ROWS,
+        }
+    }
+
+    /// 设置每批最大行数（至少 1）。
+    pub fn batch_size(mut self, rows: usize) -> Self {
+        self.batch_rows = rows.max(1);
+        self
+    }
+
+    /// 写入所有行，返回写入总行数。每行的值按构造时的列顺序排列。
+    pub fn insert<I, R>(&self, rows: I) -> Result<u64, TalonError>
+    where
+        I: IntoIterator<Item = R>,
+        R: AsRef<[Value]>,
+    {
+        let max_payload = match self.target {
+            BulkTarget::Embedded(_) => None,
+            BulkTarget::Remote(_) => Some(REMOTE_BULK_PAYLOAD_LIMIT),
+        };
+        encode_batches(
+            &self.columns,
+            rows,
+            self.batch_rows,
+            max_payload,
+            |payload| match self.target {
+                BulkTarget::Embedded(db) => db.bulk_insert_bin(&self.table, payload),
+                BulkTarget::Remote(client) => client.bulk_insert_bin(&self.table, payload),
+            },
+        )
+    }
+}
+
+/// 逐行编码并按行数 / 字节数切批，每批调用一次 `send`，返回写入总行数。
+fn encode_batches<I, R>(
+    columns: &[String],
+    rows: I,
+    batch_rows: usize,
+    max_payload: Option<usize>,
+    mut send: impl FnMut(&[u8]) -> Result<u64, TalonError>,
+) -> Result<u64, TalonError>
+where
+    I: IntoIterator<Item = R>,
+    R: AsRef<[Value]>,
+{
+    if columns.is_empty() {
+        return Err(TalonError("bulk_insert needs at least one column".into()));
+    }
+    let mut buf = Vec::new();
+    buf.extend_from_slice(&(columns.len() as u32).to_le_bytes());
+    for name in columns {
+        buf.extend_from_slice(&(name.len() as u32).to_le_bytes());
+        buf.extend_from_slice(name.as_bytes());
+    }
+    let count_pos = buf.len();
+    buf.extend_from_slice(&0u32.to_le_bytes());
+    let body_start = buf.len();
+
+    let mut inserted = 0u64;
+    let mut pending = 0usize;
+    let mut flush = |buf: &mut Vec<u8>, pending: usize, inserted: u64| {
+        buf[count_pos..body_start].copy_from_slice(&(pending as u32).to_le_bytes());
+        let n = send(buf).map_err(|e| {
+            TalonError(format!("bulk_insert failed after {inserted} rows: {}", e.0))
+        })?;
+        buf.truncate(body_start);
+        Ok::<u64, TalonError>(n)
+    };
+
+    for (i, row) in rows.into_iter().enumerate() {
+        let row = row.as_ref();
+        if row.len() != columns.len() {
+            return Err(TalonError(format!(
+                "bulk_insert failed after {inserted} rows: row {i}: expected {} values, got {}",
+                columns.len(),
+                row.len()
+            )));
+        }
+        let mark = buf.len();
+        for value in row {
+            encode_value(&mut buf, value);
+        }
+        if let Some(limit) = max_payload {
+            if buf.len() > limit {
+                if body_start + buf.len() - mark > limit {
+                    return Err(TalonError(format!(
+                        "bulk_insert failed after {inserted} rows: row {i}: \
+                         encoded row exceeds the {limit}-byte batch limit"
+                    )));
+                }
+                let tail = buf.split_off(mark);
+                inserted += flush(&mut buf, pending, inserted)?;
+                pending = 0;
+                buf.extend_from_slice(&tail);
+            }
+        }
+        pending += 1;
+        if pending >= batch_rows {
+            inserted += flush(&mut buf, pending, inserted)?;
+            pending = 0;
+        }
+    }
+    if pending > 0 {
+        inserted += flush(&mut buf, pending, inserted)?;
+    }
+    Ok(inserted)
+}
+
+impl Talon {
+    /// 批量写入 `table`：行按批编码为单个二进制载荷，返回写入行数。
+    pub fn bulk_insert<I, R>(
+        &self,
+        table: &str,
+        columns: &[&str],
+        rows: I,
+    ) -> Result<u64, TalonError>
+    where
+        I: IntoIterator<Item = R>,
+        R: AsRef<[Value]>,
+    {
+        self.bulk_inserter(table, columns).insert(rows)
+    }
+
+    /// 创建可配置批大小的批量写入器。
+    pub fn bulk_inserter(&self, table: &str, columns: &[&str]) -> BulkInsert<'_> {
+        BulkInsert::new(BulkTarget::Embedded(self), table, columns)
+    }
+
+    fn bulk_insert_bin(&self, table: &str, payload: &[u8]) -> Result<u64, TalonError> {
+        let c_table = CString::new(table)?;
+        let mut inserted: u64 = 0;
+        let rc = unsafe {
+            raw_ffi::talon_bulk_insert_bin(
+                self.handle,
+                c_table.as_ptr(),
+                payload.as_ptr(),
+                payload.len(),
+                &mut inserted,
+            )
+        };
+        if rc != 0 {
+            return Err(ffi_error("bulk_insert"));
+        }
+        Ok(inserted)
+    }
+}
+
+impl TalonRemoteClient {
+    /// Insert rows into `table` in binary batches; each request stays under
+    /// the maximum frame size. Returns the number of inserted rows.
+    pub fn bulk_insert<I, R>(
+        &self,
+        table: &str,
+        columns: &[&str],
+        rows: I,
+    ) -> Result<u64, TalonError>
+    where
+        I: IntoIterator<Item = R>,
+        R: AsRef<[Value]>,
+    {
+        self.bulk_inserter(table, columns).insert(rows)
+    }
+
+    /// Create a bulk inserter with a configurable batch size.
+    pub fn bulk_inserter(&self, table: &str, columns: &[&str]) -> BulkInsert<'_> {
+        BulkInsert::new(BulkTarget::Remote(self), table, columns)
+    }
+
+    fn bulk_insert_bin(&self, table: &str, payload: &[u8]) -> Result<u64, TalonError> {
+        let resp = self.exec_cmd_json(&serde_json::json!({
+            "module": "sql",
+            "action": "bulk_insert",
+            "params": { "table": table, "payload": BASE64.encode(payload) }
+        }))?;
+        remote_response_data(&resp)?
+            .and_then(|d| d.get("inserted"))
+            .and_then(|v| v.as_u64())
+            .ok_or_else(|| {
+                remote_error(
+                    TalonRemoteErrorKind::Protocol,
+                    format!("bulk_insert response missing inserted: {resp}"),
+                )
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn columns() -> Vec<String> {
+        vec!["id".into(), "name".into()]
+    }
+
+    fn rows(n: i64) -> Vec<Vec<Value>> {
+        (0..n)
+            .map(|i| vec![Value::Integer(i), Value::Text(format!("row-{i}"))])
+            .collect()
+    }
+
+    /// 从载荷头部读出行数。
+    fn row_count(payload: &[u8]) -> u32 {
+        // 4 + (4 + "id") + (4 + "name")
+        let pos = 4 + 4 + 2 + 4 + 4;
+        u32::from_le_bytes(payload[pos..pos + 4].try_into().unwrap())
+    }
+
+    #[test]
+    fn rows_are_split_into_batches() {
+        let mut batches = Vec::new();
+        let inserted = encode_batches(&columns(), rows(5), 2, None, |payload| {
+            batches.push(row_count(payload));
+            Ok(u64::from(row_count(payload)))
+        })
+        .unwrap();
+        assert_eq!(inserted, 5);
+        assert_eq!(batches, vec![2, 2, 1]);
+    }
+
+    #[test]
+    fn payload_limit_splits_batches_by_size() {
+        let mut sizes = Vec::new();
+        let inserted = encode_batches(&columns(), rows(10), 1000, Some(80), |payload| {
+            assert!(payload.len() <= 80);
+            sizes.push(row_count(payload));
+            Ok(u64::from(row_count(payload)))
+        })
+        .unwrap();
+        assert_eq!(inserted, 10);
+        assert!(sizes.len() > 1);
+        assert_eq!(sizes.iter().sum::<u32>(), 10);
+
+        let err = encode_batches(&columns(), rows(1), 1000, Some(20), |_| Ok(1)).unwrap_err();
+        assert!(err.0.contains("exceeds"));
+    }
+
+    #[test]
+    fn mismatched_rows_and_failed_batches_are_reported() {
+        let bad = vec![vec![Value::Integer(1)]];
+        let err = encode_batches(&columns(), bad, 10, None, |_| Ok(1)).unwrap_err();
+        assert_eq!(
+            err.0,
+            "bulk_insert failed after 0 rows: row 0: expected 2 values, got 1"
+        );
+
+        // 之前的批次已写入时，行长度错误带上已写入行数。
+        let mut late = rows(3);
+        late.push(vec![Value::Null]);
+        let err = encode_batches(&columns(), late, 2, None, |_| Ok(2)).unwrap_err();
+        assert_eq!(
+            err.0,
+            "bulk_insert failed after 2 rows: row 3: expected 2 values, got 1"
+        );
+
+        let mut calls = 0;
+        let err = encode_batches(&columns(), rows(4), 2, None, |_| {
+            calls += 1;
+            if calls == 2 {
+                Err(TalonError("disk full".into()))
+            } else {
+                Ok(2)
+            }
+        })
+        .unwrap_err();
+        assert_eq!(err.0, "bulk_insert failed after 2 rows: disk full");
+    }
+}
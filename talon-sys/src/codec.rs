@@ -0,0 +1,196 @@
+/*
+ * Copyright (c) 2026 Talon Contributors
+ * Author: dark.lijin@gmail.com
+ * Licensed under the Talon Community Dual License Agreement.
+ * See the LICENSE file in the project root for full license information.
+ */
+//! KV 类型化读写 — 通过可插拔的 `KvCodec` 在 `&[u8]` 与 Rust 类型之间转换。
+//!
+//! 默认使用 JSON；启用 `bincode` / `msgpack` feature 后可选用对应的二进制编码。
+
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+
+use crate::{KvEngine, RemoteKvEngine, TalonError};
+
+/// KV 值编解码器。
+pub trait KvCodec {
+    /// 编码器名称，用于错误信息。
+    const NAME: &'static str;
+
+    fn encode<T: Serialize + ?Sized>(value: &T) -> Result<Vec<u8>, TalonError>;
+
+    fn decode<T: DeserializeOwned>(bytes: &[u8]) -> Result<T, TalonError>;
+}
+
+/// JSON 编解码器（默认）。
+#[derive(Debug, Clone, Copy, Default)]
+pub struct JsonCodec;
+
+impl KvCodec for JsonCodec {
+    const NAME: &'static str = "json";
+
+    fn encode<T: Serialize + ?Sized>(value: &T) -> Result<Vec<u8>, TalonError> {
+        serde_json::to_vec(value).map_err(|e| codec_error::<Self>("encode", e))
+    }
+
+    fn decode<T: DeserializeOwned>(bytes: &[u8]) -> Result<T, TalonError> {
+        serde_json::from_slice(bytes).map_err(|e| codec_error::<Self>("decode", e))
+    }
+}
+
+/// bincode 编解码器（需启用 `bincode` feature）。
+#[cfg(feature = "bincode")]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct BincodeCodec;
+
+#[cfg(feature = "bincode")]
+impl KvCodec for BincodeCodec {
+    const NAME: &'static str = "bincode";
+
+    fn encode<T: Serialize + ?Sized>(value: &T) -> Result<Vec<u8>, TalonError> {
+        bincode::serialize(value).map_err(|e| codec_error::<Self>("encode", e))
+    }
+
+    fn decode<T: DeserializeOwned>(bytes: &[u8]) -> Result<T, TalonError> {
+        bincode::deserialize(bytes).map_err(|e| codec_error::<Self>("decode", e))
+    }
+}
+
+/// MessagePack 编解码器（需启用 `msgpack` feature）。
+///
+/// 结构体按字段名编码，字段增删时仍可兼容读取。
+#[cfg(feature = "msgpack")]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct MsgPackCodec;
+
+#[cfg(feature = "msgpack")]
+impl KvCodec for MsgPackCodec {
+    const NAME: &'static str = "msgpack";
+
+    fn encode<T: Serialize + ?Sized>(value: &T) -> Result<Vec<u8>, TalonError> {
+        rmp_serde::to_vec_named(value).map_err(|e| codec_error::<Self>("encode", e))
+    }
+
+    fn decode<T: DeserializeOwned>(bytes: &[u8]) -> Result<T, TalonError> {
+        rmp_serde::from_slice(bytes).map_err(|e| codec_error::<Self>("decode", e))
+    }
+}
+
+fn codec_error<C: KvCodec>(op: &str, err: impl std::fmt::Display) -> TalonError {
+    TalonError(format!("kv {} {op}: {err}", C::NAME))
+}
+
+impl<'a> KvEngine<'a> {
+    /// 读取并用 JSON 解码。
+    pub fn get_typed<T: DeserializeOwned>(&self, key: &[u8]) -> Result<Option<T>, TalonError> {
+        self.get_typed_with::<JsonCodec, T>(key)
+    }
+
+    /// 用 JSON 编码后写入，可选 TTL。
+    pub fn set_typed<T: Serialize + ?Sized>(
+        &self,
+        key: &[u8],
+        value: &T,
+        ttl_secs: Option<u64>,
+    ) -> Result<(), TalonError> {
+        self.set_typed_with::<JsonCodec, T>(key, value, ttl_secs)
+    }
+
+    /// 读取并用指定编解码器解码。
+    pub fn get_typed_with<C: KvCodec, T: DeserializeOwned>(
+        &self,
+        key: &[u8],
+    ) -> Result<Option<T>, TalonError> {
+        self.get(key)?.map(|bytes| C::decode(&bytes)).transpose()
+    }
+
+    /// 用指定编解码器编码后写入。
+    pub fn set_typed_with<C: KvCodec, T: Serialize + ?Sized>(
+        &self,
+        key: &[u8],
+        value: &T,
+        ttl_secs: Option<u64>,
+    ) -> Result<(), TalonError> {
+        self.set(key, &C::encode(value)?, ttl_secs)
+    }
+}
+
+impl<'a> RemoteKvEngine<'a> {
+    /// Read a remote value and decode it as JSON.
+    pub fn get_typed<T: DeserializeOwned>(&self, key: &[u8]) -> Result<Option<T>, TalonError> {
+        self.get_typed_with::<JsonCodec, T>(key)
+    }
+
+    /// Encode a value as JSON and write it remotely.
+    pub fn set_typed<T: Serialize + ?Sized>(
+        &self,
+        key: &[u8],
+        value: &T,
+        ttl_secs: Option<u64>,
+    ) -> Result<(), TalonError> {
+        self.set_typed_with::<JsonCodec, T>(key, value, ttl_secs)
+    }
+
+    /// Read a remote value and decode it with codec `C`.
+    pub fn get_typed_with<C: KvCodec, T: DeserializeOwned>(
+        &self,
+        key: &[u8],
+    ) -> Result<Option<T>, TalonError> {
+        self.get(key)?.map(|bytes| C::decode(&bytes)).transpose()
+    }
+
+    /// Encode a value with codec `C` and write it remotely.
+    ///
+    /// Binary codecs need a binary-safe remote transport.
+    pub fn set_typed_with<C: KvCodec, T: Serialize + ?Sized>(
+        &self,
+        key: &[u8],
+        value: &T,
+        ttl_secs: Option<u64>,
+    ) -> Result<(), TalonError> {
+        self.set(key, &C::encode(value)?, ttl_secs)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde::Deserialize;
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct Flag {
+        name: String,
+        enabled: bool,
+        rollout: Option<u8>,
+    }
+
+    fn round_trip<C: KvCodec>() {
+        let flag = Flag {
+            name: "beta".into(),
+            enabled: true,
+            rollout: Some(25),
+        };
+        let bytes = C::encode(&flag).unwrap();
+        assert_eq!(C::decode::<Flag>(&bytes).unwrap(), flag);
+        let err = C::decode::<Flag>(b"\xff\x00").unwrap_err();
+        assert!(err.0.starts_with(&format!("kv {} decode", C::NAME)));
+    }
+
+    #[test]
+    fn json_codec_round_trip() {
+        round_trip::<JsonCodec>();
+    }
+
+    #[cfg(feature = "bincode")]
+    #[test]
+    fn bincode_codec_round_trip() {
+        round_trip::<BincodeCodec>();
+    }
+
+    #[cfg(feature = "msgpack")]
+    #[test]
+    fn msgpack_codec_round_trip() {
+        round_trip::<MsgPackCodec>();
+    }
+}
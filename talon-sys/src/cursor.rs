@@ -0,0 +1,371 @@
+/*
+ * Copyright (c) 2026 Talon Contributors
+ * Author: dark.lijin@gmail.com
+ * Licensed under the Talon Community Dual License Agreement.
+ * See the LICENSE file in the project root for full license information.
+ */
+//! 流式查询游标 — 大结果集按页拉取、逐行解码。
+//!
+//! 嵌入式：引擎侧游标每次返回一页 TLV 行块（格式同 `run_sql`），迭代器直接在
+//! FFI 缓冲区上逐行解码，读完一页才拉取下一页。
+//! 远程：`cursor_open` / `cursor_fetch` / `cursor_close` 命令分页传输，单个结果集
+//! 可以超过 `MAX_REMOTE_FRAME_SIZE`。
+
+use std::collections::VecDeque;
+use std::ffi::CString;
+use std::ptr;
+use std::slice;
+
+use crate::{
+    decode_value, encode_params, ffi_error, inline_sql_params, raw_ffi, remote_error,
+    remote_query_result, remote_response_data, Params, Talon, TalonError, TalonRemoteClient,
+    TalonRemoteErrorKind, Value,
+};
+
+/// 每页默认行数。
+const CURSOR_PAGE_ROWS: usize = 1000;
+
+/// 引擎分配的字节缓冲区，drop 时释放。
+struct FfiBytes {
+    ptr: *mut u8,
+    len: usize,
+}
+
+impl AsRef<[u8]> for FfiBytes {
+    fn as_ref(&self) -> &[u8] {
+        unsafe { slice::from_raw_parts(self.ptr, self.len) }
+    }
+}
+
+impl Drop for FfiBytes {
+    fn drop(&mut self) {
+        unsafe { raw_ffi::talon_free_bytes(self.ptr, self.len) };
+    }
+}
+
+/// 一页行块：`row_count: u32, col_count: u32` + 行优先的 TLV 单元格。
+///
+/// 缓冲区通常是引擎分配的 [`FfiBytes`]，直接在其上逐行解码。
+struct RowChunk<B: AsRef<[u8]> = FfiBytes> {
+    buf: B,
+    pos: usize,
+    rows_left: usize,
+    col_count: usize,
+}
+
+impl<B: AsRef<[u8]>> RowChunk<B> {
+    fn new(buf: B) -> Result<Self, TalonError> {
+        let data = buf.as_ref();
+        if data.len() < 8 {
+            return Err(TalonError("binary result too short".into()));
+        }
+        let rows_left = u32::from_le_bytes([data[0], data[1], data[2], data[3]]) as usize;
+        let col_count = u32::from_le_bytes([data[4], data[5], data[6], data[7]]) as usize;
+        Ok(Self {
+            buf,
+            pos: 8,
+            rows_left,
+            col_count,
+        })
+    }
+
+    fn next_row(&mut self) -> Option<Result<Vec<Value>, TalonError>> {
+        if self.rows_left == 0 {
+            return None;
+        }
+        self.rows_left -= 1;
+        let data = self.buf.as_ref();
+        let mut row = Vec::with_capacity(self.col_count);
+        for _ in 0..self.col_count {
+            match decode_value(data, self.pos) {
+                Ok((value, consumed)) => {
+                    row.push(value);
+                    self.pos += consumed;
+                }
+                Err(e) => {
+                    self.rows_left = 0;
+                    return Some(Err(e));
+                }
+            }
+        }
+        Some(Ok(row))
+    }
+}
+
+enum CursorSource<'a> {
+    Embedded {
+        db: &'a Talon,
+        cursor_id: u64,
+        chunk: Option<RowChunk>,
+    },
+    Remote {
+        client: &'a TalonRemoteClient,
+        cursor_id: u64,
+        buf: VecDeque<Vec<Value>>,
+    },
+}
+
+/// 流式结果迭代器（`query_iter`）。drop 时关闭引擎 / 服务端游标。
+pub struct RowCursor<'a> {
+    source: CursorSource<'a>,
+    page_rows: usize,
+    exhausted: bool,
+}
+
+impl<'a> RowCursor<'a> {
+    /// 每次向引擎拉取的行数（默认 1000）。
+    pub fn page_size(mut self, rows: usize) -> Self {
+        self.page_rows = rows.max(1);
+        self
+    }
+
+    fn fetch(&mut self) -> Result<(), TalonError> {
+        let page_rows = self.page_rows;
+        match &mut self.source {
+            CursorSource::Embedded {
+                db,
+                cursor_id,
+                chunk,
+            } => {
+                let mut out_data: *mut u8 = ptr::null_mut();
+                let mut out_len: usize = 0;
+                let rc = unsafe {
+                    raw_ffi::talon_cursor_fetch_bin(
+                        db.handle,
+                        *cursor_id,
+                        page_rows,
+                        &mut out_data,
+                        &mut out_len,
+                    )
+                };
+                if rc != 0 {
+                    return Err(ffi_error("cursor_fetch"));
+                }
+                if out_data.is_null() || out_len == 0 {
+                    self.exhausted = true;
+                    return Ok(());
+                }
+                let next = RowChunk::new(FfiBytes {
+                    ptr: out_data,
+                    len: out_len,
+                })?;
+                self.exhausted = next.rows_left == 0;
+                *chunk = Some(next);
+            }
+            CursorSource::Remote {
+                client,
+                cursor_id,
+                buf,
+            } => {
+                let resp = client.exec_cmd_json(&serde_json::json!({
+                    "module": "sql",
+                    "action": "cursor_fetch",
+                    "params": { "cursor_id": *cursor_id, "max_rows": page_rows }
+                }))?;
+                let data = remote_response_data(&resp)?.ok_or_else(|| {
+                    remote_error(
+                        TalonRemoteErrorKind::Protocol,
+                        format!("SQL cursor_fetch response missing data: {resp}"),
+                    )
+                })?;
+                let rows = remote_query_result(data)?.rows;
+                let done = data.get("done").and_then(|d| d.as_bool());
+                self.exhausted = done.unwrap_or(rows.is_empty());
+                buf.extend(rows);
+            }
+        }
+        Ok(())
+    }
+
+    fn pop(&mut self) -> Option<Result<Vec<Value>, TalonError>> {
+        match &mut self.source {
+            CursorSource::Embedded { chunk, .. } => {
+                let row = chunk.as_mut()?.next_row();
+                if row.is_none() {
+                    *chunk = None;
+                }
+                row
+            }
+            CursorSource::Remote { buf, .. } => buf.pop_front().map(Ok),
+        }
+    }
+}
+
+impl Iterator for RowCursor<'_> {
+    type Item = Result<Vec<Value>, TalonError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(row) = self.pop() {
+                if row.is_err() {
+                    self.exhausted = true;
+                }
+                return Some(row);
+            }
+            if self.exhausted {
+                return None;
+            }
+            if let Err(e) = self.fetch() {
+                self.exhausted = true;
+                return Some(Err(e));
+            }
+        }
+    }
+}
+
+impl Drop for RowCursor<'_> {
+    fn drop(&mut self) {
+        match &mut self.source {
+            CursorSource::Embedded {
+                db,
+                cursor_id,
+                chunk,
+            } => {
+                // 先释放当前页缓冲区，再关闭游标。
+                *chunk = None;
+                unsafe { raw_ffi::talon_cursor_close(db.handle, *cursor_id) };
+            }
+            CursorSource::Remote {
+                client, cursor_id, ..
+            } => {
+                let _ = client.exec_cmd(&serde_json::json!({
+                    "module": "sql",
+                    "action": "cursor_close",
+                    "params": { "cursor_id": *cursor_id }
+                }));
+            }
+        }
+    }
+}
+
+impl Talon {
+    /// 流式执行查询：按页从引擎拉取结果，逐行解码，适合大结果集导出。
+    pub fn query_iter(
+        &self,
+        sql: &str,
+        params: impl Into<Params>,
+    ) -> Result<RowCursor<'_>, TalonError> {
+        let (sql, params) = params.into().bind(sql)?;
+        let c_sql = CString::new(&*sql)?;
+        let params_bin = encode_params(&params);
+        let mut cursor_id: u64 = 0;
+        let rc = unsafe {
+            raw_ffi::talon_cursor_open(
+                self.handle,
+                c_sql.as_ptr(),
+                params_bin.as_ptr(),
+                params_bin.len(),
+                &mut cursor_id,
+            )
+        };
+        if rc != 0 {
+            return Err(ffi_error("cursor_open"));
+        }
+        Ok(RowCursor {
+            source: CursorSource::Embedded {
+                db: self,
+                cursor_id,
+                chunk: None,
+            },
+            page_rows: CURSOR_PAGE_ROWS,
+            exhausted: false,
+        })
+    }
+}
+
+impl TalonRemoteClient {
+    /// Stream a query result in pages through a server-side cursor, so the
+    /// whole result may exceed the maximum frame size.
+    pub fn query_iter(
+        &self,
+        sql: &str,
+        params: impl Into<Params>,
+    ) -> Result<RowCursor<'_>, TalonError> {
+        let (sql, params) = params.into().bind(sql)?;
+        let rendered;
+        let sql = if params.is_empty() {
+            &*sql
+        } else {
+            rendered = inline_sql_params(&sql, &params)?;
+            &rendered
+        };
+        let resp = self.exec_cmd_json(&serde_json::json!({
+            "module": "sql",
+            "action": "cursor_open",
+            "params": { "sql": sql }
+        }))?;
+        let cursor_id = remote_response_data(&resp)?
+            .and_then(|d| d.get("cursor_id"))
+            .and_then(|v| v.as_u64())
+            .ok_or_else(|| {
+                remote_error(
+                    TalonRemoteErrorKind::Protocol,
+                    format!("SQL cursor_open response missing cursor_id: {resp}"),
+                )
+            })?;
+        Ok(RowCursor {
+            source: CursorSource::Remote {
+                client: self,
+                cursor_id,
+                buf: VecDeque::new(),
+            },
+            page_rows: CURSOR_PAGE_ROWS,
+            exhausted: false,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::encode_value;
+
+    fn chunk_bytes(rows: &[Vec<Value>]) -> Vec<u8> {
+        let cols = rows.first().map_or(0, Vec::len) as u32;
+        let mut data = (rows.len() as u32).to_le_bytes().to_vec();
+        data.extend_from_slice(&cols.to_le_bytes());
+        for value in rows.iter().flatten() {
+            encode_value(&mut data, value);
+        }
+        data
+    }
+
+    fn drain(chunk: &mut RowChunk<Vec<u8>>) -> Result<Vec<Vec<Value>>, TalonError> {
+        std::iter::from_fn(|| chunk.next_row()).collect()
+    }
+
+    #[test]
+    fn row_chunks_decode_in_order() {
+        let first = vec![
+            vec![Value::Integer(1), Value::Text("a".into())],
+            vec![Value::Integer(2), Value::Null],
+        ];
+        let second = vec![vec![Value::Integer(3), Value::Blob(vec![0, 1])]];
+        let mut rows = Vec::new();
+        for page in [&first, &second] {
+            let mut chunk = RowChunk::new(chunk_bytes(page)).unwrap();
+            rows.extend(drain(&mut chunk).unwrap());
+            assert!(chunk.next_row().is_none());
+        }
+        assert_eq!(rows, [first, second].concat());
+    }
+
+    #[test]
+    fn empty_row_chunk_yields_nothing() {
+        let mut chunk = RowChunk::new(chunk_bytes(&[])).unwrap();
+        assert_eq!(chunk.rows_left, 0);
+        assert!(chunk.next_row().is_none());
+    }
+
+    #[test]
+    fn truncated_row_chunk_is_an_error() {
+        assert!(RowChunk::new(vec![1, 0, 0, 0]).is_err());
+
+        let data = chunk_bytes(&[vec![Value::Integer(1)], vec![Value::Text("hello".into())]]);
+        let mut chunk = RowChunk::new(data[..data.len() - 2].to_vec()).unwrap();
+        assert_eq!(chunk.next_row().unwrap().unwrap(), vec![Value::Integer(1)]);
+        assert!(chunk.next_row().unwrap().is_err());
+        // 出错后不再继续解码。
+        assert!(chunk.next_row().is_none());
+    }
+}
@@ -0,0 +1,314 @@
+/*
+ * Copyright (c) 2026 Talon Contributors
+ * Author: dark.lijin@gmail.com
+ * Licensed under the Talon Community Dual License Agreement.
+ * See the LICENSE file in the project root for full license information.
+ */
+//! EXPLAIN — 查询计划树。
+//!
+//! 嵌入式经 `talon_explain`（二进制参数），远程经
+//! `{"module":"sql","action":"explain","params":{"sql","analyze"}}`，两者返回同一 JSON：
+//!
+//! ```text
+//! {"total_time_us": 120,
+//!  "plan": {"operator", "detail", "table", "index", "estimated_rows", "actual_rows",
+//!           "time_us", "children": [...]}}
+//! ```
+//!
+//! `explain` 只估算不执行，`actual_rows` / `time` 为 `None`；`explain_analyze`
+//! 真实执行查询并填充实际行数与各算子耗时（含子算子）。
+
+use std::ffi::{CStr, CString};
+use std::fmt;
+use std::ptr;
+use std::time::Duration;
+
+use serde::Deserialize;
+
+use crate::{
+    encode_params, ffi_error, inline_sql_params, raw_ffi, remote_error, remote_response_data,
+    Params, Talon, TalonError, TalonRemoteClient, TalonRemoteErrorKind,
+};
+
+/// 查询计划。
+#[derive(Debug, Clone, PartialEq)]
+pub struct QueryPlan {
+    pub root: PlanNode,
+    /// 是否为 `explain_analyze` 的实测结果。
+    pub analyzed: bool,
+    /// 整体执行耗时（仅 analyze）。
+    pub total_time: Option<Duration>,
+}
+
+/// 计划树中的一个算子。
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct PlanNode {
+    /// 算子名（如 `SeqScan`、`IndexScan`、`Filter`、`HashJoin`）。
+    pub operator: String,
+    /// 算子细节（过滤条件、连接键等）。
+    pub detail: Option<String>,
+    pub table: Option<String>,
+    /// 使用的索引名。
+    pub index: Option<String>,
+    pub estimated_rows: Option<u64>,
+    pub actual_rows: Option<u64>,
+    /// 算子耗时（含子算子，仅 analyze）。
+    pub time: Option<Duration>,
+    pub children: Vec<PlanNode>,
+}
+
+impl QueryPlan {
+    /// 深度优先遍历所有算子（先根）。
+    pub fn nodes(&self) -> Vec<&PlanNode> {
+        let mut out = Vec::new();
+        let mut stack = vec![&self.root];
+        while let Some(node) = stack.pop() {
+            out.push(node);
+            stack.extend(node.children.iter().rev());
+        }
+        out
+    }
+
+    /// 计划中使用到的索引名（去重，按出现顺序）。
+    pub fn indexes_used(&self) -> Vec<&str> {
+        let mut out: Vec<&str> = Vec::new();
+        for name in self.nodes().into_iter().filter_map(|n| n.index.as_deref()) {
+            if !out.contains(&name) {
+                out.push(name);
+            }
+        }
+        out
+    }
+}
+
+/// 以缩进树的形式输出，类似 `EXPLAIN` 的文本结果。
+impl fmt::Display for QueryPlan {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.root.fmt_tree(f, 0)?;
+        if let Some(total) = self.total_time {
+            write!(f, "Total: {total:?}")?;
+        }
+        Ok(())
+    }
+}
+
+impl PlanNode {
+    fn fmt_tree(&self, f: &mut fmt::Formatter<'_>, depth: usize) -> fmt::Result {
+        write!(f, "{:indent$}{}", "", self.operator, indent = depth * 2)?;
+        if let Some(table) = &self.table {
+            write!(f, " on {table}")?;
+        }
+        if let Some(index) = &self.index {
+            write!(f, " using {index}")?;
+        }
+        if let Some(detail) = &self.detail {
+            write!(f, " ({detail})")?;
+        }
+        if let Some(rows) = self.estimated_rows {
+            write!(f, " est={rows}")?;
+        }
+        if let Some(rows) = self.actual_rows {
+            write!(f, " actual={rows}")?;
+        }
+        if let Some(time) = self.time {
+            write!(f, " time={time:?}")?;
+        }
+        writeln!(f)?;
+        for child in &self.children {
+            child.fmt_tree(f, depth + 1)?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Deserialize)]
+struct RawPlan {
+    plan: RawNode,
+    total_time_us: Option<u64>,
+}
+
+#[derive(Deserialize)]
+struct RawNode {
+    operator: String,
+    detail: Option<String>,
+    table: Option<String>,
+    index: Option<String>,
+    estimated_rows: Option<u64>,
+    actual_rows: Option<u64>,
+    time_us: Option<u64>,
+    #[serde(default)]
+    children: Vec<RawNode>,
+}
+
+impl From<RawNode> for PlanNode {
+    fn from(raw: RawNode) -> Self {
+        PlanNode {
+            operator: raw.operator,
+            detail: raw.detail,
+            table: raw.table,
+            index: raw.index,
+            estimated_rows: raw.estimated_rows,
+            actual_rows: raw.actual_rows,
+            time: raw.time_us.map(Duration::from_micros),
+            children: raw.children.into_iter().map(PlanNode::from).collect(),
+        }
+    }
+}
+
+fn parse_plan(data: &serde_json::Value, analyzed: bool) -> Result<QueryPlan, TalonError> {
+    let raw = RawPlan::deserialize(data)
+        .map_err(|e| TalonError(format!("invalid explain response: {e}")))?;
+    Ok(QueryPlan {
+        root: raw.plan.into(),
+        analyzed,
+        total_time: raw.total_time_us.map(Duration::from_micros),
+    })
+}
+
+impl Talon {
+    /// 返回查询计划（不执行查询）。
+    pub fn explain(&self, sql: &str, params: impl Into<Params>) -> Result<QueryPlan, TalonError> {
+        self.raw_explain(sql, params.into(), false)
+    }
+
+    /// 执行查询并返回带实际行数与各算子耗时的计划。
+    pub fn explain_analyze(
+        &self,
+        sql: &str,
+        params: impl Into<Params>,
+    ) -> Result<QueryPlan, TalonError> {
+        self.raw_explain(sql, params.into(), true)
+    }
+
+    fn raw_explain(
+        &self,
+        sql: &str,
+        params: Params,
+        analyze: bool,
+    ) -> Result<QueryPlan, TalonError> {
+        let (sql, params) = params.bind(sql)?;
+        let c_sql = CString::new(&*sql)?;
+        let params_bin = encode_params(&params);
+        let mut out: *mut std::os::raw::c_char = ptr::null_mut();
+        let rc = unsafe {
+            raw_ffi::talon_explain(
+                self.handle,
+                c_sql.as_ptr(),
+                params_bin.as_ptr(),
+                params_bin.len(),
+                i32::from(analyze),
+                &mut out,
+            )
+        };
+        if rc != 0 {
+            return Err(ffi_error("explain"));
+        }
+        if out.is_null() {
+            return Err(TalonError("explain returned null output".into()));
+        }
+        let json_str = unsafe { CStr::from_ptr(out).to_string_lossy().into_owned() };
+        unsafe { raw_ffi::talon_free_string(out) };
+        let data: serde_json::Value =
+            serde_json::from_str(&json_str).map_err(|e| TalonError(format!("JSON parse: {e}")))?;
+        parse_plan(&data, analyze)
+    }
+}
+
+impl TalonRemoteClient {
+    /// Return the query plan without running the query.
+    pub fn explain(&self, sql: &str, params: impl Into<Params>) -> Result<QueryPlan, TalonError> {
+        self.remote_explain(sql, params.into(), false)
+    }
+
+    /// Run the query and return the plan with actual row counts and
+    /// per-operator timings.
+    pub fn explain_analyze(
+        &self,
+        sql: &str,
+        params: impl Into<Params>,
+    ) -> Result<QueryPlan, TalonError> {
+        self.remote_explain(sql, params.into(), true)
+    }
+
+    fn remote_explain(
+        &self,
+        sql: &str,
+        params: Params,
+        analyze: bool,
+    ) -> Result<QueryPlan, TalonError> {
+        let (sql, params) = params.bind(sql)?;
+        let rendered;
+        let sql = if params.is_empty() {
+            &*sql
+        } else {
+            rendered = inline_sql_params(&sql, &params)?;
+            &rendered
+        };
+        let resp = self.exec_cmd_json(&serde_json::json!({
+            "module": "sql",
+            "action": "explain",
+            "params": { "sql": sql, "analyze": analyze }
+        }))?;
+        let data = remote_response_data(&resp)?.ok_or_else(|| {
+            remote_error(
+                TalonRemoteErrorKind::Protocol,
+                format!("explain response missing data: {resp}"),
+            )
+        })?;
+        parse_plan(data, analyze)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn analyzed() -> QueryPlan {
+        let data = serde_json::json!({
+            "total_time_us": 1500,
+            "plan": {
+                "operator": "HashJoin",
+                "detail": "u.id = o.user_id",
+                "estimated_rows": 100,
+                "actual_rows": 80,
+                "time_us": 1400,
+                "children": [
+                    {"operator": "IndexScan", "table": "users", "index": "idx_age",
+                     "detail": "age > 30", "estimated_rows": 50, "actual_rows": 40, "time_us": 300},
+                    {"operator": "SeqScan", "table": "orders", "estimated_rows": 1000,
+                     "actual_rows": 1000, "time_us": 900}
+                ]
+            }
+        });
+        parse_plan(&data, true).unwrap()
+    }
+
+    #[test]
+    fn plan_tree_is_typed() {
+        let plan = analyzed();
+        assert!(plan.analyzed);
+        assert_eq!(plan.total_time, Some(Duration::from_micros(1500)));
+        let ops: Vec<&str> = plan.nodes().iter().map(|n| n.operator.as_str()).collect();
+        assert_eq!(ops, vec!["HashJoin", "IndexScan", "SeqScan"]);
+        assert_eq!(plan.indexes_used(), vec!["idx_age"]);
+        assert_eq!(plan.root.children[0].actual_rows, Some(40));
+    }
+
+    #[test]
+    fn plan_renders_as_indented_tree() {
+        let text = analyzed().to_string();
+        let lines: Vec<&str> = text.lines().collect();
+        assert!(lines[0].starts_with("HashJoin (u.id = o.user_id) est=100 actual=80"));
+        assert!(lines[1].starts_with("  IndexScan on users using idx_age (age > 30)"));
+        assert_eq!(lines[3], "Total: 1.5ms");
+    }
+
+    #[test]
+    fn estimate_only_plan_has_no_timings() {
+        let data = serde_json::json!({"plan": {"operator": "SeqScan", "table": "t"}});
+        let plan = parse_plan(&data, false).unwrap();
+        assert_eq!(plan.root.time, None);
+        assert_eq!(plan.total_time, None);
+        assert!(parse_plan(&serde_json::json!({}), false).is_err());
+    }
+}
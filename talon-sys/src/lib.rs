@@ -9,17 +9,20 @@
 //! Provides a source-compatible API with the native `talon` crate via C FFI,
 //! so downstream crates (`superclaw-db`) work without code changes.
 
-use std::collections::BTreeMap;
+use std::collections::{BTreeMap, VecDeque};
 use std::ffi::{CStr, CString};
 use std::fmt;
 use std::io::{Read, Write};
 use std::net::{TcpStream, ToSocketAddrs};
+use std::ops::{Bound, RangeBounds};
 use std::path::Path;
 use std::ptr;
 use std::slice;
-use std::sync::Mutex;
+use std::sync::{Mutex, PoisonError};
 use std::time::Duration;
 
+use base64::engine::general_purpose::STANDARD as BASE64;
+use base64::Engine as _;
 use serde::{Deserialize, Serialize};
 
 // ── Value 枚举（与源码 talon::Value serde 格式一致）─────────────────────────
@@ -79,6 +82,8 @@ pub enum TalonRemoteErrorKind {
     Protocol,
     Server,
     Io,
+    /// Transaction serialization conflict (`"code":"txn_conflict"`, v0.2.0+).
+    Conflict,
 }
 
 impl TalonRemoteErrorKind {
@@ -92,6 +97,7 @@ impl TalonRemoteErrorKind {
             TalonRemoteErrorKind::Protocol => "protocol",
             TalonRemoteErrorKind::Server => "server",
             TalonRemoteErrorKind::Io => "io",
+            TalonRemoteErrorKind::Conflict => "conflict",
         }
     }
 }
@@ -138,6 +144,8 @@ pub struct TalonRemoteClient {
     auth_token: Option<String>,
     timeout: Duration,
     stream: Mutex<TcpStream>,
+    kv_encoding: OnceLock<RemoteKvEncoding>,
+    stmt_cache: Mutex<RemoteStmtCache>,
 }
 
 impl TalonRemoteClient {
@@ -159,6 +167,8 @@ impl TalonRemoteClient {
             auth_token: parsed.auth_token,
             timeout: parsed.timeout,
             stream: Mutex::new(stream),
+            kv_encoding: OnceLock::new(),
+            stmt_cache: Mutex::new(RemoteStmtCache::new(DEFAULT_STMT_CACHE_CAPACITY)),
         })
     }
 
@@ -182,40 +192,52 @@ impl TalonRemoteClient {
         self.auth_token.is_some()
     }
 
-    /// Execute SQL remotely and return rows using the same `Value` shape as embedded mode.
-    pub fn run_sql(&self, sql: &str) -> Result<Vec<Vec<Value>>, TalonError> {
+    /// Whether the server accepts base64 KV payloads, i.e. arbitrary
+    /// (non-UTF-8) keys and values round-trip exactly as in embedded mode.
+    ///
+    /// Negotiated with the server on first use and cached for this client.
+    pub fn supports_binary_kv(&self) -> Result<bool, TalonError> {
+        Ok(self.kv_encoding()? == RemoteKvEncoding::Base64)
+    }
+
+    fn kv_encoding(&self) -> Result<RemoteKvEncoding, TalonError> {
+        if let Some(enc) = self.kv_encoding.get() {
+            return Ok(*enc);
+        }
         let cmd = serde_json::json!({
-            "module": "sql",
-            "action": "",
-            "params": { "sql": sql }
+            "module": "server",
+            "action": "capabilities",
+            "params": {}
         });
         let resp = self.exec_cmd_json(&cmd)?;
-        let data = remote_response_data(&resp)?.ok_or_else(|| {
-            remote_error(
-                TalonRemoteErrorKind::Protocol,
-                format!("SQL response missing data: {resp}"),
-            )
-        })?;
-        let rows = data.get("rows").and_then(|r| r.as_array()).ok_or_else(|| {
-            remote_error(
-                TalonRemoteErrorKind::Protocol,
-                format!("SQL response missing rows array: {resp}"),
-            )
-        })?;
-        rows.iter()
-            .map(|row| {
-                row.as_array()
+        let enc = match remote_response_data(&resp) {
+            Ok(data) => {
+                let encodings = data
+                    .and_then(|d| d.get("kv_encodings"))
+                    .and_then(|e| e.as_array())
                     .ok_or_else(|| {
                         remote_error(
                             TalonRemoteErrorKind::Protocol,
-                            format!("SQL row is not an array: {row}"),
+                            "server/capabilities reply is missing kv_encodings",
                         )
-                    })?
-                    .iter()
-                    .map(talon_value_from_json)
-                    .collect()
-            })
-            .collect()
+                    })?;
+                if encodings.iter().any(|v| v == "base64") {
+                    RemoteKvEncoding::Base64
+                } else {
+                    RemoteKvEncoding::Utf8
+                }
+            }
+            // Servers predating negotiation reject the command: keep UTF-8 strings.
+            Err(_) if is_unsupported_command_reply(&resp) => RemoteKvEncoding::Utf8,
+            // Anything else (auth, transient server errors) is not cached.
+            Err(e) => return Err(e),
+        };
+        Ok(*self.kv_encoding.get_or_init(|| enc))
+    }
+
+    /// Execute SQL remotely and return rows using the same `Value` shape as embedded mode.
+    pub fn run_sql(&self, sql: &str) -> Result<Vec<Vec<Value>>, TalonError> {
+        Ok(self.query(sql, &[])?.rows)
     }
 
     /// Execute parameterized SQL remotely.
@@ -256,6 +278,14 @@ impl TalonRemoteClient {
         Ok(RemoteMqEngine { client: self })
     }
 
+    /// Get a remote vector client surface for `index`.
+    pub fn vector(&self, index: &str) -> Result<RemoteVectorEngine<'_>, TalonError> {
+        Ok(RemoteVectorEngine {
+            client: self,
+            index: index.to_string(),
+        })
+    }
+
     /// Execute a raw JSON command against the remote server.
     pub fn exec_cmd_json(&self, cmd: &serde_json::Value) -> Result<serde_json::Value, TalonError> {
         let payload = serde_json::to_vec(cmd)
@@ -275,6 +305,22 @@ impl TalonRemoteClient {
         remote_response_data(&resp)?;
         Ok(())
     }
+
+    /// Open an extra authenticated connection to the same endpoint, used by
+    /// long-lived server-push subscriptions.
+    fn open_side_stream(&self) -> Result<TcpStream, TalonError> {
+        let endpoint = RemoteEndpoint {
+            endpoint: self.endpoint.clone(),
+            addr: self.addr.clone(),
+            auth_token: self.auth_token.clone(),
+            timeout: self.timeout,
+        };
+        let mut stream = connect_remote_stream(&endpoint)?;
+        if let Some(token) = endpoint.auth_token.as_deref() {
+            authenticate_remote_stream(&mut stream, token)?;
+        }
+        Ok(stream)
+    }
 }
 
 /// Remote KV engine wrapper.
@@ -285,12 +331,9 @@ pub struct RemoteKvEngine<'a> {
 impl<'a> RemoteKvEngine<'a> {
     /// Read a key from remote KV.
     pub fn get(&self, key: &[u8]) -> Result<Option<Vec<u8>>, TalonError> {
-        let key = remote_utf8("KV key", key)?;
-        let cmd = serde_json::json!({
-            "module": "kv",
-            "action": "get",
-            "params": { "key": key }
-        });
+        let enc = self.client.kv_encoding()?;
+        let key = enc.encode("KV key", key)?;
+        let cmd = kv_cmd(enc, "get", serde_json::json!({ "key": key }));
         let resp = self.client.exec_cmd_json(&cmd)?;
         let data = remote_response_data(&resp)?.ok_or_else(|| {
             remote_error(
@@ -298,50 +341,454 @@ impl<'a> RemoteKvEngine<'a> {
                 format!("KV get response missing data: {resp}"),
             )
         })?;
-        match data.get("value") {
-            Some(v) if v.is_null() => Ok(None),
-            Some(v) => v
-                .as_str()
-                .map(|s| Some(s.as_bytes().to_vec()))
-                .ok_or_else(|| {
-                    remote_error(
-                        TalonRemoteErrorKind::Protocol,
-                        format!("KV get value is not a string/null: {resp}"),
-                    )
-                }),
-            None => Err(remote_error(
-                TalonRemoteErrorKind::Protocol,
-                format!("KV get response missing value: {resp}"),
-            )),
-        }
+        remote_kv_value(enc, "get", data.get("value"), &resp)
     }
 
-    /// Write a remote KV value. The server JSON protocol stores string payloads.
+    /// Write a remote KV value.
+    ///
+    /// Non-UTF-8 keys/values require a server that negotiates binary KV
+    /// payloads (see [`TalonRemoteClient::supports_binary_kv`]).
     pub fn set(&self, key: &[u8], value: &[u8], ttl_secs: Option<u64>) -> Result<(), TalonError> {
-        let key = remote_utf8("KV key", key)?;
-        let value = remote_utf8("KV value", value)?;
+        let enc = self.client.kv_encoding()?;
+        let key = enc.encode("KV key", key)?;
+        let value = enc.encode("KV value", value)?;
         let mut params = serde_json::json!({ "key": key, "value": value });
         if let Some(ttl) = ttl_secs {
             params["ttl"] = serde_json::json!(ttl);
         }
-        let cmd = serde_json::json!({
-            "module": "kv",
-            "action": "set",
-            "params": params
-        });
+        let cmd = kv_cmd(enc, "set", params);
         self.client.exec_cmd(&cmd)
     }
 
     /// Delete a remote KV key.
     pub fn del(&self, key: &[u8]) -> Result<(), TalonError> {
-        let key = remote_utf8("KV key", key)?;
-        let cmd = serde_json::json!({
-            "module": "kv",
-            "action": "del",
-            "params": { "key": key }
-        });
+        let enc = self.client.kv_encoding()?;
+        let key = enc.encode("KV key", key)?;
+        let cmd = kv_cmd(enc, "del", serde_json::json!({ "key": key }));
+        self.client.exec_cmd(&cmd)
+    }
+
+    /// Atomically add `delta` to a remote counter and return the new value.
+    pub fn incr_by(&self, key: &[u8], delta: i64) -> Result<i64, TalonError> {
+        let enc = self.client.kv_encoding()?;
+        let key = enc.encode("KV key", key)?;
+        let cmd = kv_cmd(
+            enc,
+            "incrby",
+            serde_json::json!({ "key": key, "delta": delta }),
+        );
+        let resp = self.client.exec_cmd_json(&cmd)?;
+        remote_response_data(&resp)?
+            .and_then(|d| d.get("value"))
+            .and_then(|v| v.as_i64())
+            .ok_or_else(|| {
+                remote_error(
+                    TalonRemoteErrorKind::Protocol,
+                    format!("KV incrby response missing integer value: {resp}"),
+                )
+            })
+    }
+
+    /// Atomically subtract `delta` from a remote counter and return the new value.
+    pub fn decr_by(&self, key: &[u8], delta: i64) -> Result<i64, TalonError> {
+        let delta = delta
+            .checked_neg()
+            .ok_or_else(|| TalonError(format!("KV decr_by delta out of range: {delta}")))?;
+        self.incr_by(key, delta)
+    }
+
+    /// Write a remote KV value only if the key does not exist yet.
+    ///
+    /// Returns `true` when the value was written.
+    pub fn set_nx(
+        &self,
+        key: &[u8],
+        value: &[u8],
+        ttl_secs: Option<u64>,
+    ) -> Result<bool, TalonError> {
+        let enc = self.client.kv_encoding()?;
+        let key = enc.encode("KV key", key)?;
+        let value = enc.encode("KV value", value)?;
+        let mut params = serde_json::json!({ "key": key, "value": value });
+        if let Some(ttl) = ttl_secs {
+            params["ttl"] = serde_json::json!(ttl);
+        }
+        let cmd = kv_cmd(enc, "setnx", params);
+        let resp = self.client.exec_cmd_json(&cmd)?;
+        remote_response_data(&resp)?
+            .and_then(|d| d.get("was_set"))
+            .and_then(|v| v.as_bool())
+            .ok_or_else(|| {
+                remote_error(
+                    TalonRemoteErrorKind::Protocol,
+                    format!("KV setnx response missing was_set: {resp}"),
+                )
+            })
+    }
+
+    /// Write a remote KV value and return the previous value.
+    pub fn get_set(&self, key: &[u8], value: &[u8]) -> Result<Option<Vec<u8>>, TalonError> {
+        let enc = self.client.kv_encoding()?;
+        let key = enc.encode("KV key", key)?;
+        let value = enc.encode("KV value", value)?;
+        let cmd = kv_cmd(
+            enc,
+            "getset",
+            serde_json::json!({ "key": key, "value": value }),
+        );
+        let resp = self.client.exec_cmd_json(&cmd)?;
+        let data = remote_response_data(&resp)?.ok_or_else(|| {
+            remote_error(
+                TalonRemoteErrorKind::Protocol,
+                format!("KV getset response missing data: {resp}"),
+            )
+        })?;
+        remote_kv_value(enc, "getset", data.get("value"), &resp)
+    }
+
+    /// Remote compare-and-swap.
+    ///
+    /// `expected = None` requires the key to be absent; `new = None` deletes
+    /// the key when the comparison succeeds. Returns `true` when swapped.
+    pub fn compare_and_swap(
+        &self,
+        key: &[u8],
+        expected: Option<&[u8]>,
+        new: Option<&[u8]>,
+        ttl_secs: Option<u64>,
+    ) -> Result<bool, TalonError> {
+        let enc = self.client.kv_encoding()?;
+        let key = enc.encode("KV key", key)?;
+        let expected = expected
+            .map(|v| enc.encode("KV expected value", v))
+            .transpose()?;
+        let new = new.map(|v| enc.encode("KV value", v)).transpose()?;
+        let mut params = serde_json::json!({ "key": key, "expected": expected, "new": new });
+        if let Some(ttl) = ttl_secs {
+            params["ttl"] = serde_json::json!(ttl);
+        }
+        let cmd = kv_cmd(enc, "cas", params);
+        let resp = self.client.exec_cmd_json(&cmd)?;
+        remote_response_data(&resp)?
+            .and_then(|d| d.get("swapped"))
+            .and_then(|v| v.as_bool())
+            .ok_or_else(|| {
+                remote_error(
+                    TalonRemoteErrorKind::Protocol,
+                    format!("KV cas response missing swapped: {resp}"),
+                )
+            })
+    }
+
+    /// Remaining TTL of a remote key; `None` when missing or persistent.
+    pub fn ttl(&self, key: &[u8]) -> Result<Option<Duration>, TalonError> {
+        let enc = self.client.kv_encoding()?;
+        let key = enc.encode("KV key", key)?;
+        let cmd = kv_cmd(enc, "ttl", serde_json::json!({ "key": key }));
+        let resp = self.client.exec_cmd_json(&cmd)?;
+        let ttl_ms = remote_response_data(&resp)?
+            .and_then(|d| d.get("ttl_ms"))
+            .and_then(|v| v.as_i64())
+            .ok_or_else(|| {
+                remote_error(
+                    TalonRemoteErrorKind::Protocol,
+                    format!("KV ttl response missing ttl_ms: {resp}"),
+                )
+            })?;
+        kv_ttl_from_millis(ttl_ms).map_err(|e| remote_error(TalonRemoteErrorKind::Protocol, e.0))
+    }
+
+    /// Reset the TTL of a remote key without rewriting its value.
+    ///
+    /// Returns `false` when the key does not exist.
+    pub fn expire(&self, key: &[u8], ttl: Duration) -> Result<bool, TalonError> {
+        let enc = self.client.kv_encoding()?;
+        let key = enc.encode("KV key", key)?;
+        let ttl_ms = kv_ttl_millis(ttl)?;
+        let cmd = kv_cmd(
+            enc,
+            "expire",
+            serde_json::json!({ "key": key, "ttl_ms": ttl_ms }),
+        );
+        self.exec_updated("expire", &cmd)
+    }
+
+    /// Expire a remote key at an absolute Unix timestamp (seconds).
+    pub fn expire_at(&self, key: &[u8], unix_ts: u64) -> Result<bool, TalonError> {
+        let enc = self.client.kv_encoding()?;
+        let key = enc.encode("KV key", key)?;
+        let cmd = kv_cmd(
+            enc,
+            "expire_at",
+            serde_json::json!({ "key": key, "unix_ts": unix_ts }),
+        );
+        self.exec_updated("expire_at", &cmd)
+    }
+
+    /// Remove the TTL of a remote key. Returns `true` when a TTL was removed.
+    pub fn persist(&self, key: &[u8]) -> Result<bool, TalonError> {
+        let enc = self.client.kv_encoding()?;
+        let key = enc.encode("KV key", key)?;
+        let cmd = kv_cmd(enc, "persist", serde_json::json!({ "key": key }));
+        self.exec_updated("persist", &cmd)
+    }
+
+    fn exec_updated(&self, op: &str, cmd: &serde_json::Value) -> Result<bool, TalonError> {
+        let resp = self.client.exec_cmd_json(cmd)?;
+        remote_response_data(&resp)?
+            .and_then(|d| d.get("updated"))
+            .and_then(|v| v.as_bool())
+            .ok_or_else(|| {
+                remote_error(
+                    TalonRemoteErrorKind::Protocol,
+                    format!("KV {op} response missing updated: {resp}"),
+                )
+            })
+    }
+
+    /// Subscribe to changes of remote keys under `prefix`.
+    ///
+    /// The subscription runs on a dedicated connection: the server acks the
+    /// `watch` command and then pushes `{"ok":true,"data":{"events":[...]}}`
+    /// frames, so regular commands on this client are not interleaved.
+    pub fn watch(&self, prefix: &[u8]) -> Result<KvWatcher<'static>, TalonError> {
+        let enc = self.client.kv_encoding()?;
+        let prefix = enc.encode("KV watch prefix", prefix)?;
+        let mut stream = self.client.open_side_stream()?;
+        let cmd = kv_cmd(enc, "watch", serde_json::json!({ "prefix": prefix }));
+        let payload = serde_json::to_vec(&cmd)
+            .map_err(|e| remote_error(TalonRemoteErrorKind::Protocol, format!("encode: {e}")))?;
+        write_remote_frame(&mut stream, &payload)?;
+        let frame = read_remote_frame(&mut stream)?;
+        let resp: serde_json::Value = serde_json::from_slice(&frame)
+            .map_err(|e| remote_error(TalonRemoteErrorKind::Protocol, format!("decode: {e}")))?;
+        remote_response_data(&resp)?;
+        Ok(KvWatcher {
+            source: KvWatchSource::Remote {
+                stream,
+                timeout: self.client.timeout,
+                encoding: enc,
+            },
+            buf: VecDeque::new(),
+            closed: false,
+        })
+    }
+
+    /// Scan remote keys sharing `prefix`, fetching pages lazily.
+    pub fn scan_prefix(&self, prefix: &[u8]) -> KvScan<'a> {
+        let end = kv_prefix_end(prefix);
+        self.scan_bounds(prefix.to_vec(), end)
+    }
+
+    /// Scan a remote key range, e.g. `kv.scan_range("user:a".."user:m")`.
+    pub fn scan_range<K: AsRef<[u8]>>(&self, range: impl RangeBounds<K>) -> KvScan<'a> {
+        let (start, end) = kv_range_bounds(&range);
+        self.scan_bounds(start, end)
+    }
+
+    /// Fetch one page of `[start, end)` resuming after `cursor`.
+    pub fn scan_page(
+        &self,
+        start: &[u8],
+        end: Option<&[u8]>,
+        cursor: Option<&[u8]>,
+        limit: usize,
+    ) -> Result<KvScanPage, TalonError> {
+        remote_kv_scan(self.client, start, end, cursor, limit)
+    }
+
+    /// Read many remote keys in one command; results follow `keys` order.
+    pub fn get_many<K: AsRef<[u8]>>(&self, keys: &[K]) -> Result<Vec<Option<Vec<u8>>>, TalonError> {
+        if keys.is_empty() {
+            return Ok(vec![]);
+        }
+        let enc = self.client.kv_encoding()?;
+        let keys = keys
+            .iter()
+            .map(|k| enc.encode("KV key", k.as_ref()))
+            .collect::<Result<Vec<_>, _>>()?;
+        let cmd = kv_cmd(enc, "mget", serde_json::json!({ "keys": keys }));
+        let resp = self.client.exec_cmd_json(&cmd)?;
+        let values = remote_response_data(&resp)?
+            .and_then(|d| d.get("values"))
+            .and_then(|v| v.as_array())
+            .ok_or_else(|| {
+                remote_error(
+                    TalonRemoteErrorKind::Protocol,
+                    format!("KV mget response missing values array: {resp}"),
+                )
+            })?;
+        if values.len() != keys.len() {
+            return Err(remote_error(
+                TalonRemoteErrorKind::Protocol,
+                format!(
+                    "KV mget returned {} values for {} keys",
+                    values.len(),
+                    keys.len()
+                ),
+            ));
+        }
+        values
+            .iter()
+            .map(|v| remote_kv_value(enc, "mget", Some(v), &resp))
+            .collect()
+    }
+
+    /// Write many remote `(key, value, ttl_secs)` entries in one command.
+    ///
+    /// With `atomic = true` the server applies all writes or none.
+    pub fn set_many<K: AsRef<[u8]>, V: AsRef<[u8]>>(
+        &self,
+        entries: &[(K, V, Option<u64>)],
+        atomic: bool,
+    ) -> Result<(), TalonError> {
+        if entries.is_empty() {
+            return Ok(());
+        }
+        let enc = self.client.kv_encoding()?;
+        let entries = entries
+            .iter()
+            .map(|(key, value, ttl_secs)| {
+                Ok(serde_json::json!({
+                    "key": enc.encode("KV key", key.as_ref())?,
+                    "value": enc.encode("KV value", value.as_ref())?,
+                    "ttl": ttl_secs,
+                }))
+            })
+            .collect::<Result<Vec<_>, TalonError>>()?;
+        let cmd = kv_cmd(
+            enc,
+            "mset",
+            serde_json::json!({ "entries": entries, "atomic": atomic }),
+        );
         self.client.exec_cmd(&cmd)
     }
+
+    /// Delete many remote keys in one command and return how many existed.
+    pub fn del_many<K: AsRef<[u8]>>(&self, keys: &[K]) -> Result<u64, TalonError> {
+        if keys.is_empty() {
+            return Ok(0);
+        }
+        let enc = self.client.kv_encoding()?;
+        let keys = keys
+            .iter()
+            .map(|k| enc.encode("KV key", k.as_ref()))
+            .collect::<Result<Vec<_>, _>>()?;
+        let cmd = kv_cmd(enc, "mdel", serde_json::json!({ "keys": keys }));
+        let resp = self.client.exec_cmd_json(&cmd)?;
+        remote_response_data(&resp)?
+            .and_then(|d| d.get("deleted"))
+            .and_then(|v| v.as_u64())
+            .ok_or_else(|| {
+                remote_error(
+                    TalonRemoteErrorKind::Protocol,
+                    format!("KV mdel response missing deleted: {resp}"),
+                )
+            })
+    }
+
+    fn scan_bounds(&self, start: Vec<u8>, end: Option<Vec<u8>>) -> KvScan<'a> {
+        let client = self.client;
+        KvScan::new(Box::new(move |cursor, limit| {
+            remote_kv_scan(client, &start, end.as_deref(), cursor, limit)
+        }))
+    }
+}
+
+fn remote_kv_event(
+    enc: RemoteKvEncoding,
+    event: &serde_json::Value,
+) -> Result<KvEvent, TalonError> {
+    let key = event.get("key").and_then(|k| k.as_str()).ok_or_else(|| {
+        remote_error(
+            TalonRemoteErrorKind::Protocol,
+            format!("KV event missing key: {event}"),
+        )
+    })?;
+    let kind = event
+        .get("kind")
+        .and_then(|k| k.as_str())
+        .and_then(KvEventKind::parse)
+        .ok_or_else(|| {
+            remote_error(
+                TalonRemoteErrorKind::Protocol,
+                format!("KV event has invalid kind: {event}"),
+            )
+        })?;
+    let value = event
+        .get("value")
+        .and_then(|v| v.as_str())
+        .map(|v| enc.decode("KV event value", v))
+        .transpose()?;
+    Ok(KvEvent {
+        key: enc.decode("KV event key", key)?,
+        kind,
+        value,
+    })
+}
+
+fn remote_kv_scan(
+    client: &TalonRemoteClient,
+    start: &[u8],
+    end: Option<&[u8]>,
+    cursor: Option<&[u8]>,
+    limit: usize,
+) -> Result<KvScanPage, TalonError> {
+    let enc = client.kv_encoding()?;
+    let start = enc.encode("KV scan start", start)?;
+    let end = end.map(|e| enc.encode("KV scan end", e)).transpose()?;
+    let cursor = cursor
+        .map(|c| enc.encode("KV scan cursor", c))
+        .transpose()?;
+    let cmd = kv_cmd(
+        enc,
+        "scan",
+        serde_json::json!({ "start": start, "end": end, "cursor": cursor, "limit": limit }),
+    );
+    let resp = client.exec_cmd_json(&cmd)?;
+    let data = remote_response_data(&resp)?.ok_or_else(|| {
+        remote_error(
+            TalonRemoteErrorKind::Protocol,
+            format!("KV scan response missing data: {resp}"),
+        )
+    })?;
+    let entries = data
+        .get("entries")
+        .and_then(|e| e.as_array())
+        .ok_or_else(|| {
+            remote_error(
+                TalonRemoteErrorKind::Protocol,
+                format!("KV scan response missing entries array: {resp}"),
+            )
+        })?
+        .iter()
+        .map(|entry| {
+            let key = entry.get("key").and_then(|k| k.as_str()).ok_or_else(|| {
+                remote_error(
+                    TalonRemoteErrorKind::Protocol,
+                    format!("KV scan entry missing key: {entry}"),
+                )
+            })?;
+            let value =
+                remote_kv_value(enc, "scan", entry.get("value"), &resp)?.ok_or_else(|| {
+                    remote_error(
+                        TalonRemoteErrorKind::Protocol,
+                        format!("KV scan entry missing value: {entry}"),
+                    )
+                })?;
+            Ok((enc.decode("KV scan key", key)?, value))
+        })
+        .collect::<Result<Vec<_>, TalonError>>()?;
+    let next_cursor = data
+        .get("next_cursor")
+        .and_then(|c| c.as_str())
+        .filter(|c| !c.is_empty())
+        .map(|c| enc.decode("KV scan cursor", c))
+        .transpose()?;
+    Ok(KvScanPage {
+        entries,
+        next_cursor,
+    })
 }
 
 /// Remote MQ engine wrapper.
@@ -487,6 +934,91 @@ impl<'a> RemoteMqEngine<'a> {
     }
 }
 
+/// Remote vector engine wrapper.
+pub struct RemoteVectorEngine<'a> {
+    client: &'a TalonRemoteClient,
+    index: String,
+}
+
+impl<'a> RemoteVectorEngine<'a> {
+    /// Insert one embedding.
+    pub fn insert(&self, id: u64, embedding: &[f32]) -> Result<(), TalonError> {
+        let cmd = serde_json::json!({
+            "module": "vector",
+            "action": "insert",
+            "params": { "index": &self.index, "id": id, "vector": embedding }
+        });
+        self.client.exec_cmd(&cmd)
+    }
+
+    /// Delete one embedding.
+    pub fn delete(&self, id: u64) -> Result<(), TalonError> {
+        let cmd = serde_json::json!({
+            "module": "vector",
+            "action": "delete",
+            "params": { "index": &self.index, "id": id }
+        });
+        self.client.exec_cmd(&cmd)
+    }
+
+    /// KNN search returning `(id, distance)` pairs.
+    pub fn search(
+        &self,
+        query: &[f32],
+        k: usize,
+        metric: &str,
+    ) -> Result<Vec<(u64, f32)>, TalonError> {
+        let cmd = serde_json::json!({
+            "module": "vector",
+            "action": "search",
+            "params": { "index": &self.index, "vector": query, "k": k, "metric": metric }
+        });
+        let resp = self.client.exec_cmd_json(&cmd)?;
+        let results = remote_response_data(&resp)?
+            .and_then(|d| d.get("results"))
+            .and_then(|r| r.as_array())
+            .ok_or_else(|| {
+                remote_error(
+                    TalonRemoteErrorKind::Protocol,
+                    format!("vector search response missing results: {resp}"),
+                )
+            })?;
+        results
+            .iter()
+            .map(|hit| {
+                let id = hit.get("id").and_then(|v| v.as_u64());
+                let distance = hit.get("distance").and_then(|v| v.as_f64());
+                match (id, distance) {
+                    (Some(id), Some(distance)) => Ok((id, distance as f32)),
+                    _ => Err(remote_error(
+                        TalonRemoteErrorKind::Protocol,
+                        format!("malformed vector search hit: {hit}"),
+                    )),
+                }
+            })
+            .collect()
+    }
+
+    /// Number of stored embeddings.
+    pub fn count(&self) -> Result<u64, TalonError> {
+        let cmd = serde_json::json!({
+            "module": "vector",
+            "action": "count",
+            "params": { "index": &self.index }
+        });
+        let resp = self.client.exec_cmd_json(&cmd)?;
+        remote_response_data(&resp)?
+            .and_then(|d| d.get("count"))
+            .and_then(|c| c.as_u64())
+            .ok_or_else(|| {
+                remote_error(
+                    TalonRemoteErrorKind::Protocol,
+                    format!("vector count response missing count: {resp}"),
+                )
+            })
+    }
+}
+
 fn parse_talon_remote_endpoint(
     endpoint: &str,
     default_timeout: Duration,
@@ -688,6 +1220,26 @@ fn read_remote_frame(stream: &mut TcpStream) -> Result<Vec<u8>, TalonError> {
     Ok(buf)
 }
 
+/// Error prefixes a server uses when it does not know a command at all.
+const UNSUPPORTED_COMMAND_ERRORS: [&str; 4] = [
+    "unknown module",
+    "unknown action",
+    "unknown command",
+    "unsupported",
+];
+
+/// Whether a failed reply says the server does not know the command, as
+/// opposed to failing while executing it.
+fn is_unsupported_command_reply(resp: &serde_json::Value) -> bool {
+    let Some(msg) = resp.get("error").and_then(|v| v.as_str()) else {
+        return false;
+    };
+    let msg = msg.to_ascii_lowercase();
+    UNSUPPORTED_COMMAND_ERRORS
+        .iter()
+        .any(|prefix| msg.starts_with(prefix))
+}
+
 fn remote_response_data(
     resp: &serde_json::Value,
 ) -> Result<Option<&serde_json::Value>, TalonError> {
@@ -700,6 +1252,8 @@ fn remote_response_data(
                 .unwrap_or("unknown remote server error");
             let kind = if msg == "auth failed" {
                 TalonRemoteErrorKind::Auth
+            } else if resp.get("code").and_then(|v| v.as_str()) == Some("txn_conflict") {
+                TalonRemoteErrorKind::Conflict
             } else {
                 TalonRemoteErrorKind::Server
             };
@@ -712,15 +1266,82 @@ fn remote_response_data(
     }
 }
 
-fn remote_utf8<'a>(field: &str, bytes: &'a [u8]) -> Result<&'a str, TalonError> {
-    std::str::from_utf8(bytes).map_err(|e| {
-        remote_error(
-            TalonRemoteErrorKind::Protocol,
-            format!("{field} must be valid UTF-8 for the current JSON TCP protocol: {e}"),
-        )
+/// How KV keys/values travel inside JSON commands, negotiated per client.
+///
+/// `Utf8` sends raw strings (servers without negotiation support);
+/// `Base64` tags params with `"encoding":"base64"` and the server answers
+/// with base64 strings as well, so arbitrary bytes round-trip.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum RemoteKvEncoding {
+    Utf8,
+    Base64,
+}
+
+impl RemoteKvEncoding {
+    fn encode(self, field: &str, bytes: &[u8]) -> Result<String, TalonError> {
+        match self {
+            RemoteKvEncoding::Base64 => Ok(BASE64.encode(bytes)),
+            RemoteKvEncoding::Utf8 => std::str::from_utf8(bytes)
+                .map(str::to_string)
+                .map_err(|e| {
+                    remote_error(
+                        TalonRemoteErrorKind::Protocol,
+                        format!(
+                            "{field} must be valid UTF-8: server does not support binary KV payloads: {e}"
+                        ),
+                    )
+                }),
+        }
+    }
+
+    fn decode(self, field: &str, text: &str) -> Result<Vec<u8>, TalonError> {
+        match self {
+            RemoteKvEncoding::Base64 => BASE64.decode(text).map_err(|e| {
+                remote_error(
+                    TalonRemoteErrorKind::Protocol,
+                    format!("{field} is not valid base64: {e}"),
+                )
+            }),
+            RemoteKvEncoding::Utf8 => Ok(text.as_bytes().to_vec()),
+        }
+    }
+}
+
+fn kv_cmd(enc: RemoteKvEncoding, action: &str, mut params: serde_json::Value) -> serde_json::Value {
+    if enc == RemoteKvEncoding::Base64 {
+        params["encoding"] = serde_json::json!("base64");
+    }
+    serde_json::json!({
+        "module": "kv",
+        "action": action,
+        "params": params
     })
 }
 
+fn remote_kv_value(
+    enc: RemoteKvEncoding,
+    op: &str,
+    value: Option<&serde_json::Value>,
+    resp: &serde_json::Value,
+) -> Result<Option<Vec<u8>>, TalonError> {
+    match value {
+        Some(v) if v.is_null() => Ok(None),
+        Some(v) => {
+            let text = v.as_str().ok_or_else(|| {
+                remote_error(
+                    TalonRemoteErrorKind::Protocol,
+                    format!("KV {op} value is not a string/null: {resp}"),
+                )
+            })?;
+            enc.decode(&format!("KV {op} value"), text).map(Some)
+        }
+        None => Err(remote_error(
+            TalonRemoteErrorKind::Protocol,
+            format!("KV {op} response missing value: {resp}"),
+        )),
+    }
+}
+
 fn talon_value_from_json(value: &serde_json::Value) -> Result<Value, TalonError> {
     if let Ok(v) = serde_json::from_value::<Value>(value.clone()) {
         return Ok(v);
@@ -812,6 +1433,306 @@ fn sql_literal(value: &Value) -> Result<String, TalonError> {
     }
 }
 
+// ── KV Scan 类型 ────────────────────────────────────────────────────────────
+
+/// KV 扫描默认分页大小。
+const KV_SCAN_PAGE_SIZE: usize = 1000;
+
+/// KV 扫描的一页结果。
+#[derive(Debug, Clone, Default)]
+pub struct KvScanPage {
+    /// 本页的 (key, value)，按 key 字节序升序。
+    pub entries: Vec<(Vec<u8>, Vec<u8>)>,
+    /// 续扫游标；`None` 表示已扫描完毕。
+    pub next_cursor: Option<Vec<u8>>,
+}
+
+type KvPageFetch<'a> = Box<dyn FnMut(Option<&[u8]>, usize) -> Result<KvScanPage, TalonError> + 'a>;
+
+/// KV 扫描迭代器：按页惰性拉取，内存中最多只保留一页数据。
+///
+/// 任一页拉取失败时返回 `Err` 并结束迭代。
+pub struct KvScan<'a> {
+    fetch: KvPageFetch<'a>,
+    page_size: usize,
+    buf: VecDeque<(Vec<u8>, Vec<u8>)>,
+    cursor: Option<Vec<u8>>,
+    done: bool,
+}
+
+impl<'a> KvScan<'a> {
+    fn new(fetch: KvPageFetch<'a>) -> Self {
+        Self {
+            fetch,
+            page_size: KV_SCAN_PAGE_SIZE,
+            buf: VecDeque::new(),
+            cursor: None,
+            done: false,
+        }
+    }
+
+    /// 设置每页拉取的条数（默认 1000，最小 1）。
+    pub fn page_size(mut self, page_size: usize) -> Self {
+        self.page_size = page_size.max(1);
+        self
+    }
+
+    /// 从之前保存的游标处继续扫描。
+    pub fn resume(mut self, cursor: impl Into<Vec<u8>>) -> Self {
+        self.cursor = Some(cursor.into());
+        self
+    }
+
+    /// 下一页的续扫游标。
+    ///
+    /// 在当前页消费完后保存该游标，可通过 [`KvScan::resume`] 断点续扫。
+    pub fn cursor(&self) -> Option<&[u8]> {
+        self.cursor.as_deref()
+    }
+}
+
+impl Iterator for KvScan<'_> {
+    type Item = Result<(Vec<u8>, Vec<u8>), TalonError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(entry) = self.buf.pop_front() {
+                return Some(Ok(entry));
+            }
+            if self.done {
+                return None;
+            }
+            match (self.fetch)(self.cursor.as_deref(), self.page_size) {
+                Ok(page) => {
+                    self.done = page.next_cursor.is_none();
+                    self.cursor = page.next_cursor;
+                    self.buf.extend(page.entries);
+                }
+                Err(e) => {
+                    self.done = true;
+                    return Some(Err(e));
+                }
+            }
+        }
+    }
+}
+
+/// 前缀扫描对应的半开区间上界：最后一个非 0xFF 字节加一并截断。
+/// 前缀为空或全为 0xFF 时无上界。
+fn kv_prefix_end(prefix: &[u8]) -> Option<Vec<u8>> {
+    let mut end = prefix.to_vec();
+    while let Some(last) = end.pop() {
+        if last < 0xFF {
+            end.push(last + 1);
+            return Some(end);
+        }
+    }
+    None
+}
+
+/// 把任意 `RangeBounds` 规整为 `[start, end)` 半开区间。
+fn kv_range_bounds<K: AsRef<[u8]>>(range: &impl RangeBounds<K>) -> (Vec<u8>, Option<Vec<u8>>) {
+    let start = match range.start_bound() {
+        Bound::Included(k) => k.as_ref().to_vec(),
+        Bound::Excluded(k) => {
+            let mut k = k.as_ref().to_vec();
+            k.push(0);
+            k
+        }
+        Bound::Unbounded => Vec::new(),
+    };
+    let end = match range.end_bound() {
+        Bound::Included(k) => {
+            let mut k = k.as_ref().to_vec();
+            k.push(0);
+            Some(k)
+        }
+        Bound::Excluded(k) => Some(k.as_ref().to_vec()),
+        Bound::Unbounded => None,
+    };
+    (start, end)
+}
+
+// ── KV Watch 类型 ───────────────────────────────────────────────────────────
+
+/// 单次拉取的最大事件数。
+const KV_WATCH_BATCH: usize = 256;
+/// 迭代器阻塞等待时每轮的轮询间隔。
+const KV_WATCH_POLL_MS: u64 = 1000;
+
+/// KV 变更事件类型。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum KvEventKind {
+    /// key 被写入。
+    Set,
+    /// key 被删除。
+    Del,
+    /// key 因 TTL 到期被清理。
+    Expired,
+}
+
+impl KvEventKind {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            KvEventKind::Set => "set",
+            KvEventKind::Del => "del",
+            KvEventKind::Expired => "expired",
+        }
+    }
+
+    fn from_code(code: u8) -> Option<Self> {
+        match code {
+            0 => Some(KvEventKind::Set),
+            1 => Some(KvEventKind::Del),
+            2 => Some(KvEventKind::Expired),
+            _ => None,
+        }
+    }
+
+    fn parse(s: &str) -> Option<Self> {
+        match s {
+            "set" => Some(KvEventKind::Set),
+            "del" => Some(KvEventKind::Del),
+            "expired" => Some(KvEventKind::Expired),
+            _ => None,
+        }
+    }
+}
+
+/// KV 变更事件。
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct KvEvent {
+    pub key: Vec<u8>,
+    pub kind: KvEventKind,
+    /// `Set` 事件携带新值，其余为 `None`。
+    pub value: Option<Vec<u8>>,
+}
+
+enum KvWatchSource<'a> {
+    Embedded {
+        db: &'a Talon,
+        watch_id: u64,
+    },
+    Remote {
+        stream: TcpStream,
+        timeout: Duration,
+        encoding: RemoteKvEncoding,
+    },
+}
+
+/// KV 前缀订阅（`KvEngine::watch` / `RemoteKvEngine::watch`）。
+///
+/// 作为迭代器使用时阻塞等待下一条事件；也可用 `recv_timeout` / `try_recv`
+/// 非阻塞拉取。drop 时自动取消订阅。
+pub struct KvWatcher<'a> {
+    source: KvWatchSource<'a>,
+    buf: VecDeque<KvEvent>,
+    closed: bool,
+}
+
+impl<'a> KvWatcher<'a> {
+    /// 等待至多 `timeout` 取下一条事件，超时返回 `Ok(None)`。
+    pub fn recv_timeout(&mut self, timeout: Duration) -> Result<Option<KvEvent>, TalonError> {
+        if self.buf.is_empty() && !self.closed {
+            self.fill(Some(timeout))?;
+        }
+        Ok(self.buf.pop_front())
+    }
+
+    /// 非阻塞取下一条事件。
+    pub fn try_recv(&mut self) -> Result<Option<KvEvent>, TalonError> {
+        self.recv_timeout(Duration::ZERO)
+    }
+
+    fn fill(&mut self, timeout: Option<Duration>) -> Result<(), TalonError> {
+        match &mut self.source {
+            KvWatchSource::Embedded { db, watch_id } => {
+                let timeout_ms = timeout.map_or(KV_WATCH_POLL_MS, |t| t.as_millis() as u64);
+                let events = db.raw_kv_watch_poll(*watch_id, timeout_ms)?;
+                self.buf.extend(events);
+            }
+            KvWatchSource::Remote {
+                stream,
+                timeout: io_timeout,
+                encoding,
+            } => {
+                // 先 peek 等待数据到达，避免读超时打断半个 frame。
+                let wait = match timeout {
+                    Some(t) if t.is_zero() => Some(Duration::from_millis(1)),
+                    other => other,
+                };
+                stream.set_read_timeout(wait).map_err(|e| {
+                    remote_io_error(TalonRemoteErrorKind::Io, "set watch read timeout", e)
+                })?;
+                let mut probe = [0u8; 1];
+                match stream.peek(&mut probe) {
+                    Ok(0) => {
+                        self.closed = true;
+                        return Ok(());
+                    }
+                    Ok(_) => {}
+                    Err(e)
+                        if matches!(
+                            e.kind(),
+                            std::io::ErrorKind::WouldBlock | std::io::ErrorKind::TimedOut
+                        ) =>
+                    {
+                        return Ok(());
+                    }
+                    Err(e) => {
+                        return Err(remote_io_error(TalonRemoteErrorKind::Io, "watch peek", e))
+                    }
+                }
+                stream.set_read_timeout(Some(*io_timeout)).map_err(|e| {
+                    remote_io_error(TalonRemoteErrorKind::Io, "set watch read timeout", e)
+                })?;
+                let frame = read_remote_frame(stream)?;
+                let resp: serde_json::Value = serde_json::from_slice(&frame).map_err(|e| {
+                    remote_error(TalonRemoteErrorKind::Protocol, format!("decode: {e}"))
+                })?;
+                let events = remote_response_data(&resp)?
+                    .and_then(|d| d.get("events"))
+                    .and_then(|e| e.as_array())
+                    .ok_or_else(|| {
+                        remote_error(
+                            TalonRemoteErrorKind::Protocol,
+                            format!("KV watch frame missing events array: {resp}"),
+                        )
+                    })?;
+                for event in events {
+                    self.buf.push_back(remote_kv_event(*encoding, event)?);
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Iterator for KvWatcher<'_> {
+    type Item = Result<KvEvent, TalonError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while self.buf.is_empty() && !self.closed {
+            if let Err(e) = self.fill(None) {
+                self.closed = true;
+                return Some(Err(e));
+            }
+        }
+        self.buf.pop_front().map(Ok)
+    }
+}
+
+impl Drop for KvWatcher<'_> {
+    fn drop(&mut self) {
+        match &self.source {
+            KvWatchSource::Embedded { db, watch_id } => db.raw_kv_unwatch(*watch_id),
+            KvWatchSource::Remote { stream, .. } => {
+                let _ = stream.shutdown(std::net::Shutdown::Both);
+            }
+        }
+    }
+}
+
 // ── FTS 类型 ────────────────────────────────────────────────────────────────
 
 /// FTS 索引配置。
@@ -973,6 +1894,222 @@ mod raw_ffi {
             out_data: *mut *mut u8,
             out_len: *mut usize,
         ) -> c_int;
+
+        // 以下各节为 libtalon v0.2.0 新增符号，与 build.rs 的 `TALON_LIB_VERSION` 对应。
+
+        // ── KV 原子操作（v0.2.0+）──
+        // 写入新值并通过出参返回旧值（key 不存在时 out_old 为 null）。
+        pub fn talon_kv_getset(
+            handle: *const TalonHandle,
+            key: *const u8,
+            key_len: usize,
+            value: *const u8,
+            value_len: usize,
+            out_old: *mut *mut u8,
+            out_old_len: *mut usize,
+        ) -> c_int;
+        // expected 为 null 表示期望 key 不存在；new_value 为 null 表示比较成功后删除 key。
+        pub fn talon_kv_cas(
+            handle: *const TalonHandle,
+            key: *const u8,
+            key_len: usize,
+            expected: *const u8,
+            expected_len: usize,
+            new_value: *const u8,
+            new_len: usize,
+            ttl_secs: i64,
+            swapped: *mut c_int,
+        ) -> c_int;
+
+        // ── KV 扫描 / TTL / 订阅 / 批量（v0.2.0+）──
+        // 扫描 [start, end) 区间（end 为 null 表示无上界），从 cursor 之后续扫，
+        // 最多返回 limit 条。出参格式见 `decode_kv_page_bin`。
+        pub fn talon_kv_scan_bin(
+            handle: *const TalonHandle,
+            start: *const u8,
+            start_len: usize,
+            end: *const u8,
+            end_len: usize,
+            cursor: *const u8,
+            cursor_len: usize,
+            limit: usize,
+            out_data: *mut *mut u8,
+            out_len: *mut usize,
+        ) -> c_int;
+        // TTL 管理：out_ttl_ms 为 -1 表示未设置过期，-2 表示 key 不存在。
+        pub fn talon_kv_ttl(
+            handle: *const TalonHandle,
+            key: *const u8,
+            key_len: usize,
+            out_ttl_ms: *mut i64,
+        ) -> c_int;
+        pub fn talon_kv_expire(
+            handle: *const TalonHandle,
+            key: *const u8,
+            key_len: usize,
+            ttl_ms: i64,
+            out_updated: *mut c_int,
+        ) -> c_int;
+        pub fn talon_kv_expire_at(
+            handle: *const TalonHandle,
+            key: *const u8,
+            key_len: usize,
+            unix_ts_secs: i64,
+            out_updated: *mut c_int,
+        ) -> c_int;
+        pub fn talon_kv_persist(
+            handle: *const TalonHandle,
+            key: *const u8,
+            key_len: usize,
+            out_updated: *mut c_int,
+        ) -> c_int;
+        // KV 订阅：poll 最多等待 timeout_ms，出参格式见 `decode_kv_events_bin`。
+        pub fn talon_kv_watch(
+            handle: *const TalonHandle,
+            prefix: *const u8,
+            prefix_len: usize,
+            out_watch_id: *mut u64,
+        ) -> c_int;
+        pub fn talon_kv_watch_poll_bin(
+            handle: *const TalonHandle,
+            watch_id: u64,
+            timeout_ms: u64,
+            max_events: usize,
+            out_data: *mut *mut u8,
+            out_len: *mut usize,
+        ) -> c_int;
+        pub fn talon_kv_unwatch(handle: *const TalonHandle, watch_id: u64) -> c_int;
+        // 批量 KV：keys 载荷为 `count: u32` + 每个 `len: u32, key`，格式见 `encode_kv_keys`。
+        pub fn talon_kv_mget_bin(
+            handle: *const TalonHandle,
+            keys: *const u8,
+            keys_len: usize,
+            out_data: *mut *mut u8,
+            out_len: *mut usize,
+        ) -> c_int;
+        pub fn talon_kv_mset_bin(
+            handle: *const TalonHandle,
+            entries: *const u8,
+            entries_len: usize,
+            atomic: c_int,
+        ) -> c_int;
+        pub fn talon_kv_mdel_bin(
+            handle: *const TalonHandle,
+            keys: *const u8,
+            keys_len: usize,
+            out_deleted: *mut u64,
+        ) -> c_int;
+
+        // ── 向量批量写入（v0.2.0+）──
+        /// 批量插入：`data` 为 `count * dim` 个按 `ids` 顺序平铺的 f32。
+        pub fn talon_vector_insert_batch(
+            handle: *const TalonHandle,
+            index_name: *const c_char,
+            ids: *const u64,
+            data: *const f32,
+            count: usize,
+            dim: usize,
+        ) -> c_int;
+
+        // ── 流式游标（v0.2.0+）──
+        pub fn talon_cursor_open(
+            handle: *const TalonHandle,
+            sql: *const c_char,
+            params: *const u8,
+            params_len: usize,
+            out_cursor_id: *mut u64,
+        ) -> c_int;
+        /// 拉取至多 `max_rows` 行，格式同 `talon_run_sql_bin`；读完时返回 0 行。
+        pub fn talon_cursor_fetch_bin(
+            handle: *const TalonHandle,
+            cursor_id: u64,
+            max_rows: usize,
+            out_data: *mut *mut u8,
+            out_len: *mut usize,
+        ) -> c_int;
+        pub fn talon_cursor_close(handle: *const TalonHandle, cursor_id: u64);
+
+        // ── 事务（v0.2.0+）──
+        // 返回 0 成功；2 表示序列化冲突（可重试整个事务）；其他非零值为普通错误。
+        pub fn talon_txn_begin(handle: *const TalonHandle, out_txn_id: *mut u64) -> c_int;
+        pub fn talon_txn_commit(handle: *const TalonHandle, txn_id: u64) -> c_int;
+        pub fn talon_txn_rollback(handle: *const TalonHandle, txn_id: u64) -> c_int;
+        pub fn talon_txn_run_sql_param_bin(
+            handle: *const TalonHandle,
+            txn_id: u64,
+            sql: *const c_char,
+            params: *const u8,
+            params_len: usize,
+            out_data: *mut *mut u8,
+            out_len: *mut usize,
+        ) -> c_int;
+        pub fn talon_txn_kv_get(
+            handle: *const TalonHandle,
+            txn_id: u64,
+            key: *const u8,
+            key_len: usize,
+            out_value: *mut *mut u8,
+            out_len: *mut usize,
+        ) -> c_int;
+        pub fn talon_txn_kv_set(
+            handle: *const TalonHandle,
+            txn_id: u64,
+            key: *const u8,
+            key_len: usize,
+            value: *const u8,
+            value_len: usize,
+            ttl_secs: i64,
+        ) -> c_int;
+        pub fn talon_txn_kv_del(
+            handle: *const TalonHandle,
+            txn_id: u64,
+            key: *const u8,
+            key_len: usize,
+        ) -> c_int;
+
+        // ── 预编译语句 / 查询元数据 / 批量写入 / EXPLAIN（v0.2.0+）──
+        /// 编译 SQL，输出语句 id。
+        pub fn talon_prepare(
+            handle: *const TalonHandle,
+            sql: *const c_char,
+            out_stmt_id: *mut u64,
+        ) -> c_int;
+        /// 以二进制参数执行预编译语句，结果格式同 `talon_query_bin`。
+        pub fn talon_stmt_query_bin(
+            handle: *const TalonHandle,
+            stmt_id: u64,
+            params: *const u8,
+            params_len: usize,
+            out_data: *mut *mut u8,
+            out_len: *mut usize,
+        ) -> c_int;
+        pub fn talon_stmt_close(handle: *const TalonHandle, stmt_id: u64);
+        /// 与 `talon_run_sql_param_bin` 相同，但结果带扩展头（列元数据、影响行数、自增 ID）。
+        pub fn talon_query_bin(
+            handle: *const TalonHandle,
+            sql: *const c_char,
+            params: *const u8,
+            params_len: usize,
+            out_data: *mut *mut u8,
+            out_len: *mut usize,
+        ) -> c_int;
+        /// 批量写入：载荷格式见 `bulk.rs`，出参为写入行数。
+        pub fn talon_bulk_insert_bin(
+            handle: *const TalonHandle,
+            table: *const c_char,
+            payload: *const u8,
+            payload_len: usize,
+            out_inserted: *mut u64,
+        ) -> c_int;
+        /// 查询计划：`analyze` 非 0 时真实执行并计时，输出 JSON 格式见 `explain.rs`。
+        pub fn talon_explain(
+            handle: *const TalonHandle,
+            sql: *const c_char,
+            params: *const u8,
+            params_len: usize,
+            analyze: c_int,
+            out_json: *mut *mut c_char,
+        ) -> c_int;
     }
 }
 
@@ -1040,25 +2177,136 @@ impl<'a> KvEngine<'a> {
     }
     /// 写入 key-value，可选 TTL。
     pub fn set(&self, key: &[u8], value: &[u8], ttl_secs: Option<u64>) -> Result<(), TalonError> {
-        self.db.raw_kv_set(key, value, ttl_secs.unwrap_or(0) as i64)
+        self.db.raw_kv_set(key, value, ttl_secs_arg(ttl_secs)?)
     }
     /// 删除 key。
     pub fn del(&self, key: &[u8]) -> Result<(), TalonError> {
         self.db.raw_kv_del(key)
     }
-}
-
-/// FTS 引擎包装（通过 talon_execute JSON 命令代理）。
-pub struct FtsEngine<'a> {
-    db: &'a Talon,
-}
-
-impl<'a> FtsEngine<'a> {
-    /// 创建 FTS 索引（幂等）。
-    pub fn create_index(&self, name: &str, _config: &FtsConfig) -> Result<(), TalonError> {
-        let cmd = serde_json::json!({
-            "module": "fts", "action": "create_index",
-            "params": { "name": name }
+    /// 原子自增 `delta`，返回自增后的值（key 不存在时从 0 开始）。
+    pub fn incr_by(&self, key: &[u8], delta: i64) -> Result<i64, TalonError> {
+        self.db.raw_kv_incrby(key, delta)
+    }
+    /// 原子自减 `delta`，返回自减后的值。
+    pub fn decr_by(&self, key: &[u8], delta: i64) -> Result<i64, TalonError> {
+        let delta = delta
+            .checked_neg()
+            .ok_or_else(|| TalonError(format!("kv decr_by delta out of range: {delta}")))?;
+        self.db.raw_kv_incrby(key, delta)
+    }
+    /// 仅当 key 不存在时写入，返回是否写入成功。
+    pub fn set_nx(
+        &self,
+        key: &[u8],
+        value: &[u8],
+        ttl_secs: Option<u64>,
+    ) -> Result<bool, TalonError> {
+        self.db.raw_kv_setnx(key, value, ttl_secs_arg(ttl_secs)?)
+    }
+    /// 写入新值并返回旧值。
+    pub fn get_set(&self, key: &[u8], value: &[u8]) -> Result<Option<Vec<u8>>, TalonError> {
+        self.db.raw_kv_getset(key, value)
+    }
+    /// Compare-and-swap：当前值等于 `expected` 时写入 `new`，返回是否交换成功。
+    ///
+    /// `expected = None` 表示要求 key 不存在；`new = None` 表示比较成功后删除 key。
+    pub fn compare_and_swap(
+        &self,
+        key: &[u8],
+        expected: Option<&[u8]>,
+        new: Option<&[u8]>,
+        ttl_secs: Option<u64>,
+    ) -> Result<bool, TalonError> {
+        self.db
+            .raw_kv_cas(key, expected, new, ttl_secs_arg(ttl_secs)?)
+    }
+    /// 剩余 TTL；key 不存在或未设置过期时返回 `None`。
+    pub fn ttl(&self, key: &[u8]) -> Result<Option<Duration>, TalonError> {
+        self.db.raw_kv_ttl(key)
+    }
+    /// 设置相对过期时间（覆盖原 TTL，不改写值），返回 key 是否存在。
+    pub fn expire(&self, key: &[u8], ttl: Duration) -> Result<bool, TalonError> {
+        self.db.raw_kv_expire(key, kv_ttl_millis(ttl)?)
+    }
+    /// 设置绝对过期时间（Unix 秒），返回 key 是否存在。
+    pub fn expire_at(&self, key: &[u8], unix_ts: u64) -> Result<bool, TalonError> {
+        let unix_ts = i64::try_from(unix_ts)
+            .map_err(|_| TalonError(format!("kv expire_at timestamp out of range: {unix_ts}")))?;
+        self.db.raw_kv_expire_at(key, unix_ts)
+    }
+    /// 移除 key 的 TTL 使其永不过期，返回是否移除了 TTL。
+    pub fn persist(&self, key: &[u8]) -> Result<bool, TalonError> {
+        self.db.raw_kv_persist(key)
+    }
+    /// 订阅 `prefix` 下的 key 变更（写入 / 删除 / 过期）。
+    pub fn watch(&self, prefix: &[u8]) -> Result<KvWatcher<'a>, TalonError> {
+        let watch_id = self.db.raw_kv_watch(prefix)?;
+        Ok(KvWatcher {
+            source: KvWatchSource::Embedded {
+                db: self.db,
+                watch_id,
+            },
+            buf: VecDeque::new(),
+            closed: false,
+        })
+    }
+    /// 按前缀扫描 key，返回分页拉取的迭代器。
+    pub fn scan_prefix(&self, prefix: &[u8]) -> KvScan<'a> {
+        let end = kv_prefix_end(prefix);
+        self.scan_bounds(prefix.to_vec(), end)
+    }
+    /// 按 key 区间扫描，例如 `kv.scan_range("user:a".."user:m")`。
+    pub fn scan_range<K: AsRef<[u8]>>(&self, range: impl RangeBounds<K>) -> KvScan<'a> {
+        let (start, end) = kv_range_bounds(&range);
+        self.scan_bounds(start, end)
+    }
+    /// 拉取 `[start, end)` 区间内的一页，`cursor` 为上一页返回的续扫游标。
+    pub fn scan_page(
+        &self,
+        start: &[u8],
+        end: Option<&[u8]>,
+        cursor: Option<&[u8]>,
+        limit: usize,
+    ) -> Result<KvScanPage, TalonError> {
+        self.db.raw_kv_scan(start, end, cursor, limit)
+    }
+    /// 批量读取（单次 FFI 调用），结果与 `keys` 一一对应。
+    pub fn get_many<K: AsRef<[u8]>>(&self, keys: &[K]) -> Result<Vec<Option<Vec<u8>>>, TalonError> {
+        self.db.raw_kv_mget(keys)
+    }
+    /// 批量写入 `(key, value, ttl_secs)`（单次 FFI 调用）。
+    ///
+    /// `atomic = true` 时全部写入在同一批次内提交，任一失败则全部不生效。
+    pub fn set_many<K: AsRef<[u8]>, V: AsRef<[u8]>>(
+        &self,
+        entries: &[(K, V, Option<u64>)],
+        atomic: bool,
+    ) -> Result<(), TalonError> {
+        self.db.raw_kv_mset(entries, atomic)
+    }
+    /// 批量删除（单次 FFI 调用），返回实际删除的 key 数。
+    pub fn del_many<K: AsRef<[u8]>>(&self, keys: &[K]) -> Result<u64, TalonError> {
+        self.db.raw_kv_mdel(keys)
+    }
+    fn scan_bounds(&self, start: Vec<u8>, end: Option<Vec<u8>>) -> KvScan<'a> {
+        let db = self.db;
+        KvScan::new(Box::new(move |cursor, limit| {
+            db.raw_kv_scan(&start, end.as_deref(), cursor, limit)
+        }))
+    }
+}
+
+/// FTS 引擎包装（通过 talon_execute JSON 命令代理）。
+pub struct FtsEngine<'a> {
+    db: &'a Talon,
+}
+
+impl<'a> FtsEngine<'a> {
+    /// 创建 FTS 索引（幂等）。
+    pub fn create_index(&self, name: &str, _config: &FtsConfig) -> Result<(), TalonError> {
+        let cmd = serde_json::json!({
+            "module": "fts", "action": "create_index",
+            "params": { "name": name }
         });
         self.db.exec_cmd(&cmd)
     }
@@ -2114,6 +3362,7 @@ pub struct StoreRef;
 /// A Talon database handle. Automatically closes on drop.
 pub struct Talon {
     handle: *mut raw_ffi::TalonHandle,
+    stmt_cache: EmbeddedStmtCache,
 }
 
 // SAFETY: TalonHandle is internally synchronized via Talon's storage engine.
@@ -2135,7 +3384,10 @@ impl Talon {
         if handle.is_null() {
             return Err(TalonError(format!("Failed to open: {path_str}")));
         }
-        Ok(Talon { handle })
+        Ok(Talon {
+            handle,
+            stmt_cache: Mutex::new(StmtCache::new(DEFAULT_STMT_CACHE_CAPACITY)),
+        })
     }
 
     /// Open from `&Path`（兼容源码 Talon 签名）。
@@ -2345,7 +3597,7 @@ impl Talon {
             )
         };
         if rc != 0 {
-            return Err(TalonError("kv_get FFI failed".into()));
+            return Err(ffi_error("kv_get"));
         }
         if out_ptr.is_null() {
             return Ok(None);
@@ -2367,7 +3619,7 @@ impl Talon {
             )
         };
         if rc != 0 {
-            return Err(TalonError("kv_set FFI failed".into()));
+            return Err(ffi_error("kv_set"));
         }
         Ok(())
     }
@@ -2375,11 +3627,292 @@ impl Talon {
     fn raw_kv_del(&self, key: &[u8]) -> Result<(), TalonError> {
         let rc = unsafe { raw_ffi::talon_kv_del(self.handle, key.as_ptr(), key.len()) };
         if rc != 0 {
-            return Err(TalonError("kv_del FFI failed".into()));
+            return Err(ffi_error("kv_del"));
+        }
+        Ok(())
+    }
+
+    fn raw_kv_incrby(&self, key: &[u8], delta: i64) -> Result<i64, TalonError> {
+        let mut out_value: i64 = 0;
+        let rc = unsafe {
+            raw_ffi::talon_kv_incrby(self.handle, key.as_ptr(), key.len(), delta, &mut out_value)
+        };
+        if rc != 0 {
+            return Err(ffi_error("kv_incrby"));
+        }
+        Ok(out_value)
+    }
+
+    fn raw_kv_setnx(&self, key: &[u8], value: &[u8], ttl_secs: i64) -> Result<bool, TalonError> {
+        let mut was_set: std::os::raw::c_int = 0;
+        let rc = unsafe {
+            raw_ffi::talon_kv_setnx(
+                self.handle,
+                key.as_ptr(),
+                key.len(),
+                value.as_ptr(),
+                value.len(),
+                ttl_secs,
+                &mut was_set,
+            )
+        };
+        if rc != 0 {
+            return Err(ffi_error("kv_setnx"));
+        }
+        Ok(was_set != 0)
+    }
+
+    fn raw_kv_getset(&self, key: &[u8], value: &[u8]) -> Result<Option<Vec<u8>>, TalonError> {
+        let mut out_ptr: *mut u8 = ptr::null_mut();
+        let mut out_len: usize = 0;
+        let rc = unsafe {
+            raw_ffi::talon_kv_getset(
+                self.handle,
+                key.as_ptr(),
+                key.len(),
+                value.as_ptr(),
+                value.len(),
+                &mut out_ptr,
+                &mut out_len,
+            )
+        };
+        if rc != 0 {
+            return Err(ffi_error("kv_getset"));
+        }
+        if out_ptr.is_null() {
+            return Ok(None);
+        }
+        let data = unsafe { slice::from_raw_parts(out_ptr, out_len).to_vec() };
+        unsafe { raw_ffi::talon_free_bytes(out_ptr, out_len) };
+        Ok(Some(data))
+    }
+
+    fn raw_kv_cas(
+        &self,
+        key: &[u8],
+        expected: Option<&[u8]>,
+        new: Option<&[u8]>,
+        ttl_secs: i64,
+    ) -> Result<bool, TalonError> {
+        let (expected_ptr, expected_len) =
+            expected.map_or((ptr::null(), 0), |v| (v.as_ptr(), v.len()));
+        let (new_ptr, new_len) = new.map_or((ptr::null(), 0), |v| (v.as_ptr(), v.len()));
+        let mut swapped: std::os::raw::c_int = 0;
+        let rc = unsafe {
+            raw_ffi::talon_kv_cas(
+                self.handle,
+                key.as_ptr(),
+                key.len(),
+                expected_ptr,
+                expected_len,
+                new_ptr,
+                new_len,
+                ttl_secs,
+                &mut swapped,
+            )
+        };
+        if rc != 0 {
+            return Err(ffi_error("kv_cas"));
+        }
+        Ok(swapped != 0)
+    }
+
+    fn raw_kv_ttl(&self, key: &[u8]) -> Result<Option<Duration>, TalonError> {
+        let mut ttl_ms: i64 = 0;
+        let rc =
+            unsafe { raw_ffi::talon_kv_ttl(self.handle, key.as_ptr(), key.len(), &mut ttl_ms) };
+        if rc != 0 {
+            return Err(ffi_error("kv_ttl"));
+        }
+        kv_ttl_from_millis(ttl_ms)
+    }
+
+    fn raw_kv_expire(&self, key: &[u8], ttl_ms: i64) -> Result<bool, TalonError> {
+        let mut updated: std::os::raw::c_int = 0;
+        let rc = unsafe {
+            raw_ffi::talon_kv_expire(self.handle, key.as_ptr(), key.len(), ttl_ms, &mut updated)
+        };
+        if rc != 0 {
+            return Err(ffi_error("kv_expire"));
+        }
+        Ok(updated != 0)
+    }
+
+    fn raw_kv_expire_at(&self, key: &[u8], unix_ts_secs: i64) -> Result<bool, TalonError> {
+        let mut updated: std::os::raw::c_int = 0;
+        let rc = unsafe {
+            raw_ffi::talon_kv_expire_at(
+                self.handle,
+                key.as_ptr(),
+                key.len(),
+                unix_ts_secs,
+                &mut updated,
+            )
+        };
+        if rc != 0 {
+            return Err(ffi_error("kv_expire_at"));
+        }
+        Ok(updated != 0)
+    }
+
+    fn raw_kv_persist(&self, key: &[u8]) -> Result<bool, TalonError> {
+        let mut updated: std::os::raw::c_int = 0;
+        let rc = unsafe {
+            raw_ffi::talon_kv_persist(self.handle, key.as_ptr(), key.len(), &mut updated)
+        };
+        if rc != 0 {
+            return Err(ffi_error("kv_persist"));
+        }
+        Ok(updated != 0)
+    }
+
+    fn raw_kv_watch(&self, prefix: &[u8]) -> Result<u64, TalonError> {
+        let mut watch_id: u64 = 0;
+        let rc = unsafe {
+            raw_ffi::talon_kv_watch(self.handle, prefix.as_ptr(), prefix.len(), &mut watch_id)
+        };
+        if rc != 0 {
+            return Err(ffi_error("kv_watch"));
+        }
+        Ok(watch_id)
+    }
+
+    fn raw_kv_watch_poll(
+        &self,
+        watch_id: u64,
+        timeout_ms: u64,
+    ) -> Result<Vec<KvEvent>, TalonError> {
+        let mut out_data: *mut u8 = ptr::null_mut();
+        let mut out_len: usize = 0;
+        let rc = unsafe {
+            raw_ffi::talon_kv_watch_poll_bin(
+                self.handle,
+                watch_id,
+                timeout_ms,
+                KV_WATCH_BATCH,
+                &mut out_data,
+                &mut out_len,
+            )
+        };
+        if rc != 0 {
+            return Err(ffi_error("kv_watch_poll"));
+        }
+        if out_data.is_null() || out_len == 0 {
+            return Ok(vec![]);
+        }
+        let data = unsafe { slice::from_raw_parts(out_data, out_len) };
+        let result = decode_kv_events_bin(data);
+        unsafe { raw_ffi::talon_free_bytes(out_data, out_len) };
+        result
+    }
+
+    fn raw_kv_unwatch(&self, watch_id: u64) {
+        unsafe { raw_ffi::talon_kv_unwatch(self.handle, watch_id) };
+    }
+
+    fn raw_kv_mget<K: AsRef<[u8]>>(&self, keys: &[K]) -> Result<Vec<Option<Vec<u8>>>, TalonError> {
+        if keys.is_empty() {
+            return Ok(vec![]);
+        }
+        let keys_bin = encode_kv_keys(keys);
+        let mut out_data: *mut u8 = ptr::null_mut();
+        let mut out_len: usize = 0;
+        let rc = unsafe {
+            raw_ffi::talon_kv_mget_bin(
+                self.handle,
+                keys_bin.as_ptr(),
+                keys_bin.len(),
+                &mut out_data,
+                &mut out_len,
+            )
+        };
+        if rc != 0 {
+            return Err(ffi_error("kv_mget"));
+        }
+        if out_data.is_null() || out_len == 0 {
+            return Err(TalonError("kv_mget returned empty output".into()));
+        }
+        let data = unsafe { slice::from_raw_parts(out_data, out_len) };
+        let result = decode_kv_values_bin(data, keys.len());
+        unsafe { raw_ffi::talon_free_bytes(out_data, out_len) };
+        result
+    }
+
+    fn raw_kv_mset<K: AsRef<[u8]>, V: AsRef<[u8]>>(
+        &self,
+        entries: &[(K, V, Option<u64>)],
+        atomic: bool,
+    ) -> Result<(), TalonError> {
+        if entries.is_empty() {
+            return Ok(());
+        }
+        let entries_bin = encode_kv_entries(entries)?;
+        let rc = unsafe {
+            raw_ffi::talon_kv_mset_bin(
+                self.handle,
+                entries_bin.as_ptr(),
+                entries_bin.len(),
+                atomic as std::os::raw::c_int,
+            )
+        };
+        if rc != 0 {
+            return Err(ffi_error("kv_mset"));
         }
         Ok(())
     }
 
+    fn raw_kv_mdel<K: AsRef<[u8]>>(&self, keys: &[K]) -> Result<u64, TalonError> {
+        if keys.is_empty() {
+            return Ok(0);
+        }
+        let keys_bin = encode_kv_keys(keys);
+        let mut deleted: u64 = 0;
+        let rc = unsafe {
+            raw_ffi::talon_kv_mdel_bin(self.handle, keys_bin.as_ptr(), keys_bin.len(), &mut deleted)
+        };
+        if rc != 0 {
+            return Err(ffi_error("kv_mdel"));
+        }
+        Ok(deleted)
+    }
+
+    fn raw_kv_scan(
+        &self,
+        start: &[u8],
+        end: Option<&[u8]>,
+        cursor: Option<&[u8]>,
+        limit: usize,
+    ) -> Result<KvScanPage, TalonError> {
+        let (end_ptr, end_len) = end.map_or((ptr::null(), 0), |v| (v.as_ptr(), v.len()));
+        let (cursor_ptr, cursor_len) = cursor.map_or((ptr::null(), 0), |v| (v.as_ptr(), v.len()));
+        let mut out_data: *mut u8 = ptr::null_mut();
+        let mut out_len: usize = 0;
+        let rc = unsafe {
+            raw_ffi::talon_kv_scan_bin(
+                self.handle,
+                start.as_ptr(),
+                start.len(),
+                end_ptr,
+                end_len,
+                cursor_ptr,
+                cursor_len,
+                limit,
+                &mut out_data,
+                &mut out_len,
+            )
+        };
+        if rc != 0 {
+            return Err(ffi_error("kv_scan"));
+        }
+        if out_data.is_null() || out_len == 0 {
+            return Ok(KvScanPage::default());
+        }
+        let data = unsafe { slice::from_raw_parts(out_data, out_len) };
+        let result = decode_kv_page_bin(data);
+        unsafe { raw_ffi::talon_free_bytes(out_data, out_len) };
+        result
+    }
+
     fn raw_vector_insert(&self, index: &str, id: u64, vec: &[f32]) -> Result<(), TalonError> {
         let c_name = CString::new(index)?;
         let rc = unsafe {
@@ -2429,15 +3962,8 @@ impl Talon {
     /// 执行 JSON 命令（忽略返回值）。
     fn exec_cmd(&self, cmd: &serde_json::Value) -> Result<(), TalonError> {
         let resp = self.exec_cmd_json(cmd)?;
-        if resp.get("ok").and_then(|v| v.as_bool()) == Some(true) {
-            Ok(())
-        } else {
-            let msg = resp
-                .get("error")
-                .and_then(|v| v.as_str())
-                .unwrap_or("unknown");
-            Err(TalonError(msg.to_string()))
-        }
+        embedded_response_data(&resp)?;
+        Ok(())
     }
 
     /// 执行 JSON 命令，返回解析后的响应。
@@ -2458,47 +3984,253 @@ impl Talon {
     }
 }
 
+/// 嵌入式命令响应：`ok` 必须为 `true`，否则以 `error` 作为错误；成功时返回 `data`。
+pub(crate) fn embedded_response_data(
+    resp: &serde_json::Value,
+) -> Result<Option<&serde_json::Value>, TalonError> {
+    if resp.get("ok").and_then(|v| v.as_bool()) == Some(true) {
+        return Ok(resp.get("data"));
+    }
+    let msg = resp
+        .get("error")
+        .and_then(|v| v.as_str())
+        .unwrap_or("unknown");
+    Err(TalonError(msg.to_string()))
+}
+
 impl Drop for Talon {
     fn drop(&mut self) {
         if !self.handle.is_null() {
+            // 预编译语句须在句柄关闭前释放；锁中毒时缓存内容仍然有效，同样要清空。
+            self.stmt_cache
+                .get_mut()
+                .unwrap_or_else(PoisonError::into_inner)
+                .clear();
             unsafe { raw_ffi::talon_close(self.handle) };
             self.handle = ptr::null_mut();
         }
     }
 }
 
-// ── hybrid_search 顶层函数 ─────────────────────────────────────────────────
+// ── KvBackend：嵌入式 / 远程统一的 KV 原语 ───────────────────────────────────
 
-/// Hybrid search（FTS + Vector RRF 融合）。
+/// 嵌入式 [`Talon`] 与远程 [`TalonRemoteClient`] 共用的 KV 原语。
 ///
-/// 注意：FFI 版通过 `execute` 命令实现，`_store` 参数仅为 API 兼容保留。
-pub fn hybrid_search(
-    _store: &StoreRef,
-    _q: &fts::hybrid::HybridQuery<'_>,
-) -> Result<Vec<HybridHit>, TalonError> {
-    // FFI hybrid search 需要 Talon handle，但 StoreRef 无法访问。
-    // 当前 superclaw 已在应用层实现 RRF，此函数保留为兼容占位。
-    Ok(vec![])
+/// 分布式锁、限流器等上层组件只依赖此 trait，因此可同时运行在进程内和远程模式下。
+pub trait KvBackend: Send + Sync {
+    fn kv_get(&self, key: &[u8]) -> Result<Option<Vec<u8>>, TalonError>;
+    fn kv_set(&self, key: &[u8], value: &[u8], ttl_secs: Option<u64>) -> Result<(), TalonError>;
+    fn kv_del(&self, key: &[u8]) -> Result<(), TalonError>;
+    fn kv_incr_by(&self, key: &[u8], delta: i64) -> Result<i64, TalonError>;
+    fn kv_set_nx(
+        &self,
+        key: &[u8],
+        value: &[u8],
+        ttl_secs: Option<u64>,
+    ) -> Result<bool, TalonError>;
+    fn kv_compare_and_swap(
+        &self,
+        key: &[u8],
+        expected: Option<&[u8]>,
+        new: Option<&[u8]>,
+        ttl_secs: Option<u64>,
+    ) -> Result<bool, TalonError>;
+    fn kv_expire(&self, key: &[u8], ttl: Duration) -> Result<bool, TalonError>;
+    fn kv_get_many(&self, keys: &[&[u8]]) -> Result<Vec<Option<Vec<u8>>>, TalonError>;
+    fn kv_del_many(&self, keys: &[&[u8]]) -> Result<u64, TalonError>;
+    /// 拉取 `[start, end)` 区间内的一页，语义同 [`KvEngine::scan_page`]。
+    fn kv_scan_page(
+        &self,
+        start: &[u8],
+        end: Option<&[u8]>,
+        cursor: Option<&[u8]>,
+        limit: usize,
+    ) -> Result<KvScanPage, TalonError>;
 }
 
-// ── 二进制编码/解码（TLV 格式）────────────────────────────────────────────
-//
-// Type tags: 0=Null, 1=Integer(i64), 2=Float(f64), 3=Text(u32+bytes),
-//            4=Blob(u32+bytes), 5=Boolean(u8), 6=Jsonb(u32+bytes),
-//            7=Vector(u32+f32*dim), 8=Timestamp(i64), 9=GeoPoint(f64,f64)
-
-/// 将参数列表编码为二进制：`param_count: u32` + 每个参数的 TLV。
-fn encode_params(params: &[Value]) -> Vec<u8> {
-    let mut buf = Vec::with_capacity(4 + params.len() * 16);
-    buf.extend_from_slice(&(params.len() as u32).to_le_bytes());
-    for v in params {
-        encode_value(&mut buf, v);
+impl KvBackend for Talon {
+    fn kv_get(&self, key: &[u8]) -> Result<Option<Vec<u8>>, TalonError> {
+        self.kv()?.get(key)
+    }
+    fn kv_set(&self, key: &[u8], value: &[u8], ttl_secs: Option<u64>) -> Result<(), TalonError> {
+        self.kv()?.set(key, value, ttl_secs)
+    }
+    fn kv_del(&self, key: &[u8]) -> Result<(), TalonError> {
+        self.kv()?.del(key)
+    }
+    fn kv_incr_by(&self, key: &[u8], delta: i64) -> Result<i64, TalonError> {
+        self.kv()?.incr_by(key, delta)
+    }
+    fn kv_set_nx(
+        &self,
+        key: &[u8],
+        value: &[u8],
+        ttl_secs: Option<u64>,
+    ) -> Result<bool, TalonError> {
+        self.kv()?.set_nx(key, value, ttl_secs)
+    }
+    fn kv_compare_and_swap(
+        &self,
+        key: &[u8],
+        expected: Option<&[u8]>,
+        new: Option<&[u8]>,
+        ttl_secs: Option<u64>,
+    ) -> Result<bool, TalonError> {
+        self.kv()?.compare_and_swap(key, expected, new, ttl_secs)
+    }
+    fn kv_expire(&self, key: &[u8], ttl: Duration) -> Result<bool, TalonError> {
+        self.kv()?.expire(key, ttl)
+    }
+    fn kv_get_many(&self, keys: &[&[u8]]) -> Result<Vec<Option<Vec<u8>>>, TalonError> {
+        self.kv()?.get_many(keys)
+    }
+    fn kv_del_many(&self, keys: &[&[u8]]) -> Result<u64, TalonError> {
+        self.kv()?.del_many(keys)
+    }
+    fn kv_scan_page(
+        &self,
+        start: &[u8],
+        end: Option<&[u8]>,
+        cursor: Option<&[u8]>,
+        limit: usize,
+    ) -> Result<KvScanPage, TalonError> {
+        self.kv()?.scan_page(start, end, cursor, limit)
     }
-    buf
 }
 
-/// 编码单个 Value 到缓冲区。
-fn encode_value(buf: &mut Vec<u8>, val: &Value) {
+impl KvBackend for TalonRemoteClient {
+    fn kv_get(&self, key: &[u8]) -> Result<Option<Vec<u8>>, TalonError> {
+        self.kv()?.get(key)
+    }
+    fn kv_set(&self, key: &[u8], value: &[u8], ttl_secs: Option<u64>) -> Result<(), TalonError> {
+        self.kv()?.set(key, value, ttl_secs)
+    }
+    fn kv_del(&self, key: &[u8]) -> Result<(), TalonError> {
+        self.kv()?.del(key)
+    }
+    fn kv_incr_by(&self, key: &[u8], delta: i64) -> Result<i64, TalonError> {
+        self.kv()?.incr_by(key, delta)
+    }
+    fn kv_set_nx(
+        &self,
+        key: &[u8],
+        value: &[u8],
+        ttl_secs: Option<u64>,
+    ) -> Result<bool, TalonError> {
+        self.kv()?.set_nx(key, value, ttl_secs)
+    }
+    fn kv_compare_and_swap(
+        &self,
+        key: &[u8],
+        expected: Option<&[u8]>,
+        new: Option<&[u8]>,
+        ttl_secs: Option<u64>,
+    ) -> Result<bool, TalonError> {
+        self.kv()?.compare_and_swap(key, expected, new, ttl_secs)
+    }
+    fn kv_expire(&self, key: &[u8], ttl: Duration) -> Result<bool, TalonError> {
+        self.kv()?.expire(key, ttl)
+    }
+    fn kv_get_many(&self, keys: &[&[u8]]) -> Result<Vec<Option<Vec<u8>>>, TalonError> {
+        self.kv()?.get_many(keys)
+    }
+    fn kv_del_many(&self, keys: &[&[u8]]) -> Result<u64, TalonError> {
+        self.kv()?.del_many(keys)
+    }
+    fn kv_scan_page(
+        &self,
+        start: &[u8],
+        end: Option<&[u8]>,
+        cursor: Option<&[u8]>,
+        limit: usize,
+    ) -> Result<KvScanPage, TalonError> {
+        self.kv()?.scan_page(start, end, cursor, limit)
+    }
+}
+
+// ── SqlBackend：嵌入式 / 远程统一的 SQL 执行 ─────────────────────────────────
+
+/// 嵌入式 [`Talon`] 与远程 [`TalonRemoteClient`] 共用的 SQL 执行入口，
+/// 供迁移等同时支持两种模式的组件使用。
+pub trait SqlBackend {
+    fn sql_run(&self, sql: &str, params: &[Value]) -> Result<Vec<Vec<Value>>, TalonError>;
+
+    /// 在一个事务中执行 `f`：成功则提交，出错则回滚。
+    fn sql_transaction(
+        &self,
+        f: &mut dyn FnMut(&dyn SqlBackend) -> Result<(), TalonError>,
+    ) -> Result<(), TalonError>;
+
+    /// SQL 表是否存在（基于 schema 自省）。
+    fn sql_table_exists(&self, table: &str) -> Result<bool, TalonError>;
+}
+
+impl SqlBackend for Talon {
+    fn sql_run(&self, sql: &str, params: &[Value]) -> Result<Vec<Vec<Value>>, TalonError> {
+        self.run_sql_param(sql, params)
+    }
+
+    fn sql_transaction(
+        &self,
+        f: &mut dyn FnMut(&dyn SqlBackend) -> Result<(), TalonError>,
+    ) -> Result<(), TalonError> {
+        self.transaction(|tx| f(tx))
+    }
+
+    fn sql_table_exists(&self, table: &str) -> Result<bool, TalonError> {
+        Ok(self.schema()?.table(table).is_some())
+    }
+}
+
+impl SqlBackend for TalonRemoteClient {
+    fn sql_run(&self, sql: &str, params: &[Value]) -> Result<Vec<Vec<Value>>, TalonError> {
+        self.run_sql_param(sql, params)
+    }
+
+    fn sql_transaction(
+        &self,
+        f: &mut dyn FnMut(&dyn SqlBackend) -> Result<(), TalonError>,
+    ) -> Result<(), TalonError> {
+        self.transaction(|tx| f(tx))
+    }
+
+    fn sql_table_exists(&self, table: &str) -> Result<bool, TalonError> {
+        Ok(self.schema()?.table(table).is_some())
+    }
+}
+
+// ── hybrid_search 顶层函数 ─────────────────────────────────────────────────
+
+/// Hybrid search（FTS + Vector RRF 融合）。
+///
+/// 注意：FFI 版通过 `execute` 命令实现，`_store` 参数仅为 API 兼容保留。
+pub fn hybrid_search(
+    _store: &StoreRef,
+    _q: &fts::hybrid::HybridQuery<'_>,
+) -> Result<Vec<HybridHit>, TalonError> {
+    // FFI hybrid search 需要 Talon handle，但 StoreRef 无法访问。
+    // 当前 superclaw 已在应用层实现 RRF，此函数保留为兼容占位。
+    Ok(vec![])
+}
+
+// ── 二进制编码/解码（TLV 格式）────────────────────────────────────────────
+//
+// Type tags: 0=Null, 1=Integer(i64), 2=Float(f64), 3=Text(u32+bytes),
+//            4=Blob(u32+bytes), 5=Boolean(u8), 6=Jsonb(u32+bytes),
+//            7=Vector(u32+f32*dim), 8=Timestamp(i64), 9=GeoPoint(f64,f64)
+
+/// 将参数列表编码为二进制：`param_count: u32` + 每个参数的 TLV。
+fn encode_params(params: &[Value]) -> Vec<u8> {
+    let mut buf = Vec::with_capacity(4 + params.len() * 16);
+    buf.extend_from_slice(&(params.len() as u32).to_le_bytes());
+    for v in params {
+        encode_value(&mut buf, v);
+    }
+    buf
+}
+
+/// 编码单个 Value 到缓冲区。
+fn encode_value(buf: &mut Vec<u8>, val: &Value) {
     match val {
         Value::Null => buf.push(0),
         Value::Integer(i) => {
@@ -2590,6 +4322,158 @@ fn decode_vector_bin(data: &[u8]) -> Result<Vec<(u64, f32)>, TalonError> {
     Ok(out)
 }
 
+/// 编码批量 key：`count: u32` + 每个 `len: u32, key`。
+fn encode_kv_keys<K: AsRef<[u8]>>(keys: &[K]) -> Vec<u8> {
+    let mut buf = Vec::with_capacity(4 + keys.len() * 16);
+    buf.extend_from_slice(&(keys.len() as u32).to_le_bytes());
+    for key in keys {
+        let key = key.as_ref();
+        buf.extend_from_slice(&(key.len() as u32).to_le_bytes());
+        buf.extend_from_slice(key);
+    }
+    buf
+}
+
+/// 编码批量写入：`count: u32` + 每条 `key_len: u32, key, value_len: u32, value, ttl_secs: i64`
+/// （ttl_secs 为 0 表示不过期）。
+fn encode_kv_entries<K: AsRef<[u8]>, V: AsRef<[u8]>>(
+    entries: &[(K, V, Option<u64>)],
+) -> Result<Vec<u8>, TalonError> {
+    let mut buf = Vec::with_capacity(4 + entries.len() * 32);
+    buf.extend_from_slice(&(entries.len() as u32).to_le_bytes());
+    for (key, value, ttl_secs) in entries {
+        let (key, value) = (key.as_ref(), value.as_ref());
+        buf.extend_from_slice(&(key.len() as u32).to_le_bytes());
+        buf.extend_from_slice(key);
+        buf.extend_from_slice(&(value.len() as u32).to_le_bytes());
+        buf.extend_from_slice(value);
+        buf.extend_from_slice(&ttl_secs_arg(*ttl_secs)?.to_le_bytes());
+    }
+    Ok(buf)
+}
+
+/// `expire` 的毫秒参数：不足 1ms 的部分向上取整，避免亚毫秒 TTL 截断为 0 立即过期。
+fn kv_ttl_millis(ttl: Duration) -> Result<i64, TalonError> {
+    let ms = ttl.as_nanos().div_ceil(1_000_000);
+    i64::try_from(ms).map_err(|_| TalonError(format!("kv expire ttl out of range: {ttl:?}")))
+}
+
+/// 解析 `ttl_ms`：-1 表示未设置过期、-2 表示 key 不存在，均为 `None`；其余负值视为错误。
+fn kv_ttl_from_millis(ttl_ms: i64) -> Result<Option<Duration>, TalonError> {
+    match ttl_ms {
+        -2 | -1 => Ok(None),
+        ms if ms >= 0 => Ok(Some(Duration::from_millis(ms as u64))),
+        ms => Err(TalonError(format!("invalid kv ttl_ms: {ms}"))),
+    }
+}
+
+/// FFI 的 TTL 参数（0 表示不过期）；超出 `i64` 范围时报错而非回绕为负数。
+pub(crate) fn ttl_secs_arg(ttl_secs: Option<u64>) -> Result<i64, TalonError> {
+    let secs = ttl_secs.unwrap_or(0);
+    i64::try_from(secs).map_err(|_| TalonError(format!("kv ttl_secs out of range: {secs}")))
+}
+
+/// 读取 `len: u32` 前缀的字节串并推进 `pos`；越界时报 `truncated {what} len/data`。
+fn read_len_prefixed<'d>(
+    data: &'d [u8],
+    pos: &mut usize,
+    what: &str,
+) -> Result<&'d [u8], TalonError> {
+    let len_end = pos
+        .checked_add(4)
+        .filter(|end| *end <= data.len())
+        .ok_or_else(|| TalonError(format!("truncated {what} len")))?;
+    let len = u32::from_le_bytes(data[*pos..len_end].try_into().unwrap()) as usize;
+    let end = len_end
+        .checked_add(len)
+        .filter(|end| *end <= data.len())
+        .ok_or_else(|| TalonError(format!("truncated {what} data")))?;
+    *pos = end;
+    Ok(&data[len_end..end])
+}
+
+/// 解码批量读取结果：`count: u32` + 每条 `present: u8`，present 为 1 时跟随 `len: u32, value`。
+fn decode_kv_values_bin(data: &[u8], expected: usize) -> Result<Vec<Option<Vec<u8>>>, TalonError> {
+    if data.len() < 4 {
+        return Err(TalonError("kv values binary result too short".into()));
+    }
+    let count = u32::from_le_bytes([data[0], data[1], data[2], data[3]]) as usize;
+    if count != expected {
+        return Err(TalonError(format!(
+            "kv values count mismatch: expected {expected}, got {count}"
+        )));
+    }
+    let mut pos = 4;
+    let mut out = Vec::with_capacity(count);
+    for _ in 0..count {
+        if pos >= data.len() {
+            return Err(TalonError("truncated kv value flag".into()));
+        }
+        let present = data[pos] != 0;
+        pos += 1;
+        if !present {
+            out.push(None);
+            continue;
+        }
+        let value = read_len_prefixed(data, &mut pos, "kv value")?;
+        out.push(Some(value.to_vec()));
+    }
+    Ok(out)
+}
+
+/// 解码二进制 KV 事件：`count: u32` + 每条 `kind: u8, key_len: u32, key, has_value: u8`，
+/// has_value 为 1 时跟随 `value_len: u32, value`。kind：0=Set, 1=Del, 2=Expired。
+fn decode_kv_events_bin(data: &[u8]) -> Result<Vec<KvEvent>, TalonError> {
+    if data.len() < 4 {
+        return Err(TalonError("kv events binary result too short".into()));
+    }
+    let count = u32::from_le_bytes([data[0], data[1], data[2], data[3]]) as usize;
+    let mut pos = 4;
+    let mut events = Vec::with_capacity(count);
+    for _ in 0..count {
+        if pos >= data.len() {
+            return Err(TalonError("truncated kv event kind".into()));
+        }
+        let kind = KvEventKind::from_code(data[pos])
+            .ok_or_else(|| TalonError(format!("unknown kv event kind: {}", data[pos])))?;
+        pos += 1;
+        let key = read_len_prefixed(data, &mut pos, "kv event")?.to_vec();
+        if pos >= data.len() {
+            return Err(TalonError("truncated kv event value flag".into()));
+        }
+        let has_value = data[pos] != 0;
+        pos += 1;
+        let value = if has_value {
+            Some(read_len_prefixed(data, &mut pos, "kv event")?.to_vec())
+        } else {
+            None
+        };
+        events.push(KvEvent { key, kind, value });
+    }
+    Ok(events)
+}
+
+/// 解码二进制 KV 扫描页：`count: u32` + 每条 `key_len: u32, key, value_len: u32, value`，
+/// 末尾 `cursor_len: u32, cursor`（cursor_len 为 0 表示扫描结束）。
+fn decode_kv_page_bin(data: &[u8]) -> Result<KvScanPage, TalonError> {
+    if data.len() < 4 {
+        return Err(TalonError("kv page binary result too short".into()));
+    }
+    let count = u32::from_le_bytes([data[0], data[1], data[2], data[3]]) as usize;
+    let mut pos = 4;
+    let mut entries = Vec::with_capacity(count);
+    for _ in 0..count {
+        let key = read_len_prefixed(data, &mut pos, "kv page")?.to_vec();
+        let value = read_len_prefixed(data, &mut pos, "kv page")?.to_vec();
+        entries.push((key, value));
+    }
+    let cursor = read_len_prefixed(data, &mut pos, "kv page")?;
+    Ok(KvScanPage {
+        entries,
+        next_cursor: (!cursor.is_empty()).then(|| cursor.to_vec()),
+    })
+}
+
 /// 解码单个 Value，返回 (value, consumed_bytes)。
 fn decode_value(data: &[u8], pos: usize) -> Result<(Value, usize), TalonError> {
     if pos >= data.len() {
@@ -2723,6 +4607,32 @@ mod remote_tests {
         Err(last_err.unwrap_or_else(|| TalonError("remote connect retry exhausted".into())))
     }
 
+    /// Capability reply of a server predating KV encoding negotiation.
+    const LEGACY_CAPABILITIES: &str = r#"{"ok":false,"error":"unknown module: server"}"#;
+
+    /// Capability reply of a server accepting base64 KV payloads.
+    const BASE64_CAPABILITIES: &str = r#"{"ok":true,"data":{"kv_encodings":["utf8","base64"]}}"#;
+
+    /// Fake TCP server: answers each request frame with the next canned
+    /// response and returns the decoded request commands once done.
+    fn spawn_fake_server(
+        responses: Vec<&'static str>,
+    ) -> (String, thread::JoinHandle<Vec<serde_json::Value>>) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap().to_string();
+        let handle = thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            let mut requests = Vec::new();
+            for resp in responses {
+                let frame = read_remote_frame(&mut stream).unwrap();
+                requests.push(serde_json::from_slice(&frame).unwrap());
+                write_remote_frame(&mut stream, resp.as_bytes()).unwrap();
+            }
+            requests
+        });
+        (addr, handle)
+    }
+
     #[test]
     fn remote_endpoint_requires_talon_scheme() {
         let err = TalonRemoteClient::connect("http://127.0.0.1:7720").unwrap_err();
@@ -2745,6 +4655,646 @@ mod remote_tests {
         handle.join().unwrap();
     }
 
+    #[test]
+    fn remote_kv_atomic_ops_send_kv_commands() {
+        let (addr, handle) = spawn_fake_server(vec![
+            LEGACY_CAPABILITIES,
+            r#"{"ok":true,"data":{"value":5}}"#,
+            r#"{"ok":true,"data":{"was_set":false}}"#,
+            r#"{"ok":true,"data":{"value":"old"}}"#,
+            r#"{"ok":true,"data":{"swapped":true}}"#,
+        ]);
+        let client = TalonRemoteClient::connect(&format!("talon://{addr}")).unwrap();
+        let kv = client.kv().unwrap();
+
+        assert_eq!(kv.decr_by(b"counter", 2).unwrap(), 5);
+        assert!(!kv.set_nx(b"idem", b"1", Some(30)).unwrap());
+        assert_eq!(kv.get_set(b"k", b"new").unwrap(), Some(b"old".to_vec()));
+        assert!(kv.compare_and_swap(b"k", Some(b"new"), None, None).unwrap());
+        let err = kv.decr_by(b"counter", i64::MIN).unwrap_err();
+        assert_eq!(
+            err.0,
+            format!("KV decr_by delta out of range: {}", i64::MIN)
+        );
+
+        let requests = handle.join().unwrap();
+        assert_eq!(requests.len(), 5);
+        assert_eq!(requests[0]["action"], "capabilities");
+        let requests = &requests[1..];
+        assert_eq!(requests[0]["action"], "incrby");
+        assert_eq!(requests[0]["params"]["delta"], -2);
+        assert_eq!(requests[1]["action"], "setnx");
+        assert_eq!(requests[1]["params"]["ttl"], 30);
+        assert_eq!(requests[2]["action"], "getset");
+        assert_eq!(requests[3]["action"], "cas");
+        assert_eq!(requests[3]["params"]["expected"], "new");
+        assert!(requests[3]["params"]["new"].is_null());
+    }
+
+    #[test]
+    fn remote_kv_scan_prefix_follows_cursor_pages() {
+        let (addr, handle) = spawn_fake_server(vec![
+            LEGACY_CAPABILITIES,
+            r#"{"ok":true,"data":{"entries":[{"key":"s:1","value":"a"},{"key":"s:2","value":"b"}],"next_cursor":"s:2"}}"#,
+            r#"{"ok":true,"data":{"entries":[{"key":"s:3","value":"c"}],"next_cursor":null}}"#,
+        ]);
+        let client = TalonRemoteClient::connect(&format!("talon://{addr}")).unwrap();
+        let kv = client.kv().unwrap();
+
+        let keys: Vec<Vec<u8>> = kv
+            .scan_prefix(b"s:")
+            .page_size(2)
+            .map(|entry| entry.unwrap().0)
+            .collect();
+        assert_eq!(
+            keys,
+            vec![b"s:1".to_vec(), b"s:2".to_vec(), b"s:3".to_vec()]
+        );
+
+        let requests = &handle.join().unwrap()[1..];
+        assert_eq!(requests[0]["params"]["start"], "s:");
+        assert_eq!(requests[0]["params"]["end"], "s;");
+        assert_eq!(requests[0]["params"]["limit"], 2);
+        assert!(requests[0]["params"]["cursor"].is_null());
+        assert_eq!(requests[1]["params"]["cursor"], "s:2");
+    }
+
+    #[test]
+    fn remote_kv_scan_rejects_entries_without_value() {
+        let (addr, handle) = spawn_fake_server(vec![
+            LEGACY_CAPABILITIES,
+            r#"{"ok":true,"data":{"entries":[{"key":"s:1"}],"next_cursor":null}}"#,
+            r#"{"ok":true,"data":{"entries":[{"key":"s:1","value":null}],"next_cursor":null}}"#,
+        ]);
+        let client = TalonRemoteClient::connect(&format!("talon://{addr}")).unwrap();
+        let kv = client.kv().unwrap();
+
+        for _ in 0..2 {
+            let err = kv.scan_page(b"s:", None, None, 10).unwrap_err();
+            assert!(err.0.contains("remote protocol"), "{}", err.0);
+            assert!(err.0.contains("missing value"), "{}", err.0);
+        }
+        handle.join().unwrap();
+    }
+
+    #[test]
+    fn remote_kv_ttl_management() {
+        let (addr, handle) = spawn_fake_server(vec![
+            LEGACY_CAPABILITIES,
+            r#"{"ok":true,"data":{"ttl_ms":1500}}"#,
+            r#"{"ok":true,"data":{"ttl_ms":-1}}"#,
+            r#"{"ok":true,"data":{"updated":true}}"#,
+            r#"{"ok":true,"data":{"updated":false}}"#,
+            r#"{"ok":true,"data":{}}"#,
+        ]);
+        let client = TalonRemoteClient::connect(&format!("talon://{addr}")).unwrap();
+        let kv = client.kv().unwrap();
+
+        assert_eq!(kv.ttl(b"sess").unwrap(), Some(Duration::from_millis(1500)));
+        assert_eq!(kv.ttl(b"sess").unwrap(), None);
+        assert!(kv.expire(b"sess", Duration::from_secs(30)).unwrap());
+        assert!(!kv.persist(b"missing").unwrap());
+        let err = kv.ttl(b"sess").unwrap_err();
+        assert!(err.0.contains("remote protocol"), "{}", err.0);
+
+        let requests = &handle.join().unwrap()[1..];
+        assert_eq!(requests[2]["action"], "expire");
+        assert_eq!(requests[2]["params"]["ttl_ms"], 30_000);
+        assert_eq!(requests[3]["action"], "persist");
+    }
+
+    #[test]
+    fn remote_kv_watch_reads_pushed_events() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        let server = thread::spawn(move || {
+            // 主连接：只做编码协商。
+            let (mut main, _) = listener.accept().unwrap();
+            let _ = read_remote_frame(&mut main).unwrap();
+            write_remote_frame(&mut main, LEGACY_CAPABILITIES.as_bytes()).unwrap();
+            let (mut stream, _) = listener.accept().unwrap();
+            let frame = read_remote_frame(&mut stream).unwrap();
+            let cmd: serde_json::Value = serde_json::from_slice(&frame).unwrap();
+            assert_eq!(cmd["action"], "watch");
+            assert_eq!(cmd["params"]["prefix"], "flags:");
+            write_remote_frame(&mut stream, br#"{"ok":true,"data":{"watch_id":1}}"#).unwrap();
+            write_remote_frame(
+                &mut stream,
+                br#"{"ok":true,"data":{"events":[{"key":"flags:a","kind":"set","value":"on"},{"key":"flags:b","kind":"del"}]}}"#,
+            )
+            .unwrap();
+        });
+
+        let client = TalonRemoteClient::connect(&format!("talon://{addr}")).unwrap();
+        let mut watcher = client.kv().unwrap().watch(b"flags:").unwrap();
+        let first = watcher.next().unwrap().unwrap();
+        assert_eq!(
+            first,
+            KvEvent {
+                key: b"flags:a".to_vec(),
+                kind: KvEventKind::Set,
+                value: Some(b"on".to_vec()),
+            }
+        );
+        let second = watcher
+            .recv_timeout(Duration::from_secs(1))
+            .unwrap()
+            .unwrap();
+        assert_eq!(second.kind, KvEventKind::Del);
+        assert_eq!(second.value, None);
+        server.join().unwrap();
+        assert!(watcher.next().is_none());
+    }
+
+    #[test]
+    fn remote_kv_binary_payloads_use_base64() {
+        let (addr, handle) = spawn_fake_server(vec![
+            BASE64_CAPABILITIES,
+            r#"{"ok":true,"data":null}"#,
+            r#"{"ok":true,"data":{"value":"AP+A"}}"#,
+            r#"{"ok":true,"data":{"entries":[{"key":"/w==","value":"AQ=="}],"next_cursor":"/w=="}}"#,
+        ]);
+        let client = TalonRemoteClient::connect(&format!("talon://{addr}")).unwrap();
+        let kv = client.kv().unwrap();
+
+        assert!(client.supports_binary_kv().unwrap());
+        kv.set(&[0xff], &[0x00, 0xff, 0x80], None).unwrap();
+        assert_eq!(kv.get(&[0xff]).unwrap(), Some(vec![0x00, 0xff, 0x80]));
+        let page = kv.scan_page(&[0xff], None, None, 10).unwrap();
+        assert_eq!(page.entries, vec![(vec![0xff], vec![0x01])]);
+        assert_eq!(page.next_cursor, Some(vec![0xff]));
+
+        let requests = handle.join().unwrap();
+        assert_eq!(requests[0]["module"], "server");
+        assert_eq!(requests[1]["params"]["encoding"], "base64");
+        assert_eq!(requests[1]["params"]["key"], "/w==");
+        assert_eq!(requests[1]["params"]["value"], "AP+A");
+        assert_eq!(requests[2]["params"]["encoding"], "base64");
+    }
+
+    #[test]
+    fn remote_kv_legacy_server_rejects_binary_keys() {
+        let (addr, handle) = spawn_fake_server(vec![
+            LEGACY_CAPABILITIES,
+            r#"{"ok":true,"data":{"value":"v"}}"#,
+        ]);
+        let client = TalonRemoteClient::connect(&format!("talon://{addr}")).unwrap();
+        let kv = client.kv().unwrap();
+
+        let err = kv.get(&[0xff]).unwrap_err();
+        assert!(err.0.contains("does not support binary KV payloads"));
+        assert_eq!(kv.get(b"k").unwrap(), Some(b"v".to_vec()));
+
+        let requests = handle.join().unwrap();
+        assert_eq!(requests.len(), 2);
+        assert!(requests[1]["params"].get("encoding").is_none());
+    }
+
+    #[test]
+    fn remote_kv_encoding_is_not_cached_after_transient_failure() {
+        let (addr, handle) = spawn_fake_server(vec![
+            r#"{"ok":false,"error":"server busy"}"#,
+            BASE64_CAPABILITIES,
+            r#"{"ok":true,"data":{"value":"AP+A"}}"#,
+        ]);
+        let client = TalonRemoteClient::connect(&format!("talon://{addr}")).unwrap();
+
+        let err = client.supports_binary_kv().unwrap_err();
+        assert!(err.0.contains("server busy"));
+        assert!(client.supports_binary_kv().unwrap());
+        assert_eq!(
+            client.kv().unwrap().get(&[0xff]).unwrap(),
+            Some(vec![0x00, 0xff, 0x80])
+        );
+
+        let requests = handle.join().unwrap();
+        assert_eq!(requests[0]["module"], "server");
+        assert_eq!(requests[1]["module"], "server");
+        assert_eq!(requests[2]["params"]["encoding"], "base64");
+    }
+
+    #[test]
+    fn remote_prepared_statement_reuses_server_id() {
+        let (addr, handle) = spawn_fake_server(vec![
+            r#"{"ok":true,"data":{"stmt_id":9}}"#,
+            r#"{"ok":true,"data":{"rows":[[1]],"columns":["id"]}}"#,
+            r#"{"ok":true,"data":{"rows":[],"rows_affected":2}}"#,
+        ]);
+        let client = TalonRemoteClient::connect(&format!("talon://{addr}")).unwrap();
+
+        let mut stmt = client.prepare("SELECT id FROM t WHERE id = ?").unwrap();
+        let result = stmt.bind(Value::Integer(1)).query().unwrap();
+        assert_eq!(result.rows, vec![vec![Value::Integer(1)]]);
+        let mut again = client.prepare("SELECT id FROM t WHERE id = ?").unwrap();
+        assert_eq!(again.execute().unwrap().rows_affected, 2);
+
+        let requests = handle.join().unwrap();
+        assert_eq!(requests[0]["action"], "prepare");
+        assert_eq!(requests[1]["action"], "stmt_exec");
+        assert_eq!(requests[1]["params"]["stmt_id"], 9);
+        assert_eq!(requests[1]["params"]["params"].as_array().unwrap().len(), 1);
+        assert_eq!(requests[2]["params"]["stmt_id"], 9);
+        assert!(requests[2]["params"]["params"]
+            .as_array()
+            .unwrap()
+            .is_empty());
+    }
+
+    #[test]
+    fn remote_named_params_reach_query_and_statements() {
+        let (addr, handle) = spawn_fake_server(vec![
+            r#"{"ok":true,"data":{"rows":[],"rows_affected":1}}"#,
+            r#"{"ok":true,"data":{"stmt_id":3}}"#,
+            r#"{"ok":true,"data":{"rows":[[2]]}}"#,
+        ]);
+        let client = TalonRemoteClient::connect(&format!("talon://{addr}")).unwrap();
+
+        let params = BTreeMap::from([("id", Value::Integer(7))]);
+        client
+            .execute("DELETE FROM t WHERE id = :id", params)
+            .unwrap();
+        let mut stmt = client
+            .prepare("SELECT id FROM t WHERE a = @a AND b = :b")
+            .unwrap();
+        stmt.bind_named("b", 2)
+            .bind_named("@a", "x")
+            .query()
+            .unwrap();
+
+        let requests = handle.join().unwrap();
+        assert_eq!(requests[0]["params"]["sql"], "DELETE FROM t WHERE id = 7");
+        assert_eq!(
+            requests[1]["params"]["sql"],
+            "SELECT id FROM t WHERE a = ? AND b = ?"
+        );
+        assert_eq!(
+            requests[2]["params"]["params"],
+            serde_json::json!([{"Text": "x"}, {"Integer": 2}])
+        );
+    }
+
+    #[test]
+    fn remote_retired_statements_close_on_drop_and_retry_failures() {
+        let (addr, handle) = spawn_fake_server(vec![
+            r#"{"ok":true,"data":{"stmt_id":1}}"#,
+            r#"{"ok":true,"data":{"stmt_id":2}}"#,
+            r#"{"ok":false,"error":"server busy"}"#,
+            r#"{"ok":true,"data":null}"#,
+            r#"{"ok":true,"data":null}"#,
+        ]);
+        let client = TalonRemoteClient::connect(&format!("talon://{addr}")).unwrap();
+        client.set_statement_cache_capacity(1);
+
+        let first = client.prepare("SELECT 1").unwrap();
+        // 淘汰 id 1，但 `first` 仍在使用，暂不关闭。
+        drop(client.prepare("SELECT 2").unwrap());
+        // 释放 `first` 时关闭 id 1；服务端失败，id 留待重试。
+        drop(first);
+        client.set_statement_cache_capacity(0);
+
+        let requests = handle.join().unwrap();
+        let actions: Vec<_> = requests.iter().map(|r| r["action"].clone()).collect();
+        assert_eq!(actions[..2], ["prepare", "prepare"]);
+        assert_eq!(actions[2..], ["stmt_close", "stmt_close", "stmt_close"]);
+        assert_eq!(requests[2]["params"]["stmt_id"], 1);
+        assert_eq!(requests[3]["params"]["stmt_id"], 1);
+        assert_eq!(requests[4]["params"]["stmt_id"], 2);
+    }
+
+    #[test]
+    fn remote_prepare_succeeds_when_closing_an_evicted_id_fails() {
+        let (addr, handle) = spawn_fake_server(vec![
+            r#"{"ok":true,"data":{"stmt_id":1}}"#,
+            r#"{"ok":true,"data":{"stmt_id":2}}"#,
+            r#"{"ok":false,"error":"server busy"}"#,
+            r#"{"ok":true,"data":null}"#,
+        ]);
+        let client = TalonRemoteClient::connect(&format!("talon://{addr}")).unwrap();
+        client.set_statement_cache_capacity(1);
+
+        drop(client.prepare("SELECT 1").unwrap());
+        // 淘汰并关闭 id 1 失败，不影响 id 2 的 prepare 结果。
+        let second = client.prepare("SELECT 2").unwrap();
+        assert_eq!(second.sql(), "SELECT 2");
+        drop(second);
+
+        let requests = handle.join().unwrap();
+        let actions: Vec<_> = requests.iter().map(|r| r["action"].clone()).collect();
+        assert_eq!(actions, ["prepare", "prepare", "stmt_close", "stmt_close"]);
+        assert_eq!(requests[3]["params"]["stmt_id"], 1);
+    }
+
+    #[test]
+    fn remote_transaction_rolls_back_on_drop() {
+        let (addr, handle) = spawn_fake_server(vec![
+            r#"{"ok":true,"data":{"txn_id":4}}"#,
+            r#"{"ok":true,"data":{"rows":[]}}"#,
+            r#"{"ok":true,"data":null}"#,
+        ]);
+        let client = TalonRemoteClient::connect(&format!("talon://{addr}")).unwrap();
+        {
+            let tx = client.begin().unwrap();
+            tx.run_sql("DELETE FROM t").unwrap();
+        }
+
+        let requests = handle.join().unwrap();
+        assert_eq!(requests[0]["module"], "txn");
+        assert_eq!(requests[1]["params"]["txn_id"], 4);
+        assert_eq!(requests[2]["action"], "rollback");
+        assert_eq!(requests[2]["params"]["txn_id"], 4);
+    }
+
+    #[test]
+    fn remote_transaction_table_exists_reads_inside_the_transaction() {
+        let (addr, handle) = spawn_fake_server(vec![
+            r#"{"ok":true,"data":{"txn_id":6}}"#,
+            r#"{"ok":true,"data":{"tables":[{"name":"fresh"}]}}"#,
+            r#"{"ok":true,"data":null}"#,
+        ]);
+        let client = TalonRemoteClient::connect(&format!("talon://{addr}")).unwrap();
+        {
+            let tx = client.begin().unwrap();
+            assert!(tx.sql_table_exists("fresh").unwrap());
+        }
+
+        let requests = handle.join().unwrap();
+        assert_eq!(requests[1]["module"], "schema");
+        assert_eq!(requests[1]["params"]["txn_id"], 6);
+    }
+
+    #[test]
+    fn remote_transaction_retries_only_coded_conflicts() {
+        let (addr, handle) = spawn_fake_server(vec![
+            r#"{"ok":true,"data":{"txn_id":1}}"#,
+            r#"{"ok":false,"error":"write skew","code":"txn_conflict"}"#,
+            r#"{"ok":true,"data":null}"#,
+            r#"{"ok":true,"data":{"txn_id":2}}"#,
+            r#"{"ok":false,"error":"constraint conflict on t.id"}"#,
+            r#"{"ok":true,"data":null}"#,
+        ]);
+        let client = TalonRemoteClient::connect(&format!("talon://{addr}")).unwrap();
+
+        let err = client.transaction(|_| Ok(())).unwrap_err();
+        assert!(err.0.contains("constraint conflict"));
+
+        let requests = handle.join().unwrap();
+        let actions: Vec<_> = requests.iter().map(|r| r["action"].clone()).collect();
+        assert_eq!(actions[..3], ["begin", "commit", "rollback"]);
+        assert_eq!(actions[3..], ["begin", "commit", "rollback"]);
+        assert_eq!(requests[2]["params"]["txn_id"], 1);
+    }
+
+    #[test]
+    fn remote_query_iter_fetches_pages_until_done() {
+        let (addr, handle) = spawn_fake_server(vec![
+            r#"{"ok":true,"data":{"cursor_id":3}}"#,
+            r#"{"ok":true,"data":{"rows":[[1],[2]],"done":false}}"#,
+            r#"{"ok":true,"data":{"rows":[[3]],"done":true}}"#,
+            r#"{"ok":true,"data":null}"#,
+        ]);
+        let client = TalonRemoteClient::connect(&format!("talon://{addr}")).unwrap();
+        let rows: Vec<Vec<Value>> = client
+            .query_iter("SELECT id FROM big", &[])
+            .unwrap()
+            .page_size(2)
+            .collect::<Result<_, _>>()
+            .unwrap();
+        assert_eq!(rows.len(), 3);
+        assert_eq!(rows[2], vec![Value::Integer(3)]);
+
+        let requests = handle.join().unwrap();
+        assert_eq!(requests[0]["action"], "cursor_open");
+        assert_eq!(requests[1]["params"]["max_rows"], 2);
+        assert_eq!(requests[2]["params"]["cursor_id"], 3);
+        assert_eq!(requests[3]["action"], "cursor_close");
+    }
+
+    #[test]
+    fn remote_vector_batch_sends_flat_buffer() {
+        let (addr, handle) = spawn_fake_server(vec![
+            r#"{"ok":true,"data":null}"#,
+            r#"{"ok":true,"data":{"results":[{"id":2,"distance":0.5}]}}"#,
+        ]);
+        let client = TalonRemoteClient::connect(&format!("talon://{addr}")).unwrap();
+        let vectors = client.vector("emb").unwrap();
+
+        let mut progress = Vec::new();
+        vectors
+            .insert_batch_with_progress(&[(1, &[1.0, 0.0]), (2, &[0.0, 1.0])], |done, total| {
+                progress.push((done, total))
+            })
+            .unwrap();
+        assert_eq!(progress, vec![(2, 2)]);
+        assert_eq!(
+            vectors.search(&[0.0, 1.0], 1, "cosine").unwrap(),
+            vec![(2, 0.5)]
+        );
+
+        let requests = handle.join().unwrap();
+        assert_eq!(requests[0]["action"], "insert_batch");
+        assert_eq!(requests[0]["params"]["dim"], 2);
+        assert_eq!(requests[0]["params"]["ids"], serde_json::json!([1, 2]));
+        let flat = BASE64
+            .decode(requests[0]["params"]["vectors"].as_str().unwrap())
+            .unwrap();
+        assert_eq!(flat.len(), 16);
+        assert_eq!(&flat[8..12], &0.0f32.to_le_bytes());
+        assert_eq!(requests[1]["params"]["k"], 1);
+    }
+
+    #[test]
+    fn remote_vector_search_and_count_reject_malformed_replies() {
+        let (addr, handle) = spawn_fake_server(vec![
+            r#"{"ok":true,"data":{"results":[{"id":1,"distance":0.5},{"id":"2"}]}}"#,
+            r#"{"ok":true,"data":{}}"#,
+            r#"{"ok":true,"data":{"count":3}}"#,
+            r#"{"ok":true,"data":null}"#,
+        ]);
+        let client = TalonRemoteClient::connect(&format!("talon://{addr}")).unwrap();
+        let vectors = client.vector("emb").unwrap();
+
+        let err = vectors.search(&[1.0], 2, "cosine").unwrap_err();
+        assert!(err.0.contains("malformed vector search hit"));
+        let err = vectors.search(&[1.0], 2, "cosine").unwrap_err();
+        assert!(err.0.contains("missing results"));
+        assert_eq!(vectors.count().unwrap(), 3);
+        let err = vectors.count().unwrap_err();
+        assert!(err.0.contains("missing count"));
+        handle.join().unwrap();
+    }
+
+    #[test]
+    fn remote_vector_index_lifecycle() {
+        let (addr, handle) = spawn_fake_server(vec![
+            r#"{"ok":true,"data":null}"#,
+            r#"{"ok":true,"data":{"indexes":[{"name":"emb","dim":3,"metric":"dot","count":0}]}}"#,
+            r#"{"ok":true,"data":{"count":0,"memory_bytes":512,"state":"ready","dim":3,"metric":"dot"}}"#,
+            r#"{"ok":false,"error":"index not found"}"#,
+        ]);
+        let client = TalonRemoteClient::connect(&format!("talon://{addr}")).unwrap();
+        let vectors = client.vector("emb").unwrap();
+
+        vectors
+            .create_index(&VectorIndexConfig::new(3).metric(VectorMetric::Dot))
+            .unwrap();
+        assert_eq!(client.list_vector_indexes().unwrap()[0].metric, "dot");
+        let stats = vectors.index_stats().unwrap();
+        assert_eq!(stats.state, VectorIndexState::Ready);
+        assert_eq!(stats.memory_bytes, 512);
+        assert!(vectors.rebuild().is_err());
+
+        let requests = handle.join().unwrap();
+        assert_eq!(requests[0]["action"], "create_index");
+        assert_eq!(requests[0]["params"]["ef_construction"], 200);
+        assert_eq!(requests[2]["params"]["index"], "emb");
+        assert_eq!(requests[3]["action"], "rebuild");
+    }
+
+    #[test]
+    fn remote_vector_index_replies_are_checked_as_protocol() {
+        let (addr, handle) = spawn_fake_server(vec![
+            r#"{"ok":true,"data":{}}"#,
+            r#"{"ok":true,"data":{"count":"many"}}"#,
+        ]);
+        let client = TalonRemoteClient::connect(&format!("talon://{addr}")).unwrap();
+
+        let err = client.list_vector_indexes().unwrap_err();
+        assert!(err.0.starts_with("remote protocol: "), "{}", err.0);
+        let err = client.vector("emb").unwrap().index_stats().unwrap_err();
+        assert!(err.0.starts_with("remote protocol: "), "{}", err.0);
+        handle.join().unwrap();
+    }
+
+    #[test]
+    fn remote_vector_filtered_search_returns_payloads() {
+        let (addr, handle) = spawn_fake_server(vec![
+            r#"{"ok":true,"data":null}"#,
+            r#"{"ok":true,"data":{"results":[{"id":7,"distance":0.1,"payload":{"lang":"en"}}]}}"#,
+        ]);
+        let client = TalonRemoteClient::connect(&format!("talon://{addr}")).unwrap();
+        let vectors = client.vector("emb").unwrap();
+
+        vectors
+            .insert_with_payload(7, &[1.0, 0.0], &serde_json::json!({"lang": "en"}))
+            .unwrap();
+        let hits = vectors
+            .search_with_payload(
+                &[1.0, 0.0],
+                5,
+                VectorMetric::Cosine,
+                &VectorFilter::eq("lang", "en"),
+            )
+            .unwrap();
+        assert_eq!(hits[0].id, 7);
+        assert_eq!(hits[0].payload.as_ref().unwrap()["lang"], "en");
+
+        let requests = handle.join().unwrap();
+        assert_eq!(requests[0]["params"]["payload"]["lang"], "en");
+        assert_eq!(requests[1]["action"], "search_with");
+        assert_eq!(requests[1]["params"]["metric"], "cosine");
+        assert_eq!(requests[1]["params"]["filter"]["op"], "eq");
+        assert_eq!(requests[1]["params"]["with_payload"], true);
+    }
+
+    #[test]
+    fn remote_vector_filtered_search_rejects_malformed_hits() {
+        let (addr, handle) =
+            spawn_fake_server(vec![r#"{"ok":true,"data":{"results":[{"id":"7"}]}}"#]);
+        let client = TalonRemoteClient::connect(&format!("talon://{addr}")).unwrap();
+
+        let err = client
+            .vector("emb")
+            .unwrap()
+            .search_with_payload(&[1.0], 1, VectorMetric::Cosine, &VectorFilter::eq("a", 1))
+            .unwrap_err();
+        assert!(err.0.starts_with("remote protocol: "), "{}", err.0);
+        handle.join().unwrap();
+    }
+
+    #[test]
+    fn remote_vector_get_upsert_and_paginated_ids() {
+        let (addr, handle) = spawn_fake_server(vec![
+            r#"{"ok":true,"data":null}"#,
+            r#"{"ok":true,"data":{"vector":[0.25,0.75]}}"#,
+            r#"{"ok":true,"data":{"exists":false}}"#,
+            r#"{"ok":true,"data":{"ids":[1,4],"next":4}}"#,
+        ]);
+        let client = TalonRemoteClient::connect(&format!("talon://{addr}")).unwrap();
+        let vectors = client.vector("emb").unwrap();
+
+        vectors.upsert(4, &[0.25, 0.75]).unwrap();
+        assert_eq!(vectors.get(4).unwrap(), Some(vec![0.25, 0.75]));
+        assert!(!vectors.contains(9).unwrap());
+        let page = vectors.ids(None, 2).unwrap();
+        assert_eq!(page.ids, vec![1, 4]);
+        assert_eq!(page.next, Some(4));
+
+        let requests = handle.join().unwrap();
+        assert_eq!(requests[0]["action"], "upsert");
+        assert_eq!(requests[1]["params"]["id"], 4);
+        assert_eq!(requests[2]["action"], "contains");
+        assert_eq!(requests[3]["params"]["limit"], 2);
+    }
+
+    #[test]
+    fn remote_vector_id_replies_are_checked_as_protocol() {
+        let (addr, handle) = spawn_fake_server(vec![
+            r#"{"ok":true,"data":{"vector":["x"]}}"#,
+            r#"{"ok":true,"data":{"exists":1}}"#,
+            r#"{"ok":true,"data":{"next":5}}"#,
+        ]);
+        let client = TalonRemoteClient::connect(&format!("talon://{addr}")).unwrap();
+        let vectors = client.vector("emb").unwrap();
+
+        let errors = [
+            vectors.get(1).unwrap_err(),
+            vectors.contains(1).unwrap_err(),
+            vectors.ids(None, 10).unwrap_err(),
+        ];
+        for err in errors {
+            assert!(err.0.starts_with("remote protocol: "), "{}", err.0);
+        }
+        handle.join().unwrap();
+    }
+
+    #[test]
+    fn remote_kv_namespace_drop_all_scans_then_deletes() {
+        let (addr, handle) = spawn_fake_server(vec![
+            LEGACY_CAPABILITIES,
+            r#"{"ok":true,"data":{"entries":[{"key":"t:a","value":"1"},{"key":"t:b","value":"2"}],"next_cursor":null}}"#,
+            r#"{"ok":true,"data":{"deleted":2}}"#,
+            r#"{"ok":true,"data":{"entries":[],"next_cursor":null}}"#,
+        ]);
+        let client = TalonRemoteClient::connect(&format!("talon://{addr}")).unwrap();
+
+        assert_eq!(client.kv_ns("t").unwrap().drop_all().unwrap(), 2);
+
+        let requests = &handle.join().unwrap()[1..];
+        assert_eq!(requests[0]["action"], "scan");
+        assert_eq!(requests[0]["params"]["start"], "t:");
+        assert_eq!(requests[0]["params"]["end"], "t;");
+        assert_eq!(
+            requests[1]["params"]["keys"],
+            serde_json::json!(["t:a", "t:b"])
+        );
+    }
+
+    #[test]
+    fn remote_kv_del_many_requires_deleted_count() {
+        let (addr, handle) = spawn_fake_server(vec![
+            LEGACY_CAPABILITIES,
+            r#"{"ok":true,"data":{"deleted":1}}"#,
+            r#"{"ok":true,"data":{}}"#,
+        ]);
+        let client = TalonRemoteClient::connect(&format!("talon://{addr}")).unwrap();
+        let kv = client.kv().unwrap();
+
+        assert_eq!(kv.del_many(&["a", "b"]).unwrap(), 1);
+        let err = kv.del_many(&["a"]).unwrap_err();
+        assert!(err.0.contains("remote protocol"), "{}", err.0);
+        assert_eq!(handle.join().unwrap()[1]["action"], "mdel");
+    }
+
     #[test]
     fn remote_client_sql_kv_mq_roundtrip() {
         let db = Talon::open_anon().unwrap();
@@ -2829,6 +5379,396 @@ mod remote_tests {
     }
 }
 
+#[cfg(test)]
+mod kv_tests {
+    use super::*;
+
+    #[test]
+    fn kv_batch_binary_encoding() {
+        let keys_bin = encode_kv_keys(&["a", "bc"]);
+        assert_eq!(
+            keys_bin,
+            [2, 0, 0, 0, 1, 0, 0, 0, b'a', 2, 0, 0, 0, b'b', b'c']
+        );
+
+        let entries_bin = encode_kv_entries(&[("k", "v", Some(7))]).unwrap();
+        assert_eq!(&entries_bin[..4], &[1, 0, 0, 0]);
+        assert_eq!(&entries_bin[entries_bin.len() - 8..], &7i64.to_le_bytes());
+        let err = encode_kv_entries(&[("k", "v", Some(u64::MAX))]).unwrap_err();
+        assert!(err.0.contains("out of range"));
+
+        let values = decode_kv_values_bin(&[2, 0, 0, 0, 0, 1, 2, 0, 0, 0, b'h', b'i'], 2).unwrap();
+        assert_eq!(values, vec![None, Some(b"hi".to_vec())]);
+        assert!(decode_kv_values_bin(&[1, 0, 0, 0, 0], 2).is_err());
+    }
+
+    #[test]
+    fn kv_page_and_event_binary_decoding() {
+        let page = decode_kv_page_bin(&[
+            1, 0, 0, 0, // count
+            1, 0, 0, 0, b'a', 2, 0, 0, 0, b'v', b'1', // entry
+            1, 0, 0, 0, b'a', // cursor
+        ])
+        .unwrap();
+        assert_eq!(page.entries, vec![(b"a".to_vec(), b"v1".to_vec())]);
+        assert_eq!(page.next_cursor, Some(b"a".to_vec()));
+        let last = decode_kv_page_bin(&[0, 0, 0, 0, 0, 0, 0, 0]).unwrap();
+        assert_eq!(last.next_cursor, None);
+        let err = decode_kv_page_bin(&[1, 0, 0, 0, 5, 0, 0, 0, b'a']).unwrap_err();
+        assert_eq!(err.0, "truncated kv page data");
+
+        let events = decode_kv_events_bin(&[
+            2, 0, 0, 0, // count
+            0, 1, 0, 0, 0, b'k', 1, 1, 0, 0, 0, b'v', // Set k=v
+            1, 1, 0, 0, 0, b'k', 0, // Del k
+        ])
+        .unwrap();
+        assert_eq!(events[0].kind, KvEventKind::Set);
+        assert_eq!(events[0].value, Some(b"v".to_vec()));
+        assert_eq!(events[1].kind, KvEventKind::Del);
+        assert_eq!(events[1].value, None);
+        let err = decode_kv_events_bin(&[1, 0, 0, 0, 0, 1, 0]).unwrap_err();
+        assert_eq!(err.0, "truncated kv event len");
+    }
+
+    #[test]
+    fn kv_ttl_millis_round_up_and_sentinels() {
+        assert_eq!(kv_ttl_millis(Duration::from_micros(1)).unwrap(), 1);
+        assert_eq!(kv_ttl_millis(Duration::from_micros(1500)).unwrap(), 2);
+        assert_eq!(kv_ttl_millis(Duration::from_secs(3)).unwrap(), 3000);
+        assert!(kv_ttl_millis(Duration::MAX).is_err());
+
+        assert_eq!(kv_ttl_from_millis(-1).unwrap(), None);
+        assert_eq!(kv_ttl_from_millis(-2).unwrap(), None);
+        assert_eq!(
+            kv_ttl_from_millis(250).unwrap(),
+            Some(Duration::from_millis(250))
+        );
+        assert!(kv_ttl_from_millis(-7).is_err());
+    }
+}
+
+/// 走真实 FFI 的嵌入式路径测试（需要链接完整的 libtalon）。
+#[cfg(test)]
+mod embedded_tests {
+    use super::*;
+
+    #[test]
+    fn embedded_kv_atomic_ops() {
+        let db = Talon::open_anon().unwrap();
+        let kv = db.kv().unwrap();
+
+        assert_eq!(kv.incr_by(b"counter", 5).unwrap(), 5);
+        assert_eq!(kv.decr_by(b"counter", 2).unwrap(), 3);
+        assert!(kv.decr_by(b"counter", i64::MIN).is_err());
+
+        assert!(kv.set_nx(b"once", b"a", None).unwrap());
+        assert!(!kv.set_nx(b"once", b"b", None).unwrap());
+        assert_eq!(kv.get(b"once").unwrap(), Some(b"a".to_vec()));
+
+        assert_eq!(kv.get_set(b"once", b"c").unwrap(), Some(b"a".to_vec()));
+        assert_eq!(kv.get_set(b"fresh", b"x").unwrap(), None);
+
+        assert!(!kv
+            .compare_and_swap(b"once", Some(b"a"), Some(b"d"), None)
+            .unwrap());
+        assert!(kv
+            .compare_and_swap(b"once", Some(b"c"), Some(b"d"), None)
+            .unwrap());
+        assert!(kv.compare_and_swap(b"new", None, Some(b"n"), None).unwrap());
+        assert!(kv.compare_and_swap(b"new", Some(b"n"), None, None).unwrap());
+        assert_eq!(kv.get(b"new").unwrap(), None);
+        std::mem::forget(db);
+    }
+    #[test]
+    fn embedded_kv_scan_prefix_and_pages() {
+        let db = Talon::open_anon().unwrap();
+        let kv = db.kv().unwrap();
+        for key in ["t:a", "t:b", "u:a"] {
+            kv.set(key.as_bytes(), b"v", None).unwrap();
+        }
+
+        let scanned: Vec<_> = kv
+            .scan_prefix(b"t:")
+            .map(|entry| entry.map(|(k, _)| k))
+            .collect::<Result<_, _>>()
+            .unwrap();
+        assert_eq!(scanned, vec![b"t:a".to_vec(), b"t:b".to_vec()]);
+
+        let page = kv.scan_page(b"t:", Some(b"t;"), None, 1).unwrap();
+        assert_eq!(page.entries, vec![(b"t:a".to_vec(), b"v".to_vec())]);
+        let last = kv
+            .scan_page(b"t:", Some(b"t;"), page.next_cursor.as_deref(), 10)
+            .unwrap();
+        assert_eq!(last.entries, vec![(b"t:b".to_vec(), b"v".to_vec())]);
+        assert_eq!(last.next_cursor, None);
+        std::mem::forget(db);
+    }
+    #[test]
+    fn embedded_kv_batch_ops() {
+        let db = Talon::open_anon().unwrap();
+        let kv = db.kv().unwrap();
+
+        kv.set_many(&[("a", "1", None), ("b", "2", None)], true)
+            .unwrap();
+        assert_eq!(
+            kv.get_many(&["a", "missing", "b"]).unwrap(),
+            vec![Some(b"1".to_vec()), None, Some(b"2".to_vec())]
+        );
+        assert_eq!(kv.del_many(&["a", "b", "missing"]).unwrap(), 2);
+        assert_eq!(kv.get_many(&["a"]).unwrap(), vec![None]);
+        std::mem::forget(db);
+    }
+    #[test]
+    fn embedded_kv_ttl_lifecycle() {
+        let db = Talon::open_anon().unwrap();
+        let kv = db.kv().unwrap();
+        kv.set(b"k", b"v", None).unwrap();
+
+        assert_eq!(kv.ttl(b"k").unwrap(), None);
+        assert!(kv.expire(b"k", Duration::from_secs(60)).unwrap());
+        let ttl = kv.ttl(b"k").unwrap().unwrap();
+        assert!(ttl <= Duration::from_secs(60));
+        assert!(kv.persist(b"k").unwrap());
+        assert_eq!(kv.ttl(b"k").unwrap(), None);
+        assert!(!kv.persist(b"k").unwrap());
+        assert!(!kv.expire(b"missing", Duration::from_secs(60)).unwrap());
+        std::mem::forget(db);
+    }
+    #[test]
+    fn embedded_prepared_statement_reuse() {
+        let db = Talon::open_anon().unwrap();
+        db.run_sql("CREATE TABLE embedded_stmt (id INT, name TEXT)")
+            .unwrap();
+
+        let mut stmt = db
+            .prepare("INSERT INTO embedded_stmt VALUES (?, ?)")
+            .unwrap();
+        for (id, name) in [(1, "alice"), (2, "bob")] {
+            let res = stmt.clear_bindings().bind(id).bind(name).execute().unwrap();
+            assert_eq!(res.rows_affected, 1);
+        }
+        drop(stmt);
+        assert_eq!(db.statement_cache_len(), 1);
+
+        let mut select = db
+            .prepare("SELECT name FROM embedded_stmt WHERE id = ?")
+            .unwrap();
+        let res = select.bind(2).query().unwrap();
+        assert_eq!(res.rows, vec![vec![Value::Text("bob".into())]]);
+        std::mem::forget(db);
+    }
+
+    #[test]
+    fn embedded_drop_clears_a_poisoned_statement_cache() {
+        let db = Talon::open_anon().unwrap();
+        drop(db.prepare("SELECT 1").unwrap());
+        let poison = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+            let _guard = db.stmt_cache.lock().unwrap();
+            panic!("poison the statement cache");
+        }));
+        assert!(poison.is_err());
+        assert!(db.stmt_cache.is_poisoned());
+        // 缓存中的语句必须在 talon_close 之前关闭。
+        drop(db);
+    }
+    #[test]
+    fn embedded_txn_commit_and_rollback() {
+        let db = Talon::open_anon().unwrap();
+        db.run_sql("CREATE TABLE embedded_txn (id INT)").unwrap();
+
+        let tx = db.begin().unwrap();
+        tx.run_sql("INSERT INTO embedded_txn VALUES (1)").unwrap();
+        tx.rollback().unwrap();
+        {
+            let tx = db.begin().unwrap();
+            tx.run_sql("INSERT INTO embedded_txn VALUES (2)").unwrap();
+        }
+        db.transaction(|tx| {
+            tx.run_sql("INSERT INTO embedded_txn VALUES (3)")?;
+            tx.kv_set(b"txn:k", b"v", None)
+        })
+        .unwrap();
+
+        assert_eq!(
+            db.run_sql("SELECT id FROM embedded_txn").unwrap(),
+            vec![vec![Value::Integer(3)]]
+        );
+        assert_eq!(db.kv().unwrap().get(b"txn:k").unwrap(), Some(b"v".to_vec()));
+        std::mem::forget(db);
+    }
+    #[test]
+    fn embedded_cursor_pages_through_rows() {
+        let db = Talon::open_anon().unwrap();
+        db.run_sql("CREATE TABLE embedded_cursor (id INT)").unwrap();
+        for id in 1..=3 {
+            db.run_sql(&format!("INSERT INTO embedded_cursor VALUES ({id})"))
+                .unwrap();
+        }
+
+        let rows = db
+            .query_iter("SELECT id FROM embedded_cursor ORDER BY id", &[])
+            .unwrap()
+            .page_size(1)
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        assert_eq!(
+            rows,
+            vec![
+                vec![Value::Integer(1)],
+                vec![Value::Integer(2)],
+                vec![Value::Integer(3)],
+            ]
+        );
+        std::mem::forget(db);
+    }
+    #[test]
+    fn embedded_vector_insert_batch() {
+        let db = Talon::open_anon().unwrap();
+        let vec = db.vector("embedded_batch").unwrap();
+
+        let mut progress = Vec::new();
+        vec.insert_batch_with_progress(
+            &[
+                (1, &[1.0, 0.0][..]),
+                (2, &[0.0, 1.0][..]),
+                (3, &[1.0, 1.0][..]),
+            ],
+            |done, total| progress.push((done, total)),
+        )
+        .unwrap();
+        assert_eq!(progress.last(), Some(&(3, 3)));
+        assert_eq!(vec.count().unwrap(), 3);
+        assert!(vec
+            .insert_batch(&[(4, &[1.0][..]), (5, &[1.0, 0.0][..])])
+            .is_err());
+        std::mem::forget(db);
+    }
+    #[test]
+    fn embedded_vector_index_lifecycle() {
+        let db = Talon::open_anon().unwrap();
+        let vec = db.vector("embedded_idx").unwrap();
+        vec.create_index(&VectorIndexConfig::new(2)).unwrap();
+        vec.insert(1, &[1.0, 0.0]).unwrap();
+
+        let stats = vec.index_stats().unwrap();
+        assert_eq!(stats.count, 1);
+        assert_eq!(stats.dim, 2);
+        assert!(db
+            .list_vector_indexes()
+            .unwrap()
+            .iter()
+            .any(|i| i.name == "embedded_idx"));
+
+        vec.drop_index().unwrap();
+        assert!(!db
+            .list_vector_indexes()
+            .unwrap()
+            .iter()
+            .any(|i| i.name == "embedded_idx"));
+        std::mem::forget(db);
+    }
+    #[test]
+    fn embedded_vector_get_upsert_and_ids() {
+        let db = Talon::open_anon().unwrap();
+        let vec = db.vector("embedded_ids").unwrap();
+        vec.create_index(&VectorIndexConfig::new(2)).unwrap();
+        vec.insert(1, &[1.0, 0.0]).unwrap();
+        vec.insert(2, &[0.0, 1.0]).unwrap();
+
+        vec.upsert(1, &[0.5, 0.5]).unwrap();
+        assert_eq!(vec.get(1).unwrap(), Some(vec![0.5, 0.5]));
+        assert_eq!(vec.get(9).unwrap(), None);
+        assert!(vec.contains(2).unwrap());
+        assert!(!vec.contains(9).unwrap());
+
+        let page = vec.ids(None, 1).unwrap();
+        assert_eq!(page.ids, vec![1]);
+        let rest = vec.ids(page.next, 10).unwrap();
+        assert_eq!(rest.ids, vec![2]);
+        assert_eq!(rest.next, None);
+        std::mem::forget(db);
+    }
+}
+
+// ── KV 类型化编解码 ────────────────────────────────────────────────────────
+
+mod codec;
+
+pub use codec::*;
+
+// ── KV 命名空间 ────────────────────────────────────────────────────────────
+
+mod namespace;
+
+pub use namespace::*;
+
+// ── SQL 扩展：Value 转换 / 参数 / 行映射 / 预编译语句 / 事务 / 游标 / 批量写入 / EXPLAIN
+
+mod bulk;
+mod cursor;
+mod explain;
+mod params;
+mod result;
+mod row;
+mod stmt;
+mod txn;
+mod value;
+
+pub use bulk::*;
+pub use cursor::*;
+pub use explain::*;
+pub use params::*;
+pub use result::*;
+pub use row::*;
+pub use stmt::*;
+pub use txn::*;
+pub use value::*;
+
+/// `#[derive(FromRow)]`：按字段声明顺序映射结果行（需启用 `derive` feature）。
+#[cfg(feature = "derive")]
+pub use talon_sys_derive::FromRow;
+
+// 派生宏生成 `::talon_sys::…` 路径，crate 内测试需要该别名。
+#[cfg(test)]
+extern crate self as talon_sys;
+
+// ── Schema 迁移 ────────────────────────────────────────────────────────────
+
+mod migrate;
+
+pub use migrate::*;
+
+// ── Schema 自省 ────────────────────────────────────────────────────────────
+// 表列类型 `schema::ColumnInfo` 与结果集 `ColumnInfo` 同名，不整体 glob 导出。
+
+pub mod schema;
+
+pub use schema::{
+    FtsIndexInfo, GraphInfo, IndexInfo, Schema, TableInfo, TopicInfo, VectorIndexInfo,
+};
+
+// ── 向量扩展 ──────────────────────────────────────────────────────────────
+
+mod vector;
+
+pub use vector::*;
+
+// ── 分布式锁 ──────────────────────────────────────────────────────────────
+
+mod lock;
+
+pub use lock::*;
+
+// ── 限流 ──────────────────────────────────────────────────────────────────
+
+mod ratelimit;
+
+pub use ratelimit::*;
+
+#[cfg(test)]
+mod test_support;
+
 // ── EvoCore 封装（条件编译）──────────────────────────────────────────────────
 //
 // 启用 `evocore` feature 后，通过 `module:"evo"` 命令访问 EvoCore 进化引擎。
@@ -0,0 +1,361 @@
+/*
+ * Copyright (c) 2026 Talon Contributors
+ * Author: dark.lijin@gmail.com
+ * Licensed under the Talon Community Dual License Agreement.
+ * See the LICENSE file in the project root for full license information.
+ */
+//! 分布式锁 / 租约 — 基于 KV `set_nx` + TTL + compare-and-swap。
+//!
+//! - 锁 key：`_talon_lock:{name}`，`set_nx` 写入持有者随机 token，带 TTL；
+//! - fencing token：加锁后自增 `_talon_lock_fence:{name}`，再用 CAS 把锁值从
+//!   `owner` 换成 `owner/token`。CAS 失败说明锁在两步之间已过期易主，本次加锁作废，
+//!   因此生效的 token 一定大于之前所有持有者的 token，下游存储可据此拒绝过期持有者的写入；
+//! - 续约：后台线程每 `ttl / 3` 用 CAS 刷新 TTL。CAS 不匹配即视为锁已丢失；
+//!   网络抖动等错误在租约到期前持续重试，超过租约仍未成功才视为丢失；
+//! - 释放：drop 时 CAS(锁值 → 删除)，只会删除自己持有的锁。
+
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::mpsc::{self, RecvTimeoutError};
+use std::sync::Arc;
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use crate::{KvBackend, Talon, TalonError, TalonRemoteClient};
+
+/// 加锁失败后的重试间隔。
+const LOCK_RETRY_INTERVAL: Duration = Duration::from_millis(50);
+
+fn lock_key(name: &str) -> Vec<u8> {
+    format!("_talon_lock:{name}").into_bytes()
+}
+
+fn fence_key(name: &str) -> Vec<u8> {
+    format!("_talon_lock_fence:{name}").into_bytes()
+}
+
+/// 进程内唯一的持有者 token：`pid-纳秒时间戳-序号`。
+fn owner_token() -> Vec<u8> {
+    static SEQ: AtomicU64 = AtomicU64::new(0);
+    let nanos = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_nanos();
+    let seq = SEQ.fetch_add(1, Ordering::Relaxed);
+    format!("{}-{nanos}-{seq}", std::process::id()).into_bytes()
+}
+
+/// TTL 向上取整到秒（KV TTL 精度为秒），最少 1 秒。
+fn ttl_secs(ttl: Duration) -> u64 {
+    let secs = ttl.as_secs() + u64::from(ttl.subsec_nanos() > 0);
+    secs.max(1)
+}
+
+/// 带 fencing token 的锁值：`owner/token`。
+fn lock_value(owner: &[u8], token: u64) -> Vec<u8> {
+    let mut value = owner.to_vec();
+    value.extend_from_slice(format!("/{token}").as_bytes());
+    value
+}
+
+/// 分布式锁守卫：持有期间后台线程自动续约，drop 时释放。
+pub struct TalonLock<B: KvBackend + 'static> {
+    backend: Arc<B>,
+    name: String,
+    /// 当前锁值（`owner/token`），续约与释放均以此做 CAS。
+    value: Vec<u8>,
+    fencing_token: u64,
+    held: Arc<AtomicBool>,
+    stop: Option<mpsc::Sender<()>>,
+    renewer: Option<JoinHandle<()>>,
+}
+
+impl<B: KvBackend + 'static> TalonLock<B> {
+    /// 尝试加锁一次；锁已被他人持有时返回 `Ok(None)`。
+    pub fn try_acquire(
+        backend: Arc<B>,
+        name: &str,
+        ttl: Duration,
+    ) -> Result<Option<Self>, TalonError> {
+        let owner = owner_token();
+        let key = lock_key(name);
+        let acquired_at = Instant::now();
+        if !backend.kv_set_nx(&key, &owner, Some(ttl_secs(ttl)))? {
+            return Ok(None);
+        }
+        let fencing_token = match backend.kv_incr_by(&fence_key(name), 1) {
+            Ok(token) => token as u64,
+            Err(e) => {
+                let _ = backend.kv_compare_and_swap(&key, Some(&owner), None, None);
+                return Err(e);
+            }
+        };
+        let value = lock_value(&owner, fencing_token);
+        match backend.kv_compare_and_swap(&key, Some(&owner), Some(&value), Some(ttl_secs(ttl))) {
+            Ok(true) => {}
+            // 取 token 期间锁已过期并被他人获得，本次 token 作废。
+            Ok(false) => return Ok(None),
+            Err(e) => {
+                let _ = backend.kv_compare_and_swap(&key, Some(&owner), None, None);
+                return Err(e);
+            }
+        }
+        let held = Arc::new(AtomicBool::new(true));
+        let (stop, stop_rx) = mpsc::channel();
+        let renewer = {
+            let backend = Arc::clone(&backend);
+            let held = Arc::clone(&held);
+            let value = value.clone();
+            let interval = (ttl / 3).max(Duration::from_millis(10));
+            let secs = ttl_secs(ttl);
+            thread::spawn(move || {
+                // 租约到期时刻：以最近一次成功续约的发起时间计。
+                let mut lease_end = acquired_at + ttl;
+                let mut wait = interval;
+                loop {
+                    match stop_rx.recv_timeout(wait) {
+                        Err(RecvTimeoutError::Timeout) => {}
+                        Ok(()) | Err(RecvTimeoutError::Disconnected) => return,
+                    }
+                    let attempt = Instant::now();
+                    match backend.kv_compare_and_swap(&key, Some(&value), Some(&value), Some(secs))
+                    {
+                        Ok(true) => {
+                            lease_end = attempt + ttl;
+                            wait = interval;
+                        }
+                        Ok(false) => break,
+                        Err(_) if attempt < lease_end => {
+                            wait = LOCK_RETRY_INTERVAL.min(lease_end - attempt);
+                        }
+                        Err(_) => break,
+                    }
+                }
+                held.store(false, Ordering::SeqCst);
+            })
+        };
+        Ok(Some(Self {
+            backend,
+            name: name.to_string(),
+            value,
+            fencing_token,
+            held,
+            stop: Some(stop),
+            renewer: Some(renewer),
+        }))
+    }
+
+    /// 加锁，锁被占用时最多等待 `timeout`。
+    pub fn acquire_timeout(
+        backend: Arc<B>,
+        name: &str,
+        ttl: Duration,
+        timeout: Duration,
+    ) -> Result<Self, TalonError> {
+        let deadline = Instant::now() + timeout;
+        loop {
+            if let Some(lock) = Self::try_acquire(Arc::clone(&backend), name, ttl)? {
+                return Ok(lock);
+            }
+            let now = Instant::now();
+            if now >= deadline {
+                return Err(TalonError(format!(
+                    "lock {name}: timed out after {timeout:?}"
+                )));
+            }
+            thread::sleep(LOCK_RETRY_INTERVAL.min(deadline - now));
+        }
+    }
+
+    /// 加锁，锁被占用时阻塞等待直到获取成功。
+    pub fn acquire(backend: Arc<B>, name: &str, ttl: Duration) -> Result<Self, TalonError> {
+        loop {
+            if let Some(lock) = Self::try_acquire(Arc::clone(&backend), name, ttl)? {
+                return Ok(lock);
+            }
+            thread::sleep(LOCK_RETRY_INTERVAL);
+        }
+    }
+
+    /// 锁名称。
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// 本次加锁获得的 fencing token（同名锁每次加锁单调递增）。
+    pub fn fencing_token(&self) -> u64 {
+        self.fencing_token
+    }
+
+    /// 锁是否仍被持有；锁被他人取得或租约内始终续约失败后返回 `false`。
+    pub fn is_held(&self) -> bool {
+        self.held.load(Ordering::SeqCst)
+    }
+
+    /// 显式释放锁并返回释放结果；锁已丢失时返回 `Ok(false)`。
+    pub fn release(mut self) -> Result<bool, TalonError> {
+        self.release_inner()
+    }
+
+    fn release_inner(&mut self) -> Result<bool, TalonError> {
+        let Some(stop) = self.stop.take() else {
+            return Ok(false);
+        };
+        drop(stop);
+        if let Some(renewer) = self.renewer.take() {
+            let _ = renewer.join();
+        }
+        let was_held = self.held.swap(false, Ordering::SeqCst);
+        if !was_held {
+            return Ok(false);
+        }
+        self.backend
+            .kv_compare_and_swap(&lock_key(&self.name), Some(&self.value), None, None)
+    }
+}
+
+impl<B: KvBackend + 'static> Drop for TalonLock<B> {
+    fn drop(&mut self) {
+        let _ = self.release_inner();
+    }
+}
+
+impl Talon {
+    /// 获取分布式锁，锁被占用时阻塞等待。
+    ///
+    /// 需要 `Arc<Talon>`：后台续约线程与守卫共享同一句柄。
+    pub fn lock(
+        self: &Arc<Self>,
+        name: &str,
+        ttl: Duration,
+    ) -> Result<TalonLock<Talon>, TalonError> {
+        TalonLock::acquire(Arc::clone(self), name, ttl)
+    }
+
+    /// 尝试获取分布式锁，锁被占用时立即返回 `Ok(None)`。
+    pub fn try_lock(
+        self: &Arc<Self>,
+        name: &str,
+        ttl: Duration,
+    ) -> Result<Option<TalonLock<Talon>>, TalonError> {
+        TalonLock::try_acquire(Arc::clone(self), name, ttl)
+    }
+}
+
+impl TalonRemoteClient {
+    /// Acquire a distributed lock on the remote server, blocking until it is free.
+    pub fn lock(
+        self: &Arc<Self>,
+        name: &str,
+        ttl: Duration,
+    ) -> Result<TalonLock<TalonRemoteClient>, TalonError> {
+        TalonLock::acquire(Arc::clone(self), name, ttl)
+    }
+
+    /// Try to acquire a distributed lock once; `Ok(None)` when it is held elsewhere.
+    pub fn try_lock(
+        self: &Arc<Self>,
+        name: &str,
+        ttl: Duration,
+    ) -> Result<Option<TalonLock<TalonRemoteClient>>, TalonError> {
+        TalonLock::try_acquire(Arc::clone(self), name, ttl)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::MemKv;
+
+    #[test]
+    fn lock_is_exclusive_and_fencing_token_increases() {
+        let kv = Arc::new(MemKv::default());
+        let ttl = Duration::from_millis(60);
+
+        let first = TalonLock::try_acquire(Arc::clone(&kv), "job", ttl)
+            .unwrap()
+            .unwrap();
+        assert!(TalonLock::try_acquire(Arc::clone(&kv), "job", ttl)
+            .unwrap()
+            .is_none());
+        // 续约线程运行若干轮后锁仍然有效。
+        thread::sleep(Duration::from_millis(70));
+        assert!(first.is_held());
+        let token = first.fencing_token();
+        assert!(first.release().unwrap());
+
+        let second = TalonLock::try_acquire(Arc::clone(&kv), "job", ttl)
+            .unwrap()
+            .unwrap();
+        assert!(second.fencing_token() > token);
+    }
+
+    #[test]
+    fn lost_lock_is_reported_and_not_released() {
+        let kv = Arc::new(MemKv::default());
+        let lock = TalonLock::try_acquire(Arc::clone(&kv), "job", Duration::from_millis(30))
+            .unwrap()
+            .unwrap();
+        // 模拟 TTL 过期后被其他持有者抢占。
+        kv.kv_set(&lock_key("job"), b"someone-else", None).unwrap();
+        thread::sleep(Duration::from_millis(40));
+        assert!(!lock.is_held());
+        assert!(!lock.release().unwrap());
+        assert_eq!(
+            kv.kv_get(&lock_key("job")).unwrap(),
+            Some(b"someone-else".to_vec())
+        );
+    }
+
+    #[test]
+    fn fencing_token_is_written_into_lock_value() {
+        let kv = Arc::new(MemKv::default());
+        let lock = TalonLock::try_acquire(Arc::clone(&kv), "job", Duration::from_secs(5))
+            .unwrap()
+            .unwrap();
+        let value = kv.kv_get(&lock_key("job")).unwrap().unwrap();
+        let suffix = format!("/{}", lock.fencing_token());
+        assert!(value.ends_with(suffix.as_bytes()));
+    }
+
+    #[test]
+    fn lock_taken_over_while_issuing_token_is_abandoned() {
+        let kv = Arc::new(MemKv::default());
+        // 模拟 set_nx 之后停顿超过 TTL：取 token 前锁已过期并被他人持有。
+        kv.before_next_incr(|map| {
+            map.insert(lock_key("job"), b"someone-else".to_vec());
+        });
+        assert!(
+            TalonLock::try_acquire(Arc::clone(&kv), "job", Duration::from_secs(5))
+                .unwrap()
+                .is_none()
+        );
+        assert_eq!(
+            kv.kv_get(&lock_key("job")).unwrap(),
+            Some(b"someone-else".to_vec())
+        );
+    }
+
+    #[test]
+    fn transient_renew_errors_are_retried_within_the_lease() {
+        let kv = Arc::new(MemKv::default());
+        let lock = TalonLock::try_acquire(Arc::clone(&kv), "job", Duration::from_millis(300))
+            .unwrap()
+            .unwrap();
+        // 第一次续约（~100ms）失败两次，随后以 50ms 间隔重试成功。
+        kv.fail_next_cas(2);
+        thread::sleep(Duration::from_millis(350));
+        assert!(lock.is_held());
+        assert!(lock.release().unwrap());
+    }
+
+    #[test]
+    fn renew_errors_past_the_lease_mark_the_lock_lost() {
+        let kv = Arc::new(MemKv::default());
+        let lock = TalonLock::try_acquire(Arc::clone(&kv), "job", Duration::from_millis(60))
+            .unwrap()
+            .unwrap();
+        kv.fail_next_cas(u32::MAX);
+        thread::sleep(Duration::from_millis(120));
+        assert!(!lock.is_held());
+    }
+}
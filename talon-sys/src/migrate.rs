@@ -0,0 +1,539 @@
+/*
+ * Copyright (c) 2026 Talon Contributors
+ * Author: dark.lijin@gmail.com
+ * Licensed under the Talon Community Dual License Agreement.
+ * See the LICENSE file in the project root for full license information.
+ */
+//! Schema 迁移 — 版本化、带校验和的 SQL 迁移脚本。
+//!
+//! 迁移来源：
+//! - 目录：`Migrator::from_dir("migrations")`，文件名形如 `0001_create_users.sql`
+//!   或 `V2__add_index.sql`；
+//! - 内嵌：`Migrator::new().add_file("0001_init.sql", include_str!("../migrations/0001_init.sql"))`。
+//!
+//! 已应用的迁移记录在 `_talon_migrations` 表中（版本、名称、FNV-1a 校验和、应用时间）。
+//! 已应用的脚本内容被修改时拒绝执行；`dry_run` 只计算待执行列表，不写入数据库。
+//!
+//! 执行期间持有分布式锁 `_talon_migrations`（见 [`TalonLock`]），多个服务实例同时
+//! 启动时只有一个在执行迁移；每个迁移与其记录行在同一事务中提交，失败时整体回滚。
+
+use std::path::Path;
+use std::sync::Arc;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use crate::{KvBackend, SqlBackend, TalonError, TalonLock, Value};
+
+/// 迁移记录表名。
+pub const MIGRATIONS_TABLE: &str = "_talon_migrations";
+
+/// 迁移期间持有的分布式锁名称。
+const MIGRATIONS_LOCK: &str = "_talon_migrations";
+
+/// 迁移锁租约（持有期间后台自动续约）。
+const MIGRATIONS_LOCK_TTL: Duration = Duration::from_secs(30);
+
+/// 等待其他实例完成迁移的默认时长。
+const DEFAULT_LOCK_TIMEOUT: Duration = Duration::from_secs(300);
+
+/// 单个迁移脚本。
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Migration {
+    pub version: u64,
+    pub name: String,
+    pub sql: String,
+}
+
+impl Migration {
+    pub fn new(version: u64, name: impl Into<String>, sql: impl Into<String>) -> Self {
+        Self {
+            version,
+            name: name.into(),
+            sql: sql.into(),
+        }
+    }
+
+    /// 从文件名解析版本与名称：`0001_create_users.sql` / `V2__add_index.sql`。
+    pub fn from_file(file_name: &str, sql: impl Into<String>) -> Result<Self, TalonError> {
+        let (version, name) = parse_file_name(file_name).ok_or_else(|| {
+            TalonError(format!(
+                "migration file name must look like `0001_name.sql`: {file_name}"
+            ))
+        })?;
+        Ok(Self::new(version, name, sql))
+    }
+
+    /// 脚本内容的 FNV-1a 64 位校验和（十六进制）。
+    pub fn checksum(&self) -> String {
+        let mut hash: u64 = 0xcbf2_9ce4_8422_2325;
+        for byte in self.sql.as_bytes() {
+            hash ^= u64::from(*byte);
+            hash = hash.wrapping_mul(0x0000_0100_0000_01b3);
+        }
+        format!("{hash:016x}")
+    }
+}
+
+fn parse_file_name(file_name: &str) -> Option<(u64, String)> {
+    let stem = file_name.strip_suffix(".sql")?;
+    let stem = stem.strip_prefix(['V', 'v']).unwrap_or(stem);
+    let digits = stem.bytes().take_while(u8::is_ascii_digit).count();
+    let version = stem[..digits].parse().ok()?;
+    let name = stem[digits..].trim_start_matches('_');
+    if name.is_empty() {
+        return None;
+    }
+    Some((version, name.to_string()))
+}
+
+/// 一次迁移运行的结果。
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct MigrationReport {
+    /// 本次执行的版本（dry-run 时为将要执行的版本）。
+    pub applied: Vec<u64>,
+    /// 此前已应用的版本。
+    pub already_applied: Vec<u64>,
+    pub dry_run: bool,
+}
+
+/// 迁移执行器。
+#[derive(Debug, Clone)]
+pub struct Migrator {
+    migrations: Vec<Migration>,
+    dry_run: bool,
+    lock_timeout: Duration,
+}
+
+impl Default for Migrator {
+    fn default() -> Self {
+        Self {
+            migrations: Vec::new(),
+            dry_run: false,
+            lock_timeout: DEFAULT_LOCK_TIMEOUT,
+        }
+    }
+}
+
+impl Migrator {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 加载目录下所有 `.sql` 迁移文件。
+    pub fn from_dir(dir: impl AsRef<Path>) -> Result<Self, TalonError> {
+        let dir = dir.as_ref();
+        let entries = std::fs::read_dir(dir)
+            .map_err(|e| TalonError(format!("read migrations dir {}: {e}", dir.display())))?;
+        let mut migrator = Self::new();
+        for entry in entries {
+            let path = entry
+                .map_err(|e| TalonError(format!("read migrations dir {}: {e}", dir.display())))?
+                .path();
+            let Some(file_name) = path.file_name().and_then(|n| n.to_str()) else {
+                continue;
+            };
+            if !file_name.ends_with(".sql") {
+                continue;
+            }
+            let sql = std::fs::read_to_string(&path)
+                .map_err(|e| TalonError(format!("read migration {}: {e}", path.display())))?;
+            migrator
+                .migrations
+                .push(Migration::from_file(file_name, sql)?);
+        }
+        Ok(migrator)
+    }
+
+    /// 添加迁移。
+    pub fn add(mut self, version: u64, name: &str, sql: &str) -> Self {
+        self.migrations.push(Migration::new(version, name, sql));
+        self
+    }
+
+    /// 按文件名添加迁移，配合 `include_str!` 使用。
+    pub fn add_file(mut self, file_name: &str, sql: &str) -> Result<Self, TalonError> {
+        self.migrations.push(Migration::from_file(file_name, sql)?);
+        Ok(self)
+    }
+
+    /// 只计算待执行的迁移，不修改数据库。
+    pub fn dry_run(mut self, dry_run: bool) -> Self {
+        self.dry_run = dry_run;
+        self
+    }
+
+    /// 等待迁移锁的最长时间（默认 5 分钟），超时返回错误。
+    pub fn lock_timeout(mut self, timeout: Duration) -> Self {
+        self.lock_timeout = timeout;
+        self
+    }
+
+    /// 已注册的迁移（按版本排序）。
+    pub fn migrations(&self) -> Vec<&Migration> {
+        let mut sorted: Vec<&Migration> = self.migrations.iter().collect();
+        sorted.sort_by_key(|m| m.version);
+        sorted
+    }
+
+    /// 执行所有未应用的迁移。
+    ///
+    /// 先获取迁移锁（dry-run 除外），再逐个在事务中执行迁移。
+    pub fn run<B: SqlBackend + KvBackend + 'static>(
+        &self,
+        db: &Arc<B>,
+    ) -> Result<MigrationReport, TalonError> {
+        self.run_with(&**db, Arc::clone(db))
+    }
+
+    fn run_with<S, K>(&self, db: &S, kv: Arc<K>) -> Result<MigrationReport, TalonError>
+    where
+        S: SqlBackend + ?Sized,
+        K: KvBackend + 'static,
+    {
+        let migrations = self.migrations();
+        for pair in migrations.windows(2) {
+            if pair[0].version == pair[1].version {
+                return Err(TalonError(format!(
+                    "duplicate migration version {}: {} and {}",
+                    pair[0].version, pair[0].name, pair[1].name
+                )));
+            }
+        }
+
+        let lock = if self.dry_run {
+            None
+        } else {
+            Some(TalonLock::acquire_timeout(
+                kv,
+                MIGRATIONS_LOCK,
+                MIGRATIONS_LOCK_TTL,
+                self.lock_timeout,
+            )?)
+        };
+        let applied = if self.dry_run {
+            // dry-run 不建表：记录表不存在即视为没有已应用的迁移，其余错误照常返回。
+            if db.sql_table_exists(MIGRATIONS_TABLE)? {
+                load_applied(db)?
+            } else {
+                Vec::new()
+            }
+        } else {
+            db.sql_run(
+                &format!(
+                    "CREATE TABLE IF NOT EXISTS {MIGRATIONS_TABLE} \
+                     (version INT PRIMARY KEY, name TEXT, checksum TEXT, applied_at INT)"
+                ),
+                &[],
+            )?;
+            load_applied(db)?
+        };
+
+        for (version, checksum) in &applied {
+            if let Some(m) = migrations.iter().find(|m| m.version == *version) {
+                if m.checksum() != *checksum {
+                    return Err(TalonError(format!(
+                        "migration {version} ({}) has changed since it was applied",
+                        m.name
+                    )));
+                }
+            }
+        }
+        let latest = applied.iter().map(|(v, _)| *v).max();
+        let pending: Vec<&Migration> = migrations
+            .into_iter()
+            .filter(|m| !applied.iter().any(|(v, _)| *v == m.version))
+            .collect();
+        if let (Some(latest), Some(first)) = (latest, pending.first()) {
+            if first.version < latest {
+                return Err(TalonError(format!(
+                    "migration {} ({}) is older than the latest applied version {latest}",
+                    first.version, first.name
+                )));
+            }
+        }
+
+        let mut report = MigrationReport {
+            already_applied: applied.iter().map(|(v, _)| *v).collect(),
+            dry_run: self.dry_run,
+            ..MigrationReport::default()
+        };
+        for m in pending {
+            if let Some(lock) = &lock {
+                if !lock.is_held() {
+                    return Err(TalonError(format!(
+                        "migration lock lost before applying migration {} ({})",
+                        m.version, m.name
+                    )));
+                }
+                db.sql_transaction(&mut |tx| apply(tx, m)).map_err(|e| {
+                    TalonError(format!("migration {} ({}): {}", m.version, m.name, e.0))
+                })?;
+            }
+            report.applied.push(m.version);
+        }
+        if let Some(lock) = lock {
+            lock.release()?;
+        }
+        Ok(report)
+    }
+}
+
+/// 在事务 `tx` 中执行迁移脚本并写入记录行。
+fn apply(tx: &dyn SqlBackend, m: &Migration) -> Result<(), TalonError> {
+    for stmt in split_statements(&m.sql) {
+        tx.sql_run(stmt, &[])?;
+    }
+    let applied_at = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs() as i64;
+    tx.sql_run(
+        &format!(
+            "INSERT INTO {MIGRATIONS_TABLE} (version, name, checksum, applied_at) \
+             VALUES (?, ?, ?, ?)"
+        ),
+        &[
+            Value::Integer(m.version as i64),
+            Value::Text(m.name.clone()),
+            Value::Text(m.checksum()),
+            Value::Integer(applied_at),
+        ],
+    )?;
+    Ok(())
+}
+
+fn load_applied<B: SqlBackend + ?Sized>(db: &B) -> Result<Vec<(u64, String)>, TalonError> {
+    let rows = db.sql_run(
+        &format!("SELECT version, checksum FROM {MIGRATIONS_TABLE} ORDER BY version"),
+        &[],
+    )?;
+    rows.into_iter()
+        .map(|row| match row.as_slice() {
+            [Value::Integer(version), Value::Text(checksum)] => {
+                Ok((*version as u64, checksum.clone()))
+            }
+            other => Err(TalonError(format!(
+                "unexpected row in {MIGRATIONS_TABLE}: {other:?}"
+            ))),
+        })
+        .collect()
+}
+
+/// 按 `;` 拆分多条语句（忽略字符串与注释中的分号），丢弃空语句。
+fn split_statements(sql: &str) -> Vec<&str> {
+    let bytes = sql.as_bytes();
+    let mut out = Vec::new();
+    let mut start = 0;
+    let mut i = 0;
+    while i < bytes.len() {
+        match bytes[i] {
+            quote @ (b'\'' | b'"') => {
+                i += 1;
+                while i < bytes.len() && bytes[i] != quote {
+                    i += 1;
+                }
+            }
+            b'-' if bytes.get(i + 1) == Some(&b'-') => {
+                i = sql[i..].find('\n').map_or(bytes.len(), |n| i + n);
+            }
+            b'/' if bytes.get(i + 1) == Some(&b'*') => {
+                i = sql[i + 2..]
+                    .find("*/")
+                    .map_or(bytes.len(), |n| i + 2 + n + 1);
+            }
+            b';' => {
+                out.push(&sql[start..i]);
+                start = i + 1;
+            }
+            _ => {}
+        }
+        i += 1;
+    }
+    out.push(&sql[start..]);
+    out.into_iter()
+        .map(str::trim)
+        .filter(|s| !is_blank_sql(s))
+        .collect()
+}
+
+/// 只含空白与注释的片段。
+fn is_blank_sql(stmt: &str) -> bool {
+    stmt.lines()
+        .map(str::trim)
+        .all(|line| line.is_empty() || line.starts_with("--"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::MemKv;
+    use std::sync::Mutex;
+
+    /// 记录执行过的 SQL，并模拟 `_talon_migrations` 表与事务回滚。
+    #[derive(Default)]
+    struct FakeSql {
+        executed: Mutex<Vec<String>>,
+        applied: Mutex<Vec<(i64, String)>>,
+        table_exists: Mutex<bool>,
+        fail_on: Option<&'static str>,
+    }
+
+    impl SqlBackend for FakeSql {
+        fn sql_run(&self, sql: &str, params: &[Value]) -> Result<Vec<Vec<Value>>, TalonError> {
+            self.executed.lock().unwrap().push(sql.to_string());
+            if self.fail_on.is_some_and(|f| sql.contains(f)) {
+                return Err(TalonError(format!("cannot run {sql}")));
+            }
+            if sql.starts_with("CREATE TABLE IF NOT EXISTS _talon_migrations") {
+                *self.table_exists.lock().unwrap() = true;
+            }
+            if sql.starts_with("SELECT version") {
+                return Ok(self
+                    .applied
+                    .lock()
+                    .unwrap()
+                    .iter()
+                    .map(|(v, c)| vec![Value::Integer(*v), Value::Text(c.clone())])
+                    .collect());
+            }
+            if sql.starts_with("INSERT INTO _talon_migrations") {
+                if let [Value::Integer(v), _, Value::Text(c), _] = params {
+                    self.applied.lock().unwrap().push((*v, c.clone()));
+                }
+            }
+            Ok(vec![])
+        }
+
+        fn sql_transaction(
+            &self,
+            f: &mut dyn FnMut(&dyn SqlBackend) -> Result<(), TalonError>,
+        ) -> Result<(), TalonError> {
+            self.executed.lock().unwrap().push("BEGIN".into());
+            let snapshot = self.applied.lock().unwrap().clone();
+            let result = f(self);
+            let end = if result.is_ok() { "COMMIT" } else { "ROLLBACK" };
+            if result.is_err() {
+                *self.applied.lock().unwrap() = snapshot;
+            }
+            self.executed.lock().unwrap().push(end.into());
+            result
+        }
+
+        fn sql_table_exists(&self, table: &str) -> Result<bool, TalonError> {
+            Ok(table == MIGRATIONS_TABLE && *self.table_exists.lock().unwrap())
+        }
+    }
+
+    fn migrator() -> Migrator {
+        Migrator::new()
+            .add_file(
+                "0002_add_email.sql",
+                "ALTER TABLE users ADD COLUMN email TEXT;",
+            )
+            .unwrap()
+            .add_file(
+                "V1__init.sql",
+                "-- users\nCREATE TABLE users (id INT, note TEXT DEFAULT 'a;b');\nCREATE INDEX idx ON users (id);",
+            )
+            .unwrap()
+    }
+
+    fn run(m: &Migrator, db: &FakeSql) -> Result<MigrationReport, TalonError> {
+        m.run_with(db, Arc::new(MemKv::default()))
+    }
+
+    #[test]
+    fn applies_pending_migrations_in_version_order_once() {
+        let db = FakeSql::default();
+        let report = run(&migrator(), &db).unwrap();
+        assert_eq!(report.applied, vec![1, 2]);
+        let executed = db.executed.lock().unwrap().clone();
+        assert_eq!(executed[2], "BEGIN");
+        assert!(executed[3].starts_with("-- users\nCREATE TABLE users"));
+        assert!(executed[3].ends_with("DEFAULT 'a;b')"));
+        assert_eq!(executed[4], "CREATE INDEX idx ON users (id)");
+        assert!(executed[5].starts_with("INSERT INTO _talon_migrations"));
+        assert_eq!(executed[6], "COMMIT");
+
+        let again = run(&migrator(), &db).unwrap();
+        assert!(again.applied.is_empty());
+        assert_eq!(again.already_applied, vec![1, 2]);
+    }
+
+    #[test]
+    fn failed_migration_is_rolled_back_with_its_record() {
+        let db = FakeSql {
+            fail_on: Some("ALTER TABLE"),
+            ..FakeSql::default()
+        };
+        let err = run(&migrator(), &db).unwrap_err();
+        assert!(err
+            .0
+            .starts_with("migration 2 (add_email): cannot run ALTER TABLE"));
+        assert_eq!(db.executed.lock().unwrap().last().unwrap(), "ROLLBACK");
+        assert_eq!(db.applied.lock().unwrap().len(), 1);
+    }
+
+    #[test]
+    fn concurrent_runner_waits_for_the_migration_lock() {
+        let kv = Arc::new(MemKv::default());
+        let _other = TalonLock::try_acquire(Arc::clone(&kv), MIGRATIONS_LOCK, MIGRATIONS_LOCK_TTL)
+            .unwrap()
+            .unwrap();
+        let db = FakeSql::default();
+        let err = migrator()
+            .lock_timeout(Duration::from_millis(20))
+            .run_with(&db, kv)
+            .unwrap_err();
+        assert!(err.0.contains("timed out"));
+        assert!(db.executed.lock().unwrap().is_empty());
+    }
+
+    #[test]
+    fn changed_migration_is_refused() {
+        let db = FakeSql::default();
+        run(&migrator(), &db).unwrap();
+        let changed = Migrator::new().add(1, "init", "CREATE TABLE users (id INT)");
+        let err = run(&changed, &db).unwrap_err();
+        assert_eq!(err.0, "migration 1 (init) has changed since it was applied");
+    }
+
+    #[test]
+    fn dry_run_does_not_write() {
+        let db = FakeSql::default();
+        let report = run(&migrator().dry_run(true), &db).unwrap();
+        assert!(report.dry_run);
+        assert_eq!(report.applied, vec![1, 2]);
+        assert!(db.executed.lock().unwrap().is_empty());
+
+        run(&migrator(), &db).unwrap();
+        db.executed.lock().unwrap().clear();
+        let report = run(&migrator().add(3, "tags", "SELECT 1").dry_run(true), &db).unwrap();
+        assert_eq!(report.already_applied, vec![1, 2]);
+        assert_eq!(report.applied, vec![3]);
+        let executed = db.executed.lock().unwrap();
+        assert_eq!(executed.len(), 1);
+        assert!(executed[0].starts_with("SELECT"));
+    }
+
+    #[test]
+    fn dry_run_surfaces_read_errors() {
+        let db = FakeSql {
+            fail_on: Some("SELECT version"),
+            ..FakeSql::default()
+        };
+        *db.table_exists.lock().unwrap() = true;
+        let err = run(&migrator().dry_run(true), &db).unwrap_err();
+        assert!(err.0.contains("cannot run SELECT version"));
+    }
+
+    #[test]
+    fn file_names_are_parsed() {
+        assert_eq!(
+            parse_file_name("0003_add_tags.sql"),
+            Some((3, "add_tags".into()))
+        );
+        assert_eq!(parse_file_name("V10__x.sql"), Some((10, "x".into())));
+        assert_eq!(parse_file_name("init.sql"), None);
+        assert_eq!(parse_file_name("0001.sql"), None);
+    }
+}
@@ -0,0 +1,224 @@
+/*
+ * Copyright (c) 2026 Talon Contributors
+ * Author: dark.lijin@gmail.com
+ * Licensed under the Talon Community Dual License Agreement.
+ * See the LICENSE file in the project root for full license information.
+ */
+//! 多租户 KV 命名空间 — 透明地为 key 加 / 去 `"{namespace}:"` 前缀。
+//!
+//! 命名空间名不能为空且不能包含 `:`，以保证不同命名空间的 key 区间互不重叠。
+//! [`KvNamespace`] 只依赖 [`KvBackend`]，嵌入式与远程共用同一实现。
+
+use crate::{
+    kv_prefix_end, KvBackend, KvScan, Talon, TalonError, TalonRemoteClient, KV_SCAN_PAGE_SIZE,
+};
+
+fn namespace_prefix(ns: &str) -> Result<Vec<u8>, TalonError> {
+    if ns.is_empty() || ns.contains(':') {
+        return Err(TalonError(format!(
+            "invalid KV namespace {ns:?}: must be non-empty and must not contain ':'"
+        )));
+    }
+    let mut prefix = Vec::with_capacity(ns.len() + 1);
+    prefix.extend_from_slice(ns.as_bytes());
+    prefix.push(b':');
+    Ok(prefix)
+}
+
+fn prefixed(prefix: &[u8], key: &[u8]) -> Vec<u8> {
+    let mut full = Vec::with_capacity(prefix.len() + key.len());
+    full.extend_from_slice(prefix);
+    full.extend_from_slice(key);
+    full
+}
+
+fn strip_entry(
+    prefix_len: usize,
+    entry: Result<(Vec<u8>, Vec<u8>), TalonError>,
+) -> Result<(Vec<u8>, Vec<u8>), TalonError> {
+    entry.map(|(mut key, value)| {
+        key.drain(..prefix_len.min(key.len()));
+        (key, value)
+    })
+}
+
+/// 命名空间 KV 句柄（`Talon::kv_ns` / `TalonRemoteClient::kv_ns`）。
+pub struct KvNamespace<'a, B: KvBackend + ?Sized = Talon> {
+    backend: &'a B,
+    name: String,
+    prefix: Vec<u8>,
+}
+
+/// 远程命名空间 KV 句柄（`TalonRemoteClient::kv_ns`）。
+pub type RemoteKvNamespace<'a> = KvNamespace<'a, TalonRemoteClient>;
+
+impl Talon {
+    /// 获取命名空间 KV 句柄，所有 key 自动加 `"{ns}:"` 前缀。
+    pub fn kv_ns(&self, ns: &str) -> Result<KvNamespace<'_>, TalonError> {
+        KvNamespace::new(self, ns)
+    }
+}
+
+impl TalonRemoteClient {
+    /// Get a remote KV handle whose keys are transparently prefixed with `"{ns}:"`.
+    pub fn kv_ns(&self, ns: &str) -> Result<RemoteKvNamespace<'_>, TalonError> {
+        KvNamespace::new(self, ns)
+    }
+}
+
+impl<'a, B: KvBackend + ?Sized> KvNamespace<'a, B> {
+    /// 在任意 [`KvBackend`] 上创建命名空间句柄。
+    pub fn new(backend: &'a B, ns: &str) -> Result<Self, TalonError> {
+        Ok(Self {
+            backend,
+            name: ns.to_string(),
+            prefix: namespace_prefix(ns)?,
+        })
+    }
+    /// 命名空间名称。
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+    /// 读取命名空间内的 key。
+    pub fn get(&self, key: &[u8]) -> Result<Option<Vec<u8>>, TalonError> {
+        self.backend.kv_get(&prefixed(&self.prefix, key))
+    }
+    /// 写入命名空间内的 key，可选 TTL。
+    pub fn set(&self, key: &[u8], value: &[u8], ttl_secs: Option<u64>) -> Result<(), TalonError> {
+        self.backend
+            .kv_set(&prefixed(&self.prefix, key), value, ttl_secs)
+    }
+    /// 删除命名空间内的 key。
+    pub fn del(&self, key: &[u8]) -> Result<(), TalonError> {
+        self.backend.kv_del(&prefixed(&self.prefix, key))
+    }
+    /// 扫描命名空间内以 `prefix` 开头的 key，返回的 key 已去掉命名空间前缀。
+    pub fn scan_prefix(
+        &self,
+        prefix: &[u8],
+    ) -> impl Iterator<Item = Result<(Vec<u8>, Vec<u8>), TalonError>> + 'a {
+        let prefix_len = self.prefix.len();
+        self.scan(prefixed(&self.prefix, prefix))
+            .map(move |entry| strip_entry(prefix_len, entry))
+    }
+    /// 命名空间内的 key 数量（分页扫描计数）。
+    pub fn count(&self) -> Result<u64, TalonError> {
+        let mut count = 0;
+        for entry in self.scan(self.prefix.clone()) {
+            entry?;
+            count += 1;
+        }
+        Ok(count)
+    }
+    /// 删除命名空间内的全部 key，返回删除数量。
+    ///
+    /// 若某一页扫描到的 key 一个也没能删除，返回错误而不是反复扫描同一页。
+    pub fn drop_all(&self) -> Result<u64, TalonError> {
+        let end = kv_prefix_end(&self.prefix);
+        let mut deleted = 0;
+        loop {
+            let page =
+                self.backend
+                    .kv_scan_page(&self.prefix, end.as_deref(), None, KV_SCAN_PAGE_SIZE)?;
+            if page.entries.is_empty() {
+                return Ok(deleted);
+            }
+            let keys: Vec<&[u8]> = page.entries.iter().map(|(k, _)| k.as_slice()).collect();
+            let removed = self.backend.kv_del_many(&keys)?;
+            if removed == 0 {
+                return Err(TalonError(format!(
+                    "KV namespace {:?} drop_all made no progress after deleting {deleted} keys",
+                    self.name
+                )));
+            }
+            deleted += removed;
+        }
+    }
+
+    fn scan(&self, start: Vec<u8>) -> KvScan<'a> {
+        let backend = self.backend;
+        let end = kv_prefix_end(&start);
+        KvScan::new(Box::new(move |cursor, limit| {
+            backend.kv_scan_page(&start, end.as_deref(), cursor, limit)
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::MemKv;
+
+    fn seeded() -> MemKv {
+        let kv = MemKv::default();
+        for (key, value) in [
+            ("tenant-a:user:1", "alice"),
+            ("tenant-a:user:2", "bob"),
+            ("tenant-a:cfg", "x"),
+            ("tenant-ab:user:1", "other"),
+            ("tenant-b:user:1", "carol"),
+        ] {
+            kv.kv_set(key.as_bytes(), value.as_bytes(), None).unwrap();
+        }
+        kv
+    }
+
+    #[test]
+    fn namespace_prefix_rejects_ambiguous_names() {
+        assert_eq!(namespace_prefix("tenant-a").unwrap(), b"tenant-a:".to_vec());
+        assert!(namespace_prefix("").is_err());
+        assert!(namespace_prefix("tenant:a").is_err());
+        let stripped = strip_entry(9, Ok((b"tenant-a:k1".to_vec(), b"v".to_vec()))).unwrap();
+        assert_eq!(stripped.0, b"k1".to_vec());
+    }
+
+    #[test]
+    fn scan_and_count_stay_inside_the_namespace() {
+        let kv = seeded();
+        let ns = KvNamespace::new(&kv, "tenant-a").unwrap();
+        let users: Vec<_> = ns.scan_prefix(b"user:").map(Result::unwrap).collect();
+        assert_eq!(
+            users,
+            vec![
+                (b"user:1".to_vec(), b"alice".to_vec()),
+                (b"user:2".to_vec(), b"bob".to_vec()),
+            ]
+        );
+        assert_eq!(ns.count().unwrap(), 3);
+        assert_eq!(ns.get(b"cfg").unwrap(), Some(b"x".to_vec()));
+        assert_eq!(ns.get(b"missing").unwrap(), None);
+    }
+
+    #[test]
+    fn drop_all_pages_through_and_spares_other_namespaces() {
+        let kv = seeded();
+        let ns = KvNamespace::new(&kv, "bulk").unwrap();
+        for i in 0..(KV_SCAN_PAGE_SIZE + 5) {
+            ns.set(format!("k{i:05}").as_bytes(), b"v", None).unwrap();
+        }
+        assert_eq!(ns.count().unwrap(), (KV_SCAN_PAGE_SIZE + 5) as u64);
+        assert_eq!(ns.drop_all().unwrap(), (KV_SCAN_PAGE_SIZE + 5) as u64);
+        assert_eq!(ns.count().unwrap(), 0);
+
+        let a = KvNamespace::new(&kv, "tenant-a").unwrap();
+        assert_eq!(a.drop_all().unwrap(), 3);
+        assert_eq!(
+            KvNamespace::new(&kv, "tenant-ab").unwrap().count().unwrap(),
+            1
+        );
+        assert_eq!(
+            KvNamespace::new(&kv, "tenant-b").unwrap().count().unwrap(),
+            1
+        );
+    }
+
+    #[test]
+    fn drop_all_errors_when_a_page_cannot_be_deleted() {
+        let kv = seeded();
+        kv.ignore_deletes();
+        let ns = KvNamespace::new(&kv, "tenant-a").unwrap();
+        let err = ns.drop_all().unwrap_err();
+        assert!(err.0.contains("made no progress"), "{}", err.0);
+        assert_eq!(ns.count().unwrap(), 3);
+    }
+}
@@ -0,0 +1,564 @@
+/*
+ * Copyright (c) 2026 Talon Contributors
+ * Author: dark.lijin@gmail.com
+ * Licensed under the Talon Community Dual License Agreement.
+ * See the LICENSE file in the project root for full license information.
+ */
+//! SQL 参数 — 支持 `?`、`$1`、`:name`、`@name` 占位符。
+//!
+//! 引擎的二进制参数格式（`encode_params`）与远程 SQL 命令都只有位置参数，
+//! 因此命名 / 编号占位符在客户端由一个轻量扫描器统一改写为 `?`，再交给嵌入式
+//! 二进制编码或远程 SQL 字面量渲染（`inline_sql_params`）。`query` / `execute`、
+//! `query_iter`、`query_as` 系列与 `explain` 均接受 `impl Into<Params>`；预编译语句
+//! 在 prepare 时改写 SQL，执行时按占位符顺序排列 `bind` / `bind_named` 的参数。
+//! 单引号字符串、双引号标识符、`--` 行注释和 `/* */` 块注释中的占位符不会被替换，
+//! `::` 类型转换也不会被当作命名参数。
+//!
+//! 扫描器不是完整的 SQL 词法分析器，已知限制：
+//! - 美元符号引用字符串（`$tag$ ... $tag$`）不被识别，其中的 `:name` / `@name` / `?`
+//!   仍会被当作占位符；
+//! - 块注释不支持嵌套，`/* /* */ ... */` 在第一个 `*/` 处结束；
+//! - 反引号、方括号标识符与 `E'...'` 中的反斜杠转义按普通文本处理。
+//!
+//! 遇到以上写法时请改用位置参数 `?`。
+
+use std::borrow::Cow;
+use std::collections::{BTreeMap, BTreeSet};
+use std::mem;
+
+use serde::Serialize;
+
+use crate::{inline_sql_params, Talon, TalonError, TalonRemoteClient, ToValue, Value};
+
+/// SQL 参数：位置参数（`?` / `$N`）或命名参数（`:name` / `@name`）。
+#[derive(Debug, Clone, PartialEq)]
+pub enum Params {
+    Positional(Vec<Value>),
+    Named(BTreeMap<String, Value>),
+}
+
+impl Default for Params {
+    fn default() -> Self {
+        Params::Positional(Vec::new())
+    }
+}
+
+impl Params {
+    /// 由可序列化的值构造：结构体 / map 生成命名参数，序列 / 元组生成位置参数。
+    pub fn from_serde<T: Serialize + ?Sized>(value: &T) -> Result<Self, TalonError> {
+        let json = serde_json::to_value(value)
+            .map_err(|e| TalonError(format!("SQL params serialize: {e}")))?;
+        match json {
+            serde_json::Value::Object(map) => Ok(Params::Named(
+                map.into_iter().map(|(k, v)| (k, json_param(v))).collect(),
+            )),
+            serde_json::Value::Array(items) => Ok(Params::Positional(
+                items.into_iter().map(json_param).collect(),
+            )),
+            other => Err(TalonError(format!(
+                "SQL params must serialize to an object or a sequence, got {other}"
+            ))),
+        }
+    }
+
+    /// 把 SQL 中的占位符改写为 `?`，返回改写后的 SQL 与按顺序排列的参数。
+    ///
+    /// 没有任何位置参数时 SQL 原样透传，`@@var` 等文本不会被当作命名参数报错。
+    /// 占位符识别规则与限制见模块文档。
+    pub fn bind<'s>(self, sql: &'s str) -> Result<(Cow<'s, str>, Vec<Value>), TalonError> {
+        let placeholders = scan_placeholders(sql);
+        let anonymous = placeholders
+            .iter()
+            .any(|p| matches!(p.kind, Placeholder::Anonymous));
+        let has_other = placeholders
+            .iter()
+            .any(|p| !matches!(p.kind, Placeholder::Anonymous));
+        if anonymous && has_other {
+            return Err(TalonError(
+                "SQL mixes `?` with named or numbered placeholders".into(),
+            ));
+        }
+        match self {
+            // 纯 `?` 位置参数或无参数：原样透传，数量由引擎 / 渲染器校验。
+            Params::Positional(values) if !has_other || values.is_empty() => {
+                Ok((Cow::Borrowed(sql), values))
+            }
+            Params::Positional(values) => {
+                let mut used = vec![false; values.len()];
+                let mut ordered = Vec::with_capacity(placeholders.len());
+                for p in &placeholders {
+                    let Placeholder::Numbered(n) = &p.kind else {
+                        return Err(TalonError(format!(
+                            "named SQL parameter {} needs named params",
+                            &sql[p.start..p.end]
+                        )));
+                    };
+                    let value = n
+                        .checked_sub(1)
+                        .and_then(|i| values.get(i))
+                        .ok_or_else(|| TalonError(format!("missing SQL parameter ${n}")))?;
+                    used[n - 1] = true;
+                    ordered.push(value.clone());
+                }
+                let unused: Vec<String> = used
+                    .iter()
+                    .enumerate()
+                    .filter(|(_, u)| !**u)
+                    .map(|(i, _)| format!("${}", i + 1))
+                    .collect();
+                if !unused.is_empty() {
+                    return Err(TalonError(format!(
+                        "unused SQL parameters: {}",
+                        unused.join(", ")
+                    )));
+                }
+                Ok((Cow::Owned(rewrite(sql, &placeholders)), ordered))
+            }
+            Params::Named(values) => {
+                let mut used = BTreeSet::new();
+                let mut ordered = Vec::with_capacity(placeholders.len());
+                for p in &placeholders {
+                    let Placeholder::Named(name) = &p.kind else {
+                        return Err(TalonError(format!(
+                            "SQL placeholder {} needs positional params",
+                            &sql[p.start..p.end]
+                        )));
+                    };
+                    let value = values.get(*name).ok_or_else(|| {
+                        TalonError(format!("missing SQL parameter {}", &sql[p.start..p.end]))
+                    })?;
+                    used.insert(*name);
+                    ordered.push(value.clone());
+                }
+                let unused: Vec<&str> = values
+                    .keys()
+                    .map(String::as_str)
+                    .filter(|k| !used.contains(k))
+                    .collect();
+                if !unused.is_empty() {
+                    return Err(TalonError(format!(
+                        "unused SQL parameters: {}",
+                        unused.join(", ")
+                    )));
+                }
+                Ok((Cow::Owned(rewrite(sql, &placeholders)), ordered))
+            }
+        }
+    }
+}
+
+fn json_param(value: serde_json::Value) -> Value {
+    match value {
+        serde_json::Value::Null => Value::Null,
+        serde_json::Value::Bool(b) => Value::Boolean(b),
+        serde_json::Value::Number(n) => match n.as_i64() {
+            Some(i) => Value::Integer(i),
+            None => Value::Float(n.as_f64().unwrap_or(f64::NAN)),
+        },
+        serde_json::Value::String(s) => Value::Text(s),
+        other => Value::Jsonb(other),
+    }
+}
+
+impl From<Vec<Value>> for Params {
+    fn from(values: Vec<Value>) -> Self {
+        Params::Positional(values)
+    }
+}
+
+impl From<&[Value]> for Params {
+    fn from(values: &[Value]) -> Self {
+        Params::Positional(values.to_vec())
+    }
+}
+
+impl<const N: usize> From<&[Value; N]> for Params {
+    fn from(values: &[Value; N]) -> Self {
+        Params::Positional(values.to_vec())
+    }
+}
+
+impl From<&Vec<Value>> for Params {
+    fn from(values: &Vec<Value>) -> Self {
+        Params::Positional(values.clone())
+    }
+}
+
+/// 混合类型位置参数：`&[&1 as &dyn ToValue, &"a"][..]`。
+impl From<&[&dyn ToValue]> for Params {
+    fn from(values: &[&dyn ToValue]) -> Self {
+        Params::Positional(values.iter().map(|v| v.to_value()).collect())
+    }
+}
+
+impl<T: Into<Value>, const N: usize> From<[T; N]> for Params {
+    fn from(values: [T; N]) -> Self {
+        Params::Positional(values.into_iter().map(Into::into).collect())
+    }
+}
+
+impl From<()> for Params {
+    fn from(_: ()) -> Self {
+        Params::default()
+    }
+}
+
+impl<K: Into<String>> From<BTreeMap<K, Value>> for Params {
+    fn from(values: BTreeMap<K, Value>) -> Self {
+        Params::Named(values.into_iter().map(|(k, v)| (k.into(), v)).collect())
+    }
+}
+
+macro_rules! params_from_tuple {
+    ($($ty:ident),+) => {
+        impl<$($ty: Into<Value>),+> From<($($ty,)+)> for Params {
+            #[allow(non_snake_case)]
+            fn from(($($ty,)+): ($($ty,)+)) -> Self {
+                Params::Positional(vec![$($ty.into()),+])
+            }
+        }
+    };
+}
+
+params_from_tuple!(A);
+params_from_tuple!(A, B);
+params_from_tuple!(A, B, C);
+params_from_tuple!(A, B, C, D);
+params_from_tuple!(A, B, C, D, E);
+params_from_tuple!(A, B, C, D, E, F);
+params_from_tuple!(A, B, C, D, E, F, G);
+params_from_tuple!(A, B, C, D, E, F, G, H);
+
+/// 预编译语句上累积的参数绑定。
+#[derive(Debug, Default)]
+pub(crate) struct Bindings {
+    positional: Vec<Value>,
+    named: BTreeMap<String, Value>,
+    /// prepare 时 SQL 含命名 / 编号占位符，执行时需按占位符顺序排列参数。
+    rewritten: bool,
+}
+
+impl Bindings {
+    /// 为 `sql` 创建绑定，并返回交给引擎 prepare 的 SQL（占位符统一改写为 `?`）。
+    pub(crate) fn prepare(sql: &str) -> (Cow<'_, str>, Self) {
+        let placeholders = scan_placeholders(sql);
+        let rewritten = placeholders
+            .iter()
+            .any(|p| !matches!(p.kind, Placeholder::Anonymous));
+        let bindings = Self {
+            rewritten,
+            ..Self::default()
+        };
+        if rewritten {
+            (Cow::Owned(rewrite(sql, &placeholders)), bindings)
+        } else {
+            (Cow::Borrowed(sql), bindings)
+        }
+    }
+
+    pub(crate) fn push(&mut self, value: Value) {
+        self.positional.push(value);
+    }
+
+    /// 绑定命名参数；`name` 可带 `:` / `@` 前缀。
+    pub(crate) fn insert(&mut self, name: &str, value: Value) {
+        let name = name.strip_prefix([':', '@']).unwrap_or(name);
+        self.named.insert(name.to_string(), value);
+    }
+
+    pub(crate) fn extend(&mut self, params: Params) {
+        match params {
+            Params::Positional(values) => self.positional.extend(values),
+            Params::Named(values) => self.named.extend(values),
+        }
+    }
+
+    pub(crate) fn clear(&mut self) {
+        self.positional.clear();
+        self.named.clear();
+    }
+
+    /// 取出全部绑定，按 `sql`（prepare 时的原始文本）的占位符顺序排列。
+    pub(crate) fn take(&mut self, sql: &str) -> Result<Vec<Value>, TalonError> {
+        let positional = mem::take(&mut self.positional);
+        let named = mem::take(&mut self.named);
+        if named.is_empty() && !self.rewritten {
+            return Ok(positional);
+        }
+        let params = match (positional.is_empty(), named.is_empty()) {
+            (_, true) => Params::Positional(positional),
+            (true, false) => Params::Named(named),
+            (false, false) => {
+                return Err(TalonError(
+                    "statement mixes positional and named bindings".into(),
+                ))
+            }
+        };
+        Ok(params.bind(sql)?.1)
+    }
+}
+
+#[derive(Debug, PartialEq)]
+enum Placeholder<'s> {
+    Anonymous,
+    Numbered(usize),
+    Named(&'s str),
+}
+
+struct Span<'s> {
+    start: usize,
+    end: usize,
+    kind: Placeholder<'s>,
+}
+
+/// 扫描 SQL 中的占位符，跳过字符串、引号标识符与注释（不识别美元符号引用与嵌套注释）。
+fn scan_placeholders(sql: &str) -> Vec<Span<'_>> {
+    let bytes = sql.as_bytes();
+    let ident_end = |from: usize| {
+        from + bytes[from..]
+            .iter()
+            .take_while(|b| b.is_ascii_alphanumeric() || **b == b'_')
+            .count()
+    };
+    let mut out = Vec::new();
+    let mut i = 0;
+    while i < bytes.len() {
+        match bytes[i] {
+            quote @ (b'\'' | b'"') => {
+                i += 1;
+                while i < bytes.len() {
+                    if bytes[i] == quote {
+                        // 连续两个引号是转义，继续留在字面量中。
+                        if bytes.get(i + 1) == Some(&quote) {
+                            i += 2;
+                            continue;
+                        }
+                        break;
+                    }
+                    i += 1;
+                }
+                i += 1;
+            }
+            b'-' if bytes.get(i + 1) == Some(&b'-') => {
+                i = sql[i..].find('\n').map_or(bytes.len(), |n| i + n + 1);
+            }
+            b'/' if bytes.get(i + 1) == Some(&b'*') => {
+                i = sql[i + 2..]
+                    .find("*/")
+                    .map_or(bytes.len(), |n| i + 2 + n + 2);
+            }
+            b':' if bytes.get(i + 1) == Some(&b':') => i += 2,
+            b'?' => {
+                out.push(Span {
+                    start: i,
+                    end: i + 1,
+                    kind: Placeholder::Anonymous,
+                });
+                i += 1;
+            }
+            b'$' if bytes.get(i + 1).is_some_and(u8::is_ascii_digit) => {
+                let end = i
+                    + 1
+                    + bytes[i + 1..]
+                        .iter()
+                        .take_while(|b| b.is_ascii_digit())
+                        .count();
+                let n = sql[i + 1..end].parse().unwrap_or(usize::MAX);
+                out.push(Span {
+                    start: i,
+                    end,
+                    kind: Placeholder::Numbered(n),
+                });
+                i = end;
+            }
+            b':' | b'@'
+                if bytes
+                    .get(i + 1)
+                    .is_some_and(|b| b.is_ascii_alphabetic() || *b == b'_') =>
+            {
+                let end = ident_end(i + 1);
+                out.push(Span {
+                    start: i,
+                    end,
+                    kind: Placeholder::Named(&sql[i + 1..end]),
+                });
+                i = end;
+            }
+            _ => i += 1,
+        }
+    }
+    out
+}
+
+fn rewrite(sql: &str, placeholders: &[Span<'_>]) -> String {
+    let mut out = String::with_capacity(sql.len());
+    let mut last = 0;
+    for p in placeholders {
+        out.push_str(&sql[last..p.start]);
+        out.push('?');
+        last = p.end;
+    }
+    out.push_str(&sql[last..]);
+    out
+}
+
+impl Talon {
+    /// 执行带位置或命名参数的 SQL（`?`、`$1`、`:name`、`@name`）。
+    pub fn run_sql_with(
+        &self,
+        sql: &str,
+        params: impl Into<Params>,
+    ) -> Result<Vec<Vec<Value>>, TalonError> {
+        let (sql, values) = params.into().bind(sql)?;
+        self.run_sql_param(&sql, &values)
+    }
+}
+
+impl TalonRemoteClient {
+    /// Run SQL with positional or named parameters (`?`, `$1`, `:name`, `@name`).
+    pub fn run_sql_with(
+        &self,
+        sql: &str,
+        params: impl Into<Params>,
+    ) -> Result<Vec<Vec<Value>>, TalonError> {
+        let (sql, values) = params.into().bind(sql)?;
+        if values.is_empty() {
+            return self.run_sql(&sql);
+        }
+        self.run_sql(&inline_sql_params(&sql, &values)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn named(pairs: &[(&str, Value)]) -> Params {
+        Params::Named(
+            pairs
+                .iter()
+                .map(|(k, v)| (k.to_string(), v.clone()))
+                .collect(),
+        )
+    }
+
+    #[test]
+    fn named_placeholders_are_rewritten_in_order() {
+        let params = named(&[("id", Value::Integer(1)), ("name", Value::Text("a".into()))]);
+        let (sql, values) = params
+            .bind("SELECT * FROM t WHERE id = :id AND (name = @name OR alias = :name) AND note = ':id' AND x::TEXT = 'y'")
+            .unwrap();
+        assert_eq!(
+            sql,
+            "SELECT * FROM t WHERE id = ? AND (name = ? OR alias = ?) AND note = ':id' AND x::TEXT = 'y'"
+        );
+        assert_eq!(
+            values,
+            vec![
+                Value::Integer(1),
+                Value::Text("a".into()),
+                Value::Text("a".into())
+            ]
+        );
+    }
+
+    #[test]
+    fn numbered_placeholders_may_repeat() {
+        let (sql, values) = Params::from([Value::Integer(5), Value::Integer(6)])
+            .bind("SELECT $2, $1, $2 -- $3 in a comment")
+            .unwrap();
+        assert_eq!(sql, "SELECT ?, ?, ? -- $3 in a comment");
+        assert_eq!(
+            values,
+            vec![Value::Integer(6), Value::Integer(5), Value::Integer(6)]
+        );
+    }
+
+    #[test]
+    fn missing_and_unused_names_are_reported() {
+        let err = named(&[]).bind("SELECT :id").unwrap_err();
+        assert_eq!(err.0, "missing SQL parameter :id");
+        let err = named(&[("id", Value::Null), ("extra", Value::Null)])
+            .bind("SELECT :id")
+            .unwrap_err();
+        assert_eq!(err.0, "unused SQL parameters: extra");
+        let err = Params::from([Value::Null]).bind("SELECT $2").unwrap_err();
+        assert_eq!(err.0, "missing SQL parameter $2");
+        let err = Params::default().bind("SELECT ?, :id").unwrap_err();
+        assert!(err.0.contains("mixes"));
+    }
+
+    #[test]
+    fn scanner_limits_match_module_docs() {
+        // 美元符号引用与嵌套注释不被识别，其中的占位符仍会被改写。
+        let params = named(&[("a", Value::Null), ("b", Value::Null)]);
+        let (sql, _) = params.bind("SELECT $q$ :a $q$, /* /* */ :b */").unwrap();
+        assert_eq!(sql, "SELECT $q$ ? $q$, /* /* */ ? */");
+    }
+
+    #[test]
+    fn serde_structs_become_named_params() {
+        #[derive(Serialize)]
+        struct Filter {
+            id: i64,
+            active: bool,
+        }
+        let params = Params::from_serde(&Filter {
+            id: 3,
+            active: true,
+        })
+        .unwrap();
+        let (sql, values) = params
+            .bind("SELECT * FROM t WHERE id = :id AND active = :active")
+            .unwrap();
+        assert_eq!(sql, "SELECT * FROM t WHERE id = ? AND active = ?");
+        assert_eq!(values, vec![Value::Integer(3), Value::Boolean(true)]);
+
+        let (sql, values) = Params::from((Value::Integer(1),)).bind("SELECT ?").unwrap();
+        assert_eq!(sql, "SELECT ?");
+        assert_eq!(values.len(), 1);
+    }
+
+    #[test]
+    fn empty_positional_params_pass_sql_through() {
+        let (sql, values) = Params::from(&[]).bind("SELECT @@version, :x").unwrap();
+        assert_eq!(sql, "SELECT @@version, :x");
+        assert!(values.is_empty());
+    }
+
+    #[test]
+    fn statement_bindings_follow_prepared_placeholders() {
+        let (sql, mut bindings) = Bindings::prepare("UPDATE t SET a = :a WHERE id = @id");
+        assert_eq!(sql, "UPDATE t SET a = ? WHERE id = ?");
+        bindings.insert(":id", Value::Integer(9));
+        bindings.extend(named(&[("a", Value::Null)]));
+        let values = bindings.take("UPDATE t SET a = :a WHERE id = @id").unwrap();
+        assert_eq!(values, vec![Value::Null, Value::Integer(9)]);
+
+        let (_, mut bindings) = Bindings::prepare("SELECT $2, $1");
+        bindings.push(Value::Integer(1));
+        bindings.push(Value::Integer(2));
+        let values = bindings.take("SELECT $2, $1").unwrap();
+        assert_eq!(values, vec![Value::Integer(2), Value::Integer(1)]);
+
+        let (sql, mut bindings) = Bindings::prepare("SELECT ?");
+        assert!(matches!(sql, Cow::Borrowed(_)));
+        bindings.push(Value::Integer(1));
+        bindings.insert("x", Value::Null);
+        assert!(bindings.take("SELECT ?").unwrap_err().0.contains("mixes"));
+    }
+
+    #[test]
+    fn rust_values_convert_into_params() {
+        let (_, values) = Params::from((1, "a", None::<f64>))
+            .bind("SELECT ?, ?, ?")
+            .unwrap();
+        assert_eq!(
+            values,
+            vec![Value::Integer(1), Value::Text("a".into()), Value::Null]
+        );
+        let mixed: &[&dyn ToValue] = &[&2i32, &true];
+        let (_, values) = Params::from(mixed).bind("SELECT $1, $2").unwrap();
+        assert_eq!(values, vec![Value::Integer(2), Value::Boolean(true)]);
+    }
+}
@@ -0,0 +1,430 @@
+/*
+ * Copyright (c) 2026 Talon Contributors
+ * Author: dark.lijin@gmail.com
+ * Licensed under the Talon Community Dual License Agreement.
+ * See the LICENSE file in the project root for full license information.
+ */
+//! 限流原语 — 基于 KV 原子操作 + TTL，嵌入式与远程模式通用。
+//!
+//! - 滑动窗口：窗口切分为 [`SLIDING_WINDOW_BUCKETS`] 个子窗口，每个子窗口一个
+//!   `incr_by` 计数器（带 TTL）。请求时批量读取窗口内的计数器，先自增当前子窗口，
+//!   超限则自减回滚；每次请求的开销与 `limit` 无关，也不需要重试。
+//!   计数精度为一个子窗口，并发超限时双方可能都被拒绝（偏保守）。
+//! - 令牌桶：状态是（剩余令牌, 上次补充时间）二元组，无法用单个计数器原子更新，
+//!   因此以 `tokens:last_ms` 存于单个 key，用 compare-and-swap 乐观更新；
+//!   竞争激烈时最多重试 `CAS_MAX_RETRIES` 次后返回错误。
+
+use std::sync::Arc;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use crate::{KvBackend, Talon, TalonError, TalonRemoteClient};
+
+/// 令牌桶 CAS 冲突的最大重试次数。
+const CAS_MAX_RETRIES: usize = 32;
+
+/// 滑动窗口切分的子窗口数（窗口不足该毫秒数时按 1ms 一个子窗口）。
+pub const SLIDING_WINDOW_BUCKETS: u64 = 10;
+
+/// 限流策略。
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum RateLimitStrategy {
+    /// 令牌桶：容量 `capacity`，每秒补充 `refill_per_sec` 个令牌。
+    TokenBucket { capacity: u64, refill_per_sec: f64 },
+    /// 滑动窗口：按子窗口计数，最近 `window` 时长内最多 `limit` 个单位。
+    SlidingWindowLog { limit: u64, window: Duration },
+}
+
+/// 限流判定结果。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Decision {
+    /// 本次请求是否放行。
+    pub allowed: bool,
+    /// 被拒绝时建议的等待时长；放行时为零。
+    pub retry_after: Duration,
+}
+
+impl Decision {
+    fn allow() -> Self {
+        Self {
+            allowed: true,
+            retry_after: Duration::ZERO,
+        }
+    }
+
+    fn deny(retry_after: Duration) -> Self {
+        Self {
+            allowed: false,
+            retry_after,
+        }
+    }
+}
+
+/// 限流器：同名限流器的状态存放在 `_talon_rl:{name}:` 前缀下，多进程共享。
+pub struct RateLimiter<B: KvBackend> {
+    backend: Arc<B>,
+    name: String,
+    strategy: RateLimitStrategy,
+}
+
+impl<B: KvBackend> RateLimiter<B> {
+    /// 创建限流器；参数非法（容量、速率、窗口为零）时返回错误。
+    pub fn new(
+        backend: Arc<B>,
+        name: &str,
+        strategy: RateLimitStrategy,
+    ) -> Result<Self, TalonError> {
+        match strategy {
+            RateLimitStrategy::TokenBucket {
+                capacity,
+                refill_per_sec,
+            } => {
+                if capacity == 0 || !(refill_per_sec > 0.0 && refill_per_sec.is_finite()) {
+                    return Err(TalonError(format!(
+                        "rate limiter {name}: token bucket needs capacity > 0 and a positive refill rate"
+                    )));
+                }
+            }
+            RateLimitStrategy::SlidingWindowLog { limit, window } => {
+                if limit == 0 || window.as_millis() == 0 {
+                    return Err(TalonError(format!(
+                        "rate limiter {name}: sliding window needs limit > 0 and window >= 1ms"
+                    )));
+                }
+            }
+        }
+        Ok(Self {
+            backend,
+            name: name.to_string(),
+            strategy,
+        })
+    }
+
+    /// 限流器名称。
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// 限流策略。
+    pub fn strategy(&self) -> RateLimitStrategy {
+        self.strategy
+    }
+
+    /// 为 `key`（如用户 ID）申请 `cost` 个单位。
+    ///
+    /// `cost` 超过桶容量 / 窗口上限时永远无法满足，返回错误而非拒绝。
+    pub fn acquire(&self, key: &str, cost: u64) -> Result<Decision, TalonError> {
+        self.acquire_at(key, cost, now_ms())
+    }
+
+    fn acquire_at(&self, key: &str, cost: u64, now_ms: u64) -> Result<Decision, TalonError> {
+        let key = format!("_talon_rl:{}:{key}", self.name);
+        match self.strategy {
+            RateLimitStrategy::TokenBucket {
+                capacity,
+                refill_per_sec,
+            } => self.token_bucket(&key, cost, capacity, refill_per_sec, now_ms),
+            RateLimitStrategy::SlidingWindowLog { limit, window } => {
+                self.sliding_window_log(&key, cost, limit, window, now_ms)
+            }
+        }
+    }
+
+    fn token_bucket(
+        &self,
+        key: &str,
+        cost: u64,
+        capacity: u64,
+        rate: f64,
+        now_ms: u64,
+    ) -> Result<Decision, TalonError> {
+        if cost > capacity {
+            return Err(TalonError(format!(
+                "rate limiter {}: cost {cost} exceeds bucket capacity {capacity}",
+                self.name
+            )));
+        }
+        let key = key.as_bytes();
+        // 桶从空补满所需时间之后状态可丢弃（等价于满桶）；极低速率下截断到 KV TTL 上限。
+        let ttl_secs = ((capacity as f64 / rate).ceil() as u64)
+            .saturating_add(1)
+            .min(i64::MAX as u64);
+        for _ in 0..CAS_MAX_RETRIES {
+            let current = self.backend.kv_get(key)?;
+            let (tokens, last_ms) = current
+                .as_deref()
+                .and_then(parse_bucket_state)
+                .unwrap_or((capacity as f64, now_ms));
+            let elapsed = now_ms.saturating_sub(last_ms) as f64 / 1000.0;
+            let tokens = (tokens + elapsed * rate).min(capacity as f64);
+            let cost = cost as f64;
+            if tokens < cost {
+                // 极低速率下等待时长可能超出 `Duration` 范围，饱和为 `Duration::MAX`。
+                let wait =
+                    Duration::try_from_secs_f64((cost - tokens) / rate).unwrap_or(Duration::MAX);
+                return Ok(Decision::deny(wait));
+            }
+            let state = format!("{}:{now_ms}", tokens - cost);
+            if self.backend.kv_compare_and_swap(
+                key,
+                current.as_deref(),
+                Some(state.as_bytes()),
+                Some(ttl_secs),
+            )? {
+                return Ok(Decision::allow());
+            }
+        }
+        Err(TalonError(format!(
+            "rate limiter {}: too much contention on token bucket",
+            self.name
+        )))
+    }
+
+    fn sliding_window_log(
+        &self,
+        key: &str,
+        cost: u64,
+        limit: u64,
+        window: Duration,
+        now_ms: u64,
+    ) -> Result<Decision, TalonError> {
+        if cost > limit {
+            return Err(TalonError(format!(
+                "rate limiter {}: cost {cost} exceeds window limit {limit}",
+                self.name
+            )));
+        }
+        let delta = i64::try_from(cost).map_err(|_| {
+            TalonError(format!(
+                "rate limiter {}: cost {cost} out of range",
+                self.name
+            ))
+        })?;
+        let window_ms = window.as_millis() as u64;
+        let bucket_ms = (window_ms / SLIDING_WINDOW_BUCKETS).max(1);
+        let buckets = window_ms.div_ceil(bucket_ms);
+        let current = now_ms / bucket_ms;
+        let first = current.saturating_sub(buckets - 1);
+        let keys: Vec<String> = (first..=current).map(|b| format!("{key}:{b}")).collect();
+        let key_refs: Vec<&[u8]> = keys.iter().map(|k| k.as_bytes()).collect();
+        let mut counts = self
+            .backend
+            .kv_get_many(&key_refs)?
+            .iter()
+            .map(|raw| raw.as_deref().map_or(Ok(0), parse_counter))
+            .collect::<Result<Vec<u64>, TalonError>>()?;
+        let last = counts.len() - 1;
+        let earlier: u64 = counts[..last].iter().sum();
+        let current_key = key_refs[last];
+        let mut used = earlier + counts[last];
+        if used + cost <= limit {
+            let after = self.backend.kv_incr_by(current_key, delta)?;
+            if after == delta {
+                // 子窗口移出窗口后计数器即可丢弃。
+                let ttl = Duration::from_millis(bucket_ms * (buckets + 1));
+                self.backend.kv_expire(current_key, ttl)?;
+            }
+            let after = after.max(0) as u64;
+            if earlier + after <= limit {
+                return Ok(Decision::allow());
+            }
+            // 并发请求抢先占满了额度：回滚本次自增。
+            self.backend.kv_incr_by(current_key, -delta)?;
+            counts[last] = after - cost;
+            used = earlier + after - cost;
+        }
+        let excess = used + cost - limit;
+        window_retry_after(&counts, first, buckets, bucket_ms, excess, now_ms)
+            .map(Decision::deny)
+            .ok_or_else(|| {
+                TalonError(format!(
+                    "rate limiter {}: inconsistent sliding window counters",
+                    self.name
+                ))
+            })
+    }
+}
+
+/// 从最旧的子窗口开始依次移出窗口，返回释放出 `excess` 个单位所需的等待时长。
+fn window_retry_after(
+    counts: &[u64],
+    first: u64,
+    buckets: u64,
+    bucket_ms: u64,
+    excess: u64,
+    now_ms: u64,
+) -> Option<Duration> {
+    let mut released = 0;
+    for (bucket, &n) in (first..).zip(counts) {
+        released += n;
+        if released >= excess {
+            let expires_ms = (bucket + buckets) * bucket_ms;
+            return Some(Duration::from_millis(expires_ms.saturating_sub(now_ms)));
+        }
+    }
+    None
+}
+
+fn now_ms() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_millis() as u64
+}
+
+fn parse_bucket_state(raw: &[u8]) -> Option<(f64, u64)> {
+    let (tokens, last_ms) = std::str::from_utf8(raw).ok()?.split_once(':')?;
+    Some((tokens.parse().ok()?, last_ms.parse().ok()?))
+}
+
+/// 子窗口计数器由 `incr_by` 写入，以十进制文本存储。
+fn parse_counter(raw: &[u8]) -> Result<u64, TalonError> {
+    std::str::from_utf8(raw)
+        .ok()
+        .and_then(|text| text.parse().ok())
+        .ok_or_else(|| {
+            TalonError(format!(
+                "rate limiter counter is not an integer: {:?}",
+                String::from_utf8_lossy(raw)
+            ))
+        })
+}
+
+impl Talon {
+    /// 创建基于本库 KV 的限流器。
+    pub fn rate_limiter(
+        self: &Arc<Self>,
+        name: &str,
+        strategy: RateLimitStrategy,
+    ) -> Result<RateLimiter<Talon>, TalonError> {
+        RateLimiter::new(Arc::clone(self), name, strategy)
+    }
+}
+
+impl TalonRemoteClient {
+    /// Create a rate limiter whose state lives in the remote server's KV store.
+    pub fn rate_limiter(
+        self: &Arc<Self>,
+        name: &str,
+        strategy: RateLimitStrategy,
+    ) -> Result<RateLimiter<TalonRemoteClient>, TalonError> {
+        RateLimiter::new(Arc::clone(self), name, strategy)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::MemKv;
+
+    #[test]
+    fn token_bucket_refills_over_time() {
+        let strategy = RateLimitStrategy::TokenBucket {
+            capacity: 3,
+            refill_per_sec: 1.0,
+        };
+        let rl = RateLimiter::new(Arc::new(MemKv::default()), "llm", strategy).unwrap();
+        let t0 = 1_000_000;
+        assert!(rl.acquire_at("alice", 2, t0).unwrap().allowed);
+        assert!(rl.acquire_at("alice", 1, t0).unwrap().allowed);
+        let denied = rl.acquire_at("alice", 2, t0).unwrap();
+        assert!(!denied.allowed);
+        assert_eq!(denied.retry_after, Duration::from_secs(2));
+        // 其他 key 互不影响。
+        assert!(rl.acquire_at("bob", 3, t0).unwrap().allowed);
+        assert!(rl.acquire_at("alice", 2, t0 + 2_000).unwrap().allowed);
+        assert!(rl.acquire_at("alice", 4, t0).is_err());
+    }
+
+    #[test]
+    fn token_bucket_tiny_refill_rate_saturates_retry_after() {
+        let strategy = RateLimitStrategy::TokenBucket {
+            capacity: 1,
+            refill_per_sec: f64::MIN_POSITIVE,
+        };
+        let rl = RateLimiter::new(Arc::new(MemKv::default()), "slow", strategy).unwrap();
+        let t0 = 1_000_000;
+        assert!(rl.acquire_at("alice", 1, t0).unwrap().allowed);
+        let denied = rl.acquire_at("alice", 1, t0 + 1_000).unwrap();
+        assert!(!denied.allowed);
+        assert_eq!(denied.retry_after, Duration::MAX);
+    }
+
+    #[test]
+    fn sliding_window_log_counts_recent_requests_only() {
+        let strategy = RateLimitStrategy::SlidingWindowLog {
+            limit: 5,
+            window: Duration::from_secs(10),
+        };
+        let rl = RateLimiter::new(Arc::new(MemKv::default()), "llm", strategy).unwrap();
+        let t0 = 1_000_000;
+        assert!(rl.acquire_at("alice", 3, t0).unwrap().allowed);
+        assert!(rl.acquire_at("alice", 2, t0 + 4_000).unwrap().allowed);
+        let denied = rl.acquire_at("alice", 1, t0 + 5_000).unwrap();
+        assert!(!denied.allowed);
+        // 最早的 3 个单位所在子窗口在 t0 + 10s 移出窗口。
+        assert_eq!(denied.retry_after, Duration::from_secs(5));
+        assert!(rl.acquire_at("alice", 3, t0 + 10_000).unwrap().allowed);
+        assert!(!rl.acquire_at("alice", 1, t0 + 10_000).unwrap().allowed);
+        assert!(rl.acquire_at("alice", 6, t0).is_err());
+    }
+
+    #[test]
+    fn sliding_window_log_keeps_one_counter_per_sub_window() {
+        let strategy = RateLimitStrategy::SlidingWindowLog {
+            limit: 2,
+            window: Duration::from_millis(1_000),
+        };
+        let kv = Arc::new(MemKv::default());
+        let rl = RateLimiter::new(Arc::clone(&kv), "llm", strategy).unwrap();
+        let t0 = 1_000_000;
+        assert!(rl.acquire_at("alice", 1, t0).unwrap().allowed);
+        assert!(rl.acquire_at("alice", 1, t0 + 7).unwrap().allowed);
+        let denied = rl.acquire_at("alice", 1, t0 + 999).unwrap();
+        assert_eq!(denied.retry_after, Duration::from_millis(1));
+        // 精度为一个子窗口（100ms）：t0 与 t0 + 7 落在同一子窗口，一起移出。
+        assert!(rl.acquire_at("alice", 1, t0 + 1_000).unwrap().allowed);
+        assert!(rl.acquire_at("alice", 1, t0 + 1_006).unwrap().allowed);
+        assert!(!rl.acquire_at("alice", 1, t0 + 1_050).unwrap().allowed);
+        assert_eq!(
+            kv.kv_get(b"_talon_rl:llm:alice:10000").unwrap(),
+            Some(b"2".to_vec())
+        );
+        assert_eq!(
+            kv.kv_get(b"_talon_rl:llm:alice:10010").unwrap(),
+            Some(b"2".to_vec())
+        );
+    }
+
+    #[test]
+    fn sliding_window_log_rolls_back_when_a_concurrent_request_wins() {
+        let strategy = RateLimitStrategy::SlidingWindowLog {
+            limit: 2,
+            window: Duration::from_secs(1),
+        };
+        let kv = Arc::new(MemKv::default());
+        let rl = RateLimiter::new(Arc::clone(&kv), "llm", strategy).unwrap();
+        let t0 = 1_000_000;
+        assert!(rl.acquire_at("alice", 1, t0).unwrap().allowed);
+        // 读取计数器之后、自增之前，另一个进程占掉了剩余额度。
+        kv.before_next_incr(|map| {
+            map.insert(b"_talon_rl:llm:alice:10000".to_vec(), b"2".to_vec());
+        });
+        let denied = rl.acquire_at("alice", 1, t0 + 50).unwrap();
+        assert!(!denied.allowed);
+        assert_eq!(denied.retry_after, Duration::from_millis(950));
+        assert_eq!(
+            kv.kv_get(b"_talon_rl:llm:alice:10000").unwrap(),
+            Some(b"2".to_vec())
+        );
+    }
+
+    #[test]
+    fn invalid_strategy_is_rejected() {
+        let kv = Arc::new(MemKv::default());
+        let zero = RateLimitStrategy::SlidingWindowLog {
+            limit: 0,
+            window: Duration::from_secs(1),
+        };
+        assert!(RateLimiter::new(kv, "x", zero).is_err());
+    }
+}
@@ -0,0 +1,428 @@
+/*
+ * Copyright (c) 2026 Talon Contributors
+ * Author: dark.lijin@gmail.com
+ * Licensed under the Talon Community Dual License Agreement.
+ * See the LICENSE file in the project root for full license information.
+ */
+//! 带元数据的查询结果 — 列名、声明类型、影响行数、自增 ID。
+//!
+//! 嵌入式二进制格式在原有行块（`row_count, col_count, cells`）前加扩展头：
+//!
+//! ```text
+//! magic "TQR1"
+//! rows_affected: u64
+//! has_last_insert_id: u8, last_insert_id: i64
+//! col_count: u32, 每列 name_len: u32, name, type_len: u32, decl_type
+//! <原有行块>
+//! ```
+//!
+//! 不带 magic 的结果按旧格式解码（列元数据为空）。
+
+use std::ffi::CString;
+use std::ptr;
+use std::slice;
+
+use crate::{
+    decode_rows_bin, encode_params, ffi_error, inline_sql_params, raw_ffi, remote_error,
+    remote_response_data, talon_value_from_json, FromRow, Params, Talon, TalonError,
+    TalonRemoteClient, TalonRemoteErrorKind, Value,
+};
+
+const QUERY_RESULT_MAGIC: &[u8; 4] = b"TQR1";
+
+/// 结果列元数据。
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ColumnInfo {
+    /// 列名（表达式列为引擎生成的名称）。
+    pub name: String,
+    /// 声明类型（如 `INT`、`TEXT`）；表达式列或未知时为 `None`。
+    pub decl_type: Option<String>,
+}
+
+/// `query` 的返回值。
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct QueryResult {
+    pub columns: Vec<ColumnInfo>,
+    pub rows: Vec<Vec<Value>>,
+    /// INSERT / UPDATE / DELETE 影响的行数；查询语句为 0。
+    pub rows_affected: u64,
+    /// 最近一次 INSERT 生成的自增 ID。
+    pub last_insert_id: Option<i64>,
+}
+
+impl QueryResult {
+    /// 按列名查找列位置。
+    pub fn column_index(&self, name: &str) -> Option<usize> {
+        self.columns.iter().position(|c| c.name == name)
+    }
+
+    /// 取第 `row` 行名为 `column` 的值。
+    pub fn get(&self, row: usize, column: &str) -> Option<&Value> {
+        let idx = self.column_index(column)?;
+        self.rows.get(row)?.get(idx)
+    }
+
+    /// 结果行数。
+    pub fn len(&self) -> usize {
+        self.rows.len()
+    }
+
+    /// 是否没有结果行。
+    pub fn is_empty(&self) -> bool {
+        self.rows.is_empty()
+    }
+
+    /// 把每行映射为 `T`。
+    pub fn rows_as<T: FromRow>(self) -> Result<Vec<T>, TalonError> {
+        crate::row::map_rows(self.rows)
+    }
+}
+
+/// `execute` 的返回值：只含写语句的影响行数与自增 ID，不解码结果行。
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ExecResult {
+    /// INSERT / UPDATE / DELETE 影响的行数。
+    pub rows_affected: u64,
+    /// 最近一次 INSERT 生成的自增 ID。
+    pub last_insert_id: Option<i64>,
+}
+
+/// 解码 `talon_query_bin` 的结果（兼容无扩展头的旧格式）。
+pub(crate) fn decode_query_result_bin(data: &[u8]) -> Result<QueryResult, TalonError> {
+    let Some(header) = decode_query_header(data)? else {
+        return Ok(QueryResult {
+            rows: decode_rows_bin(data)?,
+            ..QueryResult::default()
+        });
+    };
+    let rows = if header.body.is_empty() {
+        vec![]
+    } else {
+        decode_rows_bin(header.body)?
+    };
+    Ok(QueryResult {
+        columns: header.columns,
+        rows,
+        rows_affected: header.exec.rows_affected,
+        last_insert_id: header.exec.last_insert_id,
+    })
+}
+
+/// 只解码扩展头，跳过行块；旧格式没有影响行数，按零处理。
+pub(crate) fn decode_exec_result_bin(data: &[u8]) -> Result<ExecResult, TalonError> {
+    Ok(decode_query_header(data)?.map_or_else(ExecResult::default, |header| header.exec))
+}
+
+struct QueryHeader<'a> {
+    exec: ExecResult,
+    columns: Vec<ColumnInfo>,
+    /// 扩展头之后的行块。
+    body: &'a [u8],
+}
+
+/// 解析扩展头；无 magic（旧格式）时返回 `None`。
+fn decode_query_header(data: &[u8]) -> Result<Option<QueryHeader<'_>>, TalonError> {
+    if !data.starts_with(QUERY_RESULT_MAGIC) {
+        return Ok(None);
+    }
+    let mut reader = HeaderReader {
+        data,
+        pos: QUERY_RESULT_MAGIC.len(),
+    };
+    let rows_affected = u64::from_le_bytes(reader.take_array()?);
+    let has_last_insert_id = reader.take_array::<1>()?[0] != 0;
+    let last_insert_id = i64::from_le_bytes(reader.take_array()?);
+    let col_count = u32::from_le_bytes(reader.take_array()?) as usize;
+    let mut columns = Vec::with_capacity(col_count.min(1024));
+    for _ in 0..col_count {
+        let name = reader.take_str()?;
+        let decl_type = reader.take_str()?;
+        columns.push(ColumnInfo {
+            name,
+            decl_type: (!decl_type.is_empty()).then_some(decl_type),
+        });
+    }
+    let exec = ExecResult {
+        rows_affected,
+        last_insert_id: has_last_insert_id.then_some(last_insert_id),
+    };
+    Ok(Some(QueryHeader {
+        exec,
+        columns,
+        body: &data[reader.pos..],
+    }))
+}
+
+struct HeaderReader<'a> {
+    data: &'a [u8],
+    pos: usize,
+}
+
+impl HeaderReader<'_> {
+    fn take(&mut self, n: usize) -> Result<&[u8], TalonError> {
+        let end = self
+            .pos
+            .checked_add(n)
+            .filter(|end| *end <= self.data.len())
+            .ok_or_else(|| TalonError("query result header truncated".into()))?;
+        let bytes = &self.data[self.pos..end];
+        self.pos = end;
+        Ok(bytes)
+    }
+
+    fn take_array<const N: usize>(&mut self) -> Result<[u8; N], TalonError> {
+        Ok(self.take(N)?.try_into().unwrap())
+    }
+
+    fn take_str(&mut self) -> Result<String, TalonError> {
+        let len = u32::from_le_bytes(self.take_array()?) as usize;
+        String::from_utf8(self.take(len)?.to_vec())
+            .map_err(|_| TalonError("invalid utf8 in query result header".into()))
+    }
+}
+
+impl Talon {
+    /// 执行查询，返回带列元数据的结果。
+    pub fn query(&self, sql: &str, params: impl Into<Params>) -> Result<QueryResult, TalonError> {
+        let (sql, params) = params.into().bind(sql)?;
+        self.raw_query(&sql, &params, "query", decode_query_result_bin)
+    }
+
+    /// 执行写语句（INSERT / UPDATE / DELETE / DDL），只返回影响行数与自增 ID。
+    pub fn execute(&self, sql: &str, params: impl Into<Params>) -> Result<ExecResult, TalonError> {
+        let (sql, params) = params.into().bind(sql)?;
+        self.raw_query(&sql, &params, "execute", decode_exec_result_bin)
+    }
+
+    fn raw_query<T: Default>(
+        &self,
+        sql: &str,
+        params: &[Value],
+        op: &str,
+        decode: fn(&[u8]) -> Result<T, TalonError>,
+    ) -> Result<T, TalonError> {
+        let c_sql = CString::new(sql)?;
+        let params_bin = encode_params(params);
+        let mut out_data: *mut u8 = ptr::null_mut();
+        let mut out_len: usize = 0;
+        let rc = unsafe {
+            raw_ffi::talon_query_bin(
+                self.handle,
+                c_sql.as_ptr(),
+                params_bin.as_ptr(),
+                params_bin.len(),
+                &mut out_data,
+                &mut out_len,
+            )
+        };
+        if rc != 0 {
+            return Err(ffi_error(op));
+        }
+        if out_data.is_null() || out_len == 0 {
+            return Ok(T::default());
+        }
+        let data = unsafe { slice::from_raw_parts(out_data, out_len) };
+        let result = decode(data);
+        unsafe { raw_ffi::talon_free_bytes(out_data, out_len) };
+        result
+    }
+}
+
+impl TalonRemoteClient {
+    /// Run a query remotely and return rows together with column metadata.
+    pub fn query(&self, sql: &str, params: impl Into<Params>) -> Result<QueryResult, TalonError> {
+        let (sql, params) = params.into().bind(sql)?;
+        let resp = self.remote_query(&sql, &params)?;
+        remote_query_result(sql_response_data(&resp)?)
+    }
+
+    /// Run a write statement remotely and return only `rows_affected` and
+    /// `last_insert_id`; any rows in the reply are not decoded.
+    pub fn execute(&self, sql: &str, params: impl Into<Params>) -> Result<ExecResult, TalonError> {
+        let (sql, params) = params.into().bind(sql)?;
+        let resp = self.remote_query(&sql, &params)?;
+        Ok(remote_exec_result(sql_response_data(&resp)?))
+    }
+
+    fn remote_query(&self, sql: &str, params: &[Value]) -> Result<serde_json::Value, TalonError> {
+        let rendered;
+        let sql = if params.is_empty() {
+            sql
+        } else {
+            rendered = inline_sql_params(sql, params)?;
+            &rendered
+        };
+        self.exec_cmd_json(&serde_json::json!({
+            "module": "sql",
+            "action": "",
+            "params": { "sql": sql }
+        }))
+    }
+}
+
+fn sql_response_data(resp: &serde_json::Value) -> Result<&serde_json::Value, TalonError> {
+    remote_response_data(resp)?.ok_or_else(|| {
+        remote_error(
+            TalonRemoteErrorKind::Protocol,
+            format!("SQL response missing data: {resp}"),
+        )
+    })
+}
+
+/// 解析远程 SQL 响应：`rows` 必需，`columns`（字符串或 `{name,type}`）、
+/// `rows_affected`、`last_insert_id` 可选。
+pub(crate) fn remote_query_result(data: &serde_json::Value) -> Result<QueryResult, TalonError> {
+    let rows = data
+        .get("rows")
+        .and_then(|r| r.as_array())
+        .ok_or_else(|| {
+            remote_error(
+                TalonRemoteErrorKind::Protocol,
+                format!("SQL response missing rows array: {data}"),
+            )
+        })?
+        .iter()
+        .map(|row| {
+            row.as_array()
+                .ok_or_else(|| {
+                    remote_error(
+                        TalonRemoteErrorKind::Protocol,
+                        format!("SQL row is not an array: {row}"),
+                    )
+                })?
+                .iter()
+                .map(talon_value_from_json)
+                .collect()
+        })
+        .collect::<Result<Vec<Vec<Value>>, TalonError>>()?;
+    let columns = data
+        .get("columns")
+        .and_then(|c| c.as_array())
+        .map(|cols| {
+            cols.iter()
+                .map(|col| match col {
+                    serde_json::Value::String(name) => Ok(ColumnInfo {
+                        name: name.clone(),
+                        decl_type: None,
+                    }),
+                    _ => {
+                        let name = col.get("name").and_then(|n| n.as_str()).ok_or_else(|| {
+                            remote_error(
+                                TalonRemoteErrorKind::Protocol,
+                                format!("SQL column missing name: {col}"),
+                            )
+                        })?;
+                        Ok(ColumnInfo {
+                            name: name.to_string(),
+                            decl_type: col
+                                .get("type")
+                                .and_then(|t| t.as_str())
+                                .filter(|t| !t.is_empty())
+                                .map(str::to_string),
+                        })
+                    }
+                })
+                .collect::<Result<Vec<_>, TalonError>>()
+        })
+        .transpose()?
+        .unwrap_or_default();
+    let exec = remote_exec_result(data);
+    Ok(QueryResult {
+        columns,
+        rows,
+        rows_affected: exec.rows_affected,
+        last_insert_id: exec.last_insert_id,
+    })
+}
+
+/// 只读取远程 SQL 响应中的 `rows_affected` / `last_insert_id`，不解析 `rows`。
+pub(crate) fn remote_exec_result(data: &serde_json::Value) -> ExecResult {
+    ExecResult {
+        rows_affected: data
+            .get("rows_affected")
+            .and_then(|n| n.as_u64())
+            .unwrap_or(0),
+        last_insert_id: data.get("last_insert_id").and_then(|n| n.as_i64()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn extended_header_carries_column_metadata() {
+        let mut data = QUERY_RESULT_MAGIC.to_vec();
+        data.extend_from_slice(&3u64.to_le_bytes());
+        data.push(1);
+        data.extend_from_slice(&42i64.to_le_bytes());
+        data.extend_from_slice(&2u32.to_le_bytes());
+        for (name, ty) in [("id", "INT"), ("n", "")] {
+            data.extend_from_slice(&(name.len() as u32).to_le_bytes());
+            data.extend_from_slice(name.as_bytes());
+            data.extend_from_slice(&(ty.len() as u32).to_le_bytes());
+            data.extend_from_slice(ty.as_bytes());
+        }
+        // 行块：1 行 2 列，均为 NULL（tag 0）。
+        data.extend_from_slice(&1u32.to_le_bytes());
+        data.extend_from_slice(&2u32.to_le_bytes());
+        data.extend_from_slice(&[0, 0]);
+
+        let result = decode_query_result_bin(&data).unwrap();
+        assert_eq!(result.rows_affected, 3);
+        assert_eq!(result.last_insert_id, Some(42));
+        assert_eq!(result.columns[0].decl_type.as_deref(), Some("INT"));
+        assert_eq!(result.columns[1].decl_type, None);
+        assert_eq!(result.get(0, "n"), Some(&Value::Null));
+
+        assert!(decode_query_result_bin(&data[..10]).is_err());
+    }
+
+    #[test]
+    fn legacy_rows_block_decodes_without_metadata() {
+        let mut data = 0u32.to_le_bytes().to_vec();
+        data.extend_from_slice(&0u32.to_le_bytes());
+        let result = decode_query_result_bin(&data).unwrap();
+        assert!(result.columns.is_empty() && result.is_empty());
+    }
+
+    #[test]
+    fn remote_response_metadata() {
+        let data = serde_json::json!({
+            "rows": [[1, "a"]],
+            "columns": ["id", {"name": "name", "type": "TEXT"}],
+            "rows_affected": 0
+        });
+        let result = remote_query_result(&data).unwrap();
+        assert_eq!(result.column_index("name"), Some(1));
+        assert_eq!(result.columns[1].decl_type.as_deref(), Some("TEXT"));
+        assert_eq!(result.last_insert_id, None);
+        assert_eq!(result.len(), 1);
+    }
+
+    #[test]
+    fn exec_result_reads_only_the_header() {
+        let mut data = QUERY_RESULT_MAGIC.to_vec();
+        data.extend_from_slice(&2u64.to_le_bytes());
+        data.push(1);
+        data.extend_from_slice(&7i64.to_le_bytes());
+        data.extend_from_slice(&0u32.to_le_bytes());
+        // 截断的行块：`query` 会报错，`execute` 不解码行。
+        data.extend_from_slice(&[1, 0]);
+        assert!(decode_query_result_bin(&data).is_err());
+        let exec = decode_exec_result_bin(&data).unwrap();
+        assert_eq!(exec.rows_affected, 2);
+        assert_eq!(exec.last_insert_id, Some(7));
+        assert_eq!(
+            decode_exec_result_bin(&[0, 0, 0, 0, 0, 0, 0, 0]).unwrap(),
+            ExecResult::default()
+        );
+
+        let remote = remote_exec_result(&serde_json::json!({
+            "rows": "not an array",
+            "rows_affected": 3
+        }));
+        assert_eq!(remote.rows_affected, 3);
+        assert_eq!(remote.last_insert_id, None);
+    }
+}
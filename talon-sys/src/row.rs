@@ -0,0 +1,216 @@
+/*
+ * Copyright (c) 2026 Talon Contributors
+ * Author: dark.lijin@gmail.com
+ * Licensed under the Talon Community Dual License Agreement.
+ * See the LICENSE file in the project root for full license information.
+ */
+//! 结果行映射 — `FromRow` 与 `query_as` 系列辅助方法。
+//!
+//! 列按位置映射：元组第 N 个元素 / 结构体第 N 个字段对应结果行第 N 列，
+//! 结果列数必须与元素 / 字段数一致，多出或缺少的列均报错。
+//! 启用 `derive` feature 后可用 `#[derive(FromRow)]` 为结构体生成实现。
+
+use crate::{FromValue, Params, Talon, TalonError, TalonRemoteClient, Value};
+
+/// 将一行 `Vec<Value>` 转换为 Rust 类型。
+pub trait FromRow: Sized {
+    fn from_row(row: Vec<Value>) -> Result<Self, TalonError>;
+}
+
+/// 校验结果行的列数与目标类型一致。
+///
+/// 供 `#[derive(FromRow)]` 生成的代码使用，避免多出的列被静默忽略。
+#[doc(hidden)]
+pub fn row_check_len(row: &[Value], expected: usize) -> Result<(), TalonError> {
+    if row.len() != expected {
+        return Err(TalonError(format!(
+            "expected {expected} columns, but row has {}",
+            row.len()
+        )));
+    }
+    Ok(())
+}
+
+/// 取出第 `index` 列并转换为 `T`（原位置留下 `Null`）。
+///
+/// 供 `#[derive(FromRow)]` 生成的代码使用；错误信息包含列位置与期望类型。
+#[doc(hidden)]
+pub fn row_take<T: FromValue>(row: &mut [Value], index: usize) -> Result<T, TalonError> {
+    let len = row.len();
+    let cell = row.get_mut(index).ok_or_else(|| {
+        TalonError(format!(
+            "column {index}: expected {}, but row has only {len} columns",
+            T::TYPE_NAME
+        ))
+    })?;
+    T::from_value(std::mem::take(cell)).map_err(|found| {
+        TalonError(format!(
+            "column {index}: expected {}, found {}",
+            T::TYPE_NAME,
+            found.type_name()
+        ))
+    })
+}
+
+impl FromRow for Vec<Value> {
+    fn from_row(row: Vec<Value>) -> Result<Self, TalonError> {
+        Ok(row)
+    }
+}
+
+macro_rules! from_row_tuple {
+    ($len:literal: $($ty:ident => $idx:tt),+) => {
+        impl<$($ty: FromValue),+> FromRow for ($($ty,)+) {
+            fn from_row(mut row: Vec<Value>) -> Result<Self, TalonError> {
+                row_check_len(&row, $len)?;
+                Ok(($(row_take::<$ty>(&mut row, $idx)?,)+))
+            }
+        }
+    };
+}
+
+from_row_tuple!(1: A => 0);
+from_row_tuple!(2: A => 0, B => 1);
+from_row_tuple!(3: A => 0, B => 1, C => 2);
+from_row_tuple!(4: A => 0, B => 1, C => 2, D => 3);
+from_row_tuple!(5: A => 0, B => 1, C => 2, D => 3, E => 4);
+from_row_tuple!(6: A => 0, B => 1, C => 2, D => 3, E => 4, F => 5);
+from_row_tuple!(7: A => 0, B => 1, C => 2, D => 3, E => 4, F => 5, G => 6);
+from_row_tuple!(8: A => 0, B => 1, C => 2, D => 3, E => 4, F => 5, G => 6, H => 7);
+
+pub(crate) fn map_rows<T: FromRow>(rows: Vec<Vec<Value>>) -> Result<Vec<T>, TalonError> {
+    rows.into_iter()
+        .enumerate()
+        .map(|(i, row)| T::from_row(row).map_err(|e| TalonError(format!("row {i}: {}", e.0))))
+        .collect()
+}
+
+fn first_row<T: FromRow>(rows: Vec<Vec<Value>>) -> Result<Option<T>, TalonError> {
+    rows.into_iter()
+        .next()
+        .map(|row| T::from_row(row).map_err(|e| TalonError(format!("row 0: {}", e.0))))
+        .transpose()
+}
+
+impl Talon {
+    /// 执行参数化 SQL，并把每行映射为 `T`。
+    pub fn query_as<T: FromRow>(
+        &self,
+        sql: &str,
+        params: impl Into<Params>,
+    ) -> Result<Vec<T>, TalonError> {
+        map_rows(self.run_sql_with(sql, params)?)
+    }
+
+    /// 执行参数化 SQL，映射第一行；无结果时返回错误。
+    pub fn query_one_as<T: FromRow>(
+        &self,
+        sql: &str,
+        params: impl Into<Params>,
+    ) -> Result<T, TalonError> {
+        self.query_opt_as(sql, params)?
+            .ok_or_else(|| TalonError("query_one_as: query returned no rows".into()))
+    }
+
+    /// 执行参数化 SQL，映射第一行；无结果时返回 `None`。
+    pub fn query_opt_as<T: FromRow>(
+        &self,
+        sql: &str,
+        params: impl Into<Params>,
+    ) -> Result<Option<T>, TalonError> {
+        first_row(self.run_sql_with(sql, params)?)
+    }
+}
+
+impl TalonRemoteClient {
+    /// Run parameterized SQL remotely and map every row into `T`.
+    pub fn query_as<T: FromRow>(
+        &self,
+        sql: &str,
+        params: impl Into<Params>,
+    ) -> Result<Vec<T>, TalonError> {
+        map_rows(self.run_sql_with(sql, params)?)
+    }
+
+    /// Run parameterized SQL remotely and map the first row; errors when empty.
+    pub fn query_one_as<T: FromRow>(
+        &self,
+        sql: &str,
+        params: impl Into<Params>,
+    ) -> Result<T, TalonError> {
+        self.query_opt_as(sql, params)?
+            .ok_or_else(|| TalonError("query_one_as: query returned no rows".into()))
+    }
+
+    /// Run parameterized SQL remotely and map the first row, if any.
+    pub fn query_opt_as<T: FromRow>(
+        &self,
+        sql: &str,
+        params: impl Into<Params>,
+    ) -> Result<Option<T>, TalonError> {
+        first_row(self.run_sql_with(sql, params)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tuple_rows_convert_by_position() {
+        let rows = vec![
+            vec![Value::Integer(1), Value::Text("a".into()), Value::Null],
+            vec![
+                Value::Integer(2),
+                Value::Text("b".into()),
+                Value::Float(0.5),
+            ],
+        ];
+        let mapped: Vec<(i64, String, Option<f64>)> = map_rows(rows).unwrap();
+        assert_eq!(mapped[0], (1, "a".to_string(), None));
+        assert_eq!(mapped[1], (2, "b".to_string(), Some(0.5)));
+    }
+
+    #[test]
+    fn conversion_errors_name_column_and_type() {
+        let rows = vec![vec![Value::Integer(1), Value::Integer(2)]];
+        let err = map_rows::<(i64, String)>(rows).unwrap_err();
+        assert_eq!(err.0, "row 0: column 1: expected String, found Integer");
+
+        let err = map_rows::<(i64, i64, i64)>(vec![vec![Value::Integer(1)]]).unwrap_err();
+        assert_eq!(err.0, "row 0: expected 3 columns, but row has 1");
+        let err = map_rows::<(i64,)>(vec![vec![Value::Integer(1), Value::Null]]).unwrap_err();
+        assert_eq!(err.0, "row 0: expected 1 columns, but row has 2");
+
+        let err = map_rows::<(u8,)>(vec![vec![Value::Integer(300)]]).unwrap_err();
+        assert!(err.0.contains("column 0: expected u8, found Integer"));
+    }
+
+    #[cfg(feature = "derive")]
+    #[test]
+    fn derived_struct_maps_fields_in_order() {
+        #[derive(crate::FromRow, Debug, PartialEq)]
+        struct User {
+            id: i64,
+            name: String,
+            email: Option<String>,
+        }
+
+        #[derive(crate::FromRow, Debug, PartialEq)]
+        struct Pair(i64, bool);
+
+        let row = vec![Value::Integer(7), Value::Text("ann".into()), Value::Null];
+        assert_eq!(
+            first_row::<User>(vec![row]).unwrap(),
+            Some(User {
+                id: 7,
+                name: "ann".into(),
+                email: None,
+            })
+        );
+        let pair = first_row::<Pair>(vec![vec![Value::Integer(1), Value::Boolean(true)]]);
+        assert_eq!(pair.unwrap(), Some(Pair(1, true)));
+        let extra = vec![Value::Integer(1), Value::Boolean(true), Value::Null];
+        assert!(first_row::<Pair>(vec![extra]).is_err());
+    }
+}
@@ -0,0 +1,259 @@
+/*
+ * Copyright (c) 2026 Talon Contributors
+ * Author: dark.lijin@gmail.com
+ * Licensed under the Talon Community Dual License Agreement.
+ * See the LICENSE file in the project root for full license information.
+ */
+//! Schema 自省 — 一次调用描述整个多模数据库。
+//!
+//! 嵌入式与远程均通过 `{"module":"schema","action":"describe"}` 命令获取；
+//! 事务内的自省额外携带 `txn_id`，可见本事务未提交的 DDL。响应 `data` 形如：
+//!
+//! ```text
+//! {
+//!   "tables": [{"name", "columns": [{"name", "type", "nullable", "primary_key", "default"}],
+//!               "indexes": [{"name", "columns", "unique"}]}],
+//!   "vector_indexes": [{"name", "dim", "metric", "count"}],
+//!   "fts_indexes": [{"name", "doc_count"}],
+//!   "graphs": [{"name", "vertex_count", "edge_count"}],
+//!   "topics": [{"name", "max_len", "len"}]
+//! }
+//! ```
+//!
+//! 缺失的字段取默认值，旧版引擎未返回的分类为空列表。
+//!
+//! 表列类型与结果集的 [`crate::ColumnInfo`] 同名，因此本模块以 `talon_sys::schema`
+//! 路径导出。
+
+use serde::Deserialize;
+
+use crate::{
+    embedded_response_data, remote_error, remote_response_data, Talon, TalonError,
+    TalonRemoteClient, TalonRemoteErrorKind,
+};
+
+/// 数据库整体结构。
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(default)]
+pub struct Schema {
+    pub tables: Vec<TableInfo>,
+    pub vector_indexes: Vec<VectorIndexInfo>,
+    pub fts_indexes: Vec<FtsIndexInfo>,
+    pub graphs: Vec<GraphInfo>,
+    pub topics: Vec<TopicInfo>,
+}
+
+impl Schema {
+    /// 按名称查找 SQL 表。
+    pub fn table(&self, name: &str) -> Option<&TableInfo> {
+        self.tables.iter().find(|t| t.name == name)
+    }
+}
+
+/// SQL 表。
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(default)]
+pub struct TableInfo {
+    pub name: String,
+    pub columns: Vec<ColumnInfo>,
+    pub indexes: Vec<IndexInfo>,
+}
+
+impl TableInfo {
+    /// 按名称查找列。
+    pub fn column(&self, name: &str) -> Option<&ColumnInfo> {
+        self.columns.iter().find(|c| c.name == name)
+    }
+
+    /// 主键列名（按声明顺序）。
+    pub fn primary_key(&self) -> Vec<&str> {
+        self.columns
+            .iter()
+            .filter(|c| c.primary_key)
+            .map(|c| c.name.as_str())
+            .collect()
+    }
+}
+
+/// 表列定义。
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(default)]
+pub struct ColumnInfo {
+    pub name: String,
+    /// 声明类型（如 `INT`、`TEXT`、`VECTOR(384)`）。
+    #[serde(rename = "type")]
+    pub decl_type: String,
+    pub nullable: bool,
+    pub primary_key: bool,
+    /// 默认值表达式（SQL 文本）。
+    pub default: Option<String>,
+}
+
+impl Default for ColumnInfo {
+    fn default() -> Self {
+        Self {
+            name: String::new(),
+            decl_type: String::new(),
+            nullable: true,
+            primary_key: false,
+            default: None,
+        }
+    }
+}
+
+/// SQL 二级索引。
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(default)]
+pub struct IndexInfo {
+    pub name: String,
+    pub columns: Vec<String>,
+    pub unique: bool,
+}
+
+/// 向量索引。
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(default)]
+pub struct VectorIndexInfo {
+    pub name: String,
+    pub dim: u32,
+    /// 距离度量（如 `cosine`、`l2`）。
+    pub metric: String,
+    pub count: u64,
+}
+
+/// 全文索引。
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(default)]
+pub struct FtsIndexInfo {
+    pub name: String,
+    pub doc_count: u64,
+}
+
+/// 图。
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(default)]
+pub struct GraphInfo {
+    pub name: String,
+    pub vertex_count: u64,
+    pub edge_count: u64,
+}
+
+/// MQ topic。
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(default)]
+pub struct TopicInfo {
+    pub name: String,
+    /// 最大保留消息数（0 表示不限）。
+    pub max_len: u64,
+    /// 当前消息数。
+    pub len: u64,
+}
+
+fn describe_cmd(txn_id: Option<u64>) -> serde_json::Value {
+    let params = match txn_id {
+        Some(id) => serde_json::json!({ "txn_id": id }),
+        None => serde_json::json!({}),
+    };
+    serde_json::json!({"module": "schema", "action": "describe", "params": params})
+}
+
+fn parse_schema(data: &serde_json::Value) -> Result<Schema, TalonError> {
+    Schema::deserialize(data).map_err(|e| TalonError(format!("invalid schema response: {e}")))
+}
+
+impl Talon {
+    /// 描述数据库结构：SQL 表（列、索引）、向量索引、FTS 索引、图与 MQ topic。
+    pub fn schema(&self) -> Result<Schema, TalonError> {
+        self.schema_in(None)
+    }
+
+    /// 在 `txn_id` 指定的事务内（`None` 为事务外）描述数据库结构。
+    pub(crate) fn schema_in(&self, txn_id: Option<u64>) -> Result<Schema, TalonError> {
+        let resp = self.exec_cmd_json(&describe_cmd(txn_id))?;
+        let data = embedded_response_data(&resp)
+            .map_err(|e| TalonError(format!("schema: {}", e.0)))?
+            .ok_or_else(|| TalonError(format!("schema response missing data: {resp}")))?;
+        parse_schema(data)
+    }
+}
+
+impl TalonRemoteClient {
+    /// Describe the remote database: SQL tables with columns and indexes,
+    /// vector indexes, FTS indexes, graphs and MQ topics.
+    pub fn schema(&self) -> Result<Schema, TalonError> {
+        self.schema_in(None)
+    }
+
+    /// Describe the database as seen by transaction `txn_id` (`None` for
+    /// outside any transaction).
+    pub(crate) fn schema_in(&self, txn_id: Option<u64>) -> Result<Schema, TalonError> {
+        let resp = self.exec_cmd_json(&describe_cmd(txn_id))?;
+        let data = remote_response_data(&resp)?.ok_or_else(|| {
+            remote_error(
+                TalonRemoteErrorKind::Protocol,
+                format!("schema response missing data: {resp}"),
+            )
+        })?;
+        parse_schema(data)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn full_response_is_parsed() {
+        let data = serde_json::json!({
+            "tables": [{
+                "name": "users",
+                "columns": [
+                    {"name": "id", "type": "INT", "nullable": false, "primary_key": true},
+                    {"name": "email", "type": "TEXT", "default": "''"}
+                ],
+                "indexes": [{"name": "idx_email", "columns": ["email"], "unique": true}]
+            }],
+            "vector_indexes": [{"name": "emb", "dim": 384, "metric": "cosine", "count": 10}],
+            "fts_indexes": [{"name": "docs", "doc_count": 3}],
+            "graphs": [{"name": "social", "vertex_count": 5, "edge_count": 7}],
+            "topics": [{"name": "events", "max_len": 1000, "len": 2}]
+        });
+        let schema = parse_schema(&data).unwrap();
+        let users = schema.table("users").unwrap();
+        assert_eq!(users.primary_key(), vec!["id"]);
+        let email = users.column("email").unwrap();
+        assert_eq!(email.decl_type, "TEXT");
+        assert!(email.nullable);
+        assert_eq!(email.default.as_deref(), Some("''"));
+        assert!(users.indexes[0].unique);
+        assert_eq!(schema.vector_indexes[0].dim, 384);
+        assert_eq!(schema.fts_indexes[0].doc_count, 3);
+        assert_eq!(schema.graphs[0].edge_count, 7);
+        assert_eq!(schema.topics[0].max_len, 1000);
+    }
+
+    #[test]
+    fn missing_sections_default_to_empty() {
+        let schema = parse_schema(&serde_json::json!({"tables": []})).unwrap();
+        assert_eq!(schema, Schema::default());
+        assert!(parse_schema(&serde_json::json!({"tables": 1})).is_err());
+    }
+
+    #[test]
+    fn embedded_response_requires_ok_true() {
+        let ok = serde_json::json!({"ok": true, "data": {"tables": []}});
+        assert!(embedded_response_data(&ok).unwrap().is_some());
+        let no_data = serde_json::json!({"ok": true});
+        assert!(embedded_response_data(&no_data).unwrap().is_none());
+        let missing_ok = serde_json::json!({"data": {"tables": []}});
+        assert_eq!(
+            embedded_response_data(&missing_ok).unwrap_err().0,
+            "unknown"
+        );
+        let failed = serde_json::json!({"ok": false, "error": "no such module"});
+        assert_eq!(
+            embedded_response_data(&failed).unwrap_err().0,
+            "no such module"
+        );
+    }
+}
@@ -0,0 +1,471 @@
+/*
+ * Copyright (c) 2026 Talon Contributors
+ * Author: dark.lijin@gmail.com
+ * Licensed under the Talon Community Dual License Agreement.
+ * See the LICENSE file in the project root for full license information.
+ */
+//! 预编译语句 — 引擎侧缓存执行计划，热路径只传参数。
+//!
+//! 每个句柄维护一个按 SQL 文本索引的 LRU 语句缓存：重复 `prepare` 同一 SQL
+//! 直接复用已编译的语句。被淘汰的语句在最后一个 [`Statement`] 释放后才关闭。
+//!
+//! SQL 中的 `:name` / `@name` / `$N` 占位符在 prepare 时改写为 `?`，执行时按
+//! 占位符顺序排列绑定的参数（规则见 [`Params`]）。
+
+use std::collections::VecDeque;
+use std::ffi::CString;
+use std::ptr;
+use std::slice;
+use std::sync::{Arc, Mutex};
+
+use crate::{
+    decode_exec_result_bin, decode_query_result_bin, encode_params, ffi_error, raw_ffi,
+    remote_error, remote_exec_result, remote_query_result, remote_response_data, Bindings,
+    ExecResult, Params, QueryResult, Talon, TalonError, TalonRemoteClient, TalonRemoteErrorKind,
+    Value,
+};
+
+/// 语句缓存默认容量。
+pub(crate) const DEFAULT_STMT_CACHE_CAPACITY: usize = 64;
+
+/// 按 SQL 文本索引的 LRU 缓存（队首为最近使用）。
+#[derive(Debug)]
+pub(crate) struct StmtCache<T> {
+    capacity: usize,
+    entries: VecDeque<(String, Arc<T>)>,
+}
+
+impl<T> StmtCache<T> {
+    pub(crate) fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            entries: VecDeque::new(),
+        }
+    }
+
+    fn get(&mut self, sql: &str) -> Option<Arc<T>> {
+        let pos = self.entries.iter().position(|(s, _)| s == sql)?;
+        let entry = self.entries.remove(pos)?;
+        let stmt = Arc::clone(&entry.1);
+        self.entries.push_front(entry);
+        Some(stmt)
+    }
+
+    /// 插入新语句，返回被淘汰的条目。
+    fn insert(&mut self, sql: &str, stmt: Arc<T>) -> Vec<Arc<T>> {
+        self.entries.push_front((sql.to_string(), stmt));
+        self.shrink()
+    }
+
+    fn set_capacity(&mut self, capacity: usize) -> Vec<Arc<T>> {
+        self.capacity = capacity;
+        self.shrink()
+    }
+
+    pub(crate) fn clear(&mut self) -> Vec<Arc<T>> {
+        self.entries.drain(..).map(|(_, stmt)| stmt).collect()
+    }
+
+    fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    fn shrink(&mut self) -> Vec<Arc<T>> {
+        let keep = self.capacity.min(self.entries.len());
+        self.entries.drain(keep..).map(|(_, stmt)| stmt).collect()
+    }
+}
+
+/// 引擎侧语句 id；最后一个引用释放时关闭。
+pub(crate) struct PreparedId {
+    handle: *mut raw_ffi::TalonHandle,
+    id: u64,
+}
+
+// SAFETY: 与 Talon 相同，TalonHandle 由引擎内部同步。
+unsafe impl Send for PreparedId {}
+unsafe impl Sync for PreparedId {}
+
+impl Drop for PreparedId {
+    fn drop(&mut self) {
+        unsafe { raw_ffi::talon_stmt_close(self.handle, self.id) };
+    }
+}
+
+/// 嵌入式句柄的语句缓存类型。
+pub(crate) type EmbeddedStmtCache = Mutex<StmtCache<PreparedId>>;
+
+/// 预编译语句（`Talon::prepare`）。
+///
+/// `bind` 依次追加位置参数，`bind_named` 绑定命名参数，`query` / `execute`
+/// 执行后清空绑定，可反复使用。
+pub struct Statement<'a> {
+    db: &'a Talon,
+    sql: String,
+    prepared: Arc<PreparedId>,
+    bindings: Bindings,
+}
+
+impl Statement<'_> {
+    /// 语句的 SQL 文本。
+    pub fn sql(&self) -> &str {
+        &self.sql
+    }
+
+    /// 绑定下一个位置参数（`?`，或 `$N` 中的第 N 个）。
+    pub fn bind(&mut self, value: impl Into<Value>) -> &mut Self {
+        self.bindings.push(value.into());
+        self
+    }
+
+    /// 绑定命名参数 `:name` / `@name`；`name` 可带或不带前缀。
+    pub fn bind_named(&mut self, name: &str, value: impl Into<Value>) -> &mut Self {
+        self.bindings.insert(name, value.into());
+        self
+    }
+
+    /// 一次追加多个位置参数或命名参数。
+    pub fn bind_params(&mut self, params: impl Into<Params>) -> &mut Self {
+        self.bindings.extend(params.into());
+        self
+    }
+
+    /// 清空已绑定的参数。
+    pub fn clear_bindings(&mut self) -> &mut Self {
+        self.bindings.clear();
+        self
+    }
+
+    /// 以已绑定参数执行查询。
+    pub fn query(&mut self) -> Result<QueryResult, TalonError> {
+        self.run("stmt_query", decode_query_result_bin)
+    }
+
+    /// 以已绑定参数执行写语句，只返回影响行数与自增 ID。
+    pub fn execute(&mut self) -> Result<ExecResult, TalonError> {
+        self.run("stmt_execute", decode_exec_result_bin)
+    }
+
+    fn run<T: Default>(
+        &mut self,
+        op: &str,
+        decode: fn(&[u8]) -> Result<T, TalonError>,
+    ) -> Result<T, TalonError> {
+        let params_bin = encode_params(&self.bindings.take(&self.sql)?);
+        let mut out_data: *mut u8 = ptr::null_mut();
+        let mut out_len: usize = 0;
+        let rc = unsafe {
+            raw_ffi::talon_stmt_query_bin(
+                self.db.handle,
+                self.prepared.id,
+                params_bin.as_ptr(),
+                params_bin.len(),
+                &mut out_data,
+                &mut out_len,
+            )
+        };
+        if rc != 0 {
+            return Err(ffi_error(op));
+        }
+        if out_data.is_null() || out_len == 0 {
+            return Ok(T::default());
+        }
+        let data = unsafe { slice::from_raw_parts(out_data, out_len) };
+        let result = decode(data);
+        unsafe { raw_ffi::talon_free_bytes(out_data, out_len) };
+        result
+    }
+}
+
+impl Talon {
+    /// 预编译 SQL；同一 SQL 命中语句缓存时直接复用。
+    pub fn prepare(&self, sql: &str) -> Result<Statement<'_>, TalonError> {
+        let mut cache = self
+            .stmt_cache
+            .lock()
+            .map_err(|_| TalonError("statement cache lock poisoned".into()))?;
+        let (exec_sql, bindings) = Bindings::prepare(sql);
+        let prepared = match cache.get(sql) {
+            Some(prepared) => prepared,
+            None => {
+                let c_sql = CString::new(&*exec_sql)?;
+                let mut id: u64 = 0;
+                let rc = unsafe { raw_ffi::talon_prepare(self.handle, c_sql.as_ptr(), &mut id) };
+                if rc != 0 {
+                    return Err(ffi_error("prepare"));
+                }
+                let prepared = Arc::new(PreparedId {
+                    handle: self.handle,
+                    id,
+                });
+                cache.insert(sql, Arc::clone(&prepared));
+                prepared
+            }
+        };
+        Ok(Statement {
+            db: self,
+            sql: sql.to_string(),
+            prepared,
+            bindings,
+        })
+    }
+
+    /// 调整语句缓存容量（默认 64）；为 0 时不缓存。
+    pub fn set_statement_cache_capacity(&self, capacity: usize) {
+        if let Ok(mut cache) = self.stmt_cache.lock() {
+            cache.set_capacity(capacity);
+        }
+    }
+
+    /// 当前缓存的语句数。
+    pub fn statement_cache_len(&self) -> usize {
+        self.stmt_cache.lock().map(|c| c.len()).unwrap_or(0)
+    }
+}
+
+/// Remote statement cache: evicted ids are closed on the server once no
+/// `RemoteStatement` references them any more.
+#[derive(Debug)]
+pub(crate) struct RemoteStmtCache {
+    cache: StmtCache<u64>,
+    retired: Vec<Arc<u64>>,
+    /// 已无人引用、等待在服务端关闭的语句 id（关闭失败的会放回此处重试）。
+    pending_close: Vec<u64>,
+}
+
+impl RemoteStmtCache {
+    pub(crate) fn new(capacity: usize) -> Self {
+        Self {
+            cache: StmtCache::new(capacity),
+            retired: Vec::new(),
+            pending_close: Vec::new(),
+        }
+    }
+
+    fn retire(&mut self, evicted: Vec<Arc<u64>>) {
+        self.retired.extend(evicted);
+    }
+
+    /// 缓存新 prepare 的语句 id；并发 prepare 同一 SQL 时沿用先缓存的 id，
+    /// 本次的 id 直接等待关闭。
+    fn insert_prepared(&mut self, sql: &str, id: u64) -> Arc<u64> {
+        if let Some(existing) = self.cache.get(sql) {
+            self.pending_close.push(id);
+            return existing;
+        }
+        let id = Arc::new(id);
+        let evicted = self.cache.insert(sql, Arc::clone(&id));
+        self.retire(evicted);
+        id
+    }
+
+    /// 取出已无人引用、可在服务端关闭的语句 id。
+    fn take_closable(&mut self) -> Vec<u64> {
+        let mut closable = std::mem::take(&mut self.pending_close);
+        self.retired.retain(|id| {
+            if Arc::strong_count(id) == 1 {
+                closable.push(**id);
+                false
+            } else {
+                true
+            }
+        });
+        closable
+    }
+}
+
+/// Remote prepared statement backed by a server-side statement id.
+///
+/// Unlike `run_sql_param`, which inlines parameters into the SQL text, bound
+/// values are sent as-is: `sql/stmt_exec` takes `params` as a JSON array of
+/// serde-tagged [`Value`]s (e.g. `{"Integer":1}`) and binds them on the
+/// server (talon-server v0.2.0+).
+pub struct RemoteStatement<'a> {
+    client: &'a TalonRemoteClient,
+    sql: String,
+    id: Arc<u64>,
+    bindings: Bindings,
+}
+
+impl RemoteStatement<'_> {
+    /// SQL text of this statement.
+    pub fn sql(&self) -> &str {
+        &self.sql
+    }
+
+    /// Bind the next positional parameter (`?`, or the N-th for `$N`).
+    pub fn bind(&mut self, value: impl Into<Value>) -> &mut Self {
+        self.bindings.push(value.into());
+        self
+    }
+
+    /// Bind the named parameter `:name` / `@name`; the prefix is optional.
+    pub fn bind_named(&mut self, name: &str, value: impl Into<Value>) -> &mut Self {
+        self.bindings.insert(name, value.into());
+        self
+    }
+
+    /// Append several positional or named parameters at once.
+    pub fn bind_params(&mut self, params: impl Into<Params>) -> &mut Self {
+        self.bindings.extend(params.into());
+        self
+    }
+
+    /// Drop all bound parameters.
+    pub fn clear_bindings(&mut self) -> &mut Self {
+        self.bindings.clear();
+        self
+    }
+
+    /// Run the statement with the bound parameters.
+    pub fn query(&mut self) -> Result<QueryResult, TalonError> {
+        let resp = self.run()?;
+        remote_query_result(stmt_exec_data(&resp)?)
+    }
+
+    /// Run a write statement and return only `rows_affected` and
+    /// `last_insert_id`; any rows in the reply are not decoded.
+    pub fn execute(&mut self) -> Result<ExecResult, TalonError> {
+        let resp = self.run()?;
+        Ok(remote_exec_result(stmt_exec_data(&resp)?))
+    }
+
+    fn run(&mut self) -> Result<serde_json::Value, TalonError> {
+        let params = self.bindings.take(&self.sql)?;
+        self.client.exec_cmd_json(&serde_json::json!({
+            "module": "sql",
+            "action": "stmt_exec",
+            "params": { "stmt_id": *self.id, "params": params }
+        }))
+    }
+}
+
+fn stmt_exec_data(resp: &serde_json::Value) -> Result<&serde_json::Value, TalonError> {
+    remote_response_data(resp)?.ok_or_else(|| {
+        remote_error(
+            TalonRemoteErrorKind::Protocol,
+            format!("SQL stmt_exec response missing data: {resp}"),
+        )
+    })
+}
+
+impl Drop for RemoteStatement<'_> {
+    fn drop(&mut self) {
+        // 释放本语句的引用后，已淘汰且无人引用的 id 即可关闭；失败的留待下次重试。
+        self.id = Arc::new(*self.id);
+        let _ = self.client.close_retired_statements();
+    }
+}
+
+impl TalonRemoteClient {
+    /// Prepare SQL on the server; repeated SQL reuses the cached statement id.
+    pub fn prepare(&self, sql: &str) -> Result<RemoteStatement<'_>, TalonError> {
+        let (exec_sql, bindings) = Bindings::prepare(sql);
+        let cached = self.stmt_cache()?.cache.get(sql);
+        let id = match cached {
+            Some(id) => id,
+            None => {
+                let resp = self.exec_cmd_json(&serde_json::json!({
+                    "module": "sql",
+                    "action": "prepare",
+                    "params": { "sql": exec_sql }
+                }))?;
+                let id = remote_response_data(&resp)?
+                    .and_then(|d| d.get("stmt_id"))
+                    .and_then(|v| v.as_u64())
+                    .ok_or_else(|| {
+                        remote_error(
+                            TalonRemoteErrorKind::Protocol,
+                            format!("SQL prepare response missing stmt_id: {resp}"),
+                        )
+                    })?;
+                self.stmt_cache()?.insert_prepared(sql, id)
+            }
+        };
+        let stmt = RemoteStatement {
+            client: self,
+            sql: sql.to_string(),
+            id,
+            bindings,
+        };
+        // Closing older evicted ids is best-effort; failures are retried later.
+        let _ = self.close_retired_statements();
+        Ok(stmt)
+    }
+
+    /// Resize the remote statement cache (default 64); 0 disables caching.
+    pub fn set_statement_cache_capacity(&self, capacity: usize) {
+        if let Ok(mut cache) = self.stmt_cache() {
+            let evicted = cache.cache.set_capacity(capacity);
+            cache.retire(evicted);
+        }
+        let _ = self.close_retired_statements();
+    }
+
+    /// Close evicted statement ids nobody references any more. Ids whose
+    /// close fails stay queued and are retried on the next call.
+    fn close_retired_statements(&self) -> Result<(), TalonError> {
+        let closable = self.stmt_cache()?.take_closable();
+        for (i, &id) in closable.iter().enumerate() {
+            let closed = self.exec_cmd(&serde_json::json!({
+                "module": "sql",
+                "action": "stmt_close",
+                "params": { "stmt_id": id }
+            }));
+            if let Err(e) = closed {
+                self.stmt_cache()?
+                    .pending_close
+                    .extend_from_slice(&closable[i..]);
+                return Err(e);
+            }
+        }
+        Ok(())
+    }
+
+    fn stmt_cache(&self) -> Result<std::sync::MutexGuard<'_, RemoteStmtCache>, TalonError> {
+        self.stmt_cache
+            .lock()
+            .map_err(|_| remote_error(TalonRemoteErrorKind::Io, "statement cache lock poisoned"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn stmt_cache_evicts_least_recently_used() {
+        let mut cache = StmtCache::new(2);
+        assert!(cache.insert("a", Arc::new(1)).is_empty());
+        assert!(cache.insert("b", Arc::new(2)).is_empty());
+        assert_eq!(cache.get("a").as_deref(), Some(&1));
+        let evicted = cache.insert("c", Arc::new(3));
+        assert_eq!(evicted.iter().map(|s| **s).collect::<Vec<_>>(), vec![2]);
+        assert!(cache.get("b").is_none());
+        assert_eq!(cache.set_capacity(0).len(), 2);
+        assert_eq!(cache.len(), 0);
+    }
+
+    #[test]
+    fn remote_cache_closes_retired_ids_once_unused() {
+        let mut cache = RemoteStmtCache::new(1);
+        let held = Arc::new(1);
+        cache.cache.insert("a", Arc::clone(&held));
+        let evicted = cache.cache.insert("b", Arc::new(2));
+        cache.retire(evicted);
+        assert!(cache.take_closable().is_empty());
+        drop(held);
+        assert_eq!(cache.take_closable(), vec![1]);
+    }
+
+    #[test]
+    fn concurrent_prepare_of_same_sql_keeps_one_id() {
+        let mut cache = RemoteStmtCache::new(4);
+        let first = cache.insert_prepared("a", 1);
+        // 另一线程在缓存未命中后也完成了 prepare。
+        let second = cache.insert_prepared("a", 2);
+        assert_eq!((*first, *second), (1, 1));
+        assert_eq!(cache.cache.len(), 1);
+        assert_eq!(cache.take_closable(), vec![2]);
+    }
+}
@@ -0,0 +1,142 @@
+/*
+ * Copyright (c) 2026 Talon Contributors
+ * Author: dark.lijin@gmail.com
+ * Licensed under the Talon Community Dual License Agreement.
+ * See the LICENSE file in the project root for full license information.
+ */
+//! 单元测试共用的内存 [`KvBackend`] 实现。
+
+use std::collections::BTreeMap;
+use std::sync::atomic::{AtomicBool, AtomicU32, Ordering};
+use std::sync::Mutex;
+use std::time::Duration;
+
+use crate::{KvBackend, KvScanPage, TalonError};
+
+type KvMap = BTreeMap<Vec<u8>, Vec<u8>>;
+type IncrHook = Box<dyn FnOnce(&mut KvMap) + Send>;
+
+/// 内存 KV 后端，用于验证锁、限流、命名空间等协议（TTL 不生效）。
+#[derive(Default)]
+pub(crate) struct MemKv {
+    map: Mutex<KvMap>,
+    cas_errors: AtomicU32,
+    incr_hook: Mutex<Option<IncrHook>>,
+    ignore_deletes: AtomicBool,
+}
+
+impl MemKv {
+    /// 让接下来的 `n` 次 compare-and-swap 返回错误，模拟网络抖动。
+    pub(crate) fn fail_next_cas(&self, n: u32) {
+        self.cas_errors.store(n, Ordering::SeqCst);
+    }
+
+    /// 在下一次 `incr_by` 之前修改数据，模拟两次调用之间的并发写入。
+    pub(crate) fn before_next_incr(&self, hook: impl FnOnce(&mut KvMap) + Send + 'static) {
+        *self.incr_hook.lock().unwrap() = Some(Box::new(hook));
+    }
+
+    /// 让之后的 `del_many` 不再删除任何 key，模拟后端删不掉已扫描到的 key。
+    pub(crate) fn ignore_deletes(&self) {
+        self.ignore_deletes.store(true, Ordering::SeqCst);
+    }
+}
+
+impl KvBackend for MemKv {
+    fn kv_get(&self, key: &[u8]) -> Result<Option<Vec<u8>>, TalonError> {
+        Ok(self.map.lock().unwrap().get(key).cloned())
+    }
+    fn kv_set(&self, key: &[u8], value: &[u8], _: Option<u64>) -> Result<(), TalonError> {
+        self.map
+            .lock()
+            .unwrap()
+            .insert(key.to_vec(), value.to_vec());
+        Ok(())
+    }
+    fn kv_del(&self, key: &[u8]) -> Result<(), TalonError> {
+        self.map.lock().unwrap().remove(key);
+        Ok(())
+    }
+    fn kv_incr_by(&self, key: &[u8], delta: i64) -> Result<i64, TalonError> {
+        let mut map = self.map.lock().unwrap();
+        if let Some(hook) = self.incr_hook.lock().unwrap().take() {
+            hook(&mut map);
+        }
+        let cur = map
+            .get(key)
+            .map(|v| String::from_utf8_lossy(v).parse::<i64>().unwrap())
+            .unwrap_or(0);
+        map.insert(key.to_vec(), (cur + delta).to_string().into_bytes());
+        Ok(cur + delta)
+    }
+    fn kv_set_nx(&self, key: &[u8], value: &[u8], _: Option<u64>) -> Result<bool, TalonError> {
+        let mut map = self.map.lock().unwrap();
+        if map.contains_key(key) {
+            return Ok(false);
+        }
+        map.insert(key.to_vec(), value.to_vec());
+        Ok(true)
+    }
+    fn kv_compare_and_swap(
+        &self,
+        key: &[u8],
+        expected: Option<&[u8]>,
+        new: Option<&[u8]>,
+        _: Option<u64>,
+    ) -> Result<bool, TalonError> {
+        let injected = self
+            .cas_errors
+            .fetch_update(Ordering::SeqCst, Ordering::SeqCst, |n| n.checked_sub(1));
+        if injected.is_ok() {
+            return Err(TalonError("injected cas failure".into()));
+        }
+        let mut map = self.map.lock().unwrap();
+        if map.get(key).map(Vec::as_slice) != expected {
+            return Ok(false);
+        }
+        match new {
+            Some(v) => map.insert(key.to_vec(), v.to_vec()),
+            None => map.remove(key),
+        };
+        Ok(true)
+    }
+    fn kv_expire(&self, key: &[u8], _: Duration) -> Result<bool, TalonError> {
+        Ok(self.map.lock().unwrap().contains_key(key))
+    }
+    fn kv_get_many(&self, keys: &[&[u8]]) -> Result<Vec<Option<Vec<u8>>>, TalonError> {
+        let map = self.map.lock().unwrap();
+        Ok(keys.iter().map(|k| map.get(*k).cloned()).collect())
+    }
+    fn kv_del_many(&self, keys: &[&[u8]]) -> Result<u64, TalonError> {
+        if self.ignore_deletes.load(Ordering::SeqCst) {
+            return Ok(0);
+        }
+        let mut map = self.map.lock().unwrap();
+        Ok(keys.iter().filter(|k| map.remove(**k).is_some()).count() as u64)
+    }
+    fn kv_scan_page(
+        &self,
+        start: &[u8],
+        end: Option<&[u8]>,
+        cursor: Option<&[u8]>,
+        limit: usize,
+    ) -> Result<KvScanPage, TalonError> {
+        let map = self.map.lock().unwrap();
+        let mut entries: Vec<(Vec<u8>, Vec<u8>)> = map
+            .range(start.to_vec()..)
+            .filter(|(k, _)| cursor.is_none_or(|c| k.as_slice() > c))
+            .take_while(|(k, _)| end.is_none_or(|e| k.as_slice() < e))
+            .take(limit + 1)
+            .map(|(k, v)| (k.clone(), v.clone()))
+            .collect();
+        let more = entries.len() > limit;
+        entries.truncate(limit);
+        let next_cursor = more
+            .then(|| entries.last().map(|(k, _)| k.clone()))
+            .flatten();
+        Ok(KvScanPage {
+            entries,
+            next_cursor,
+        })
+    }
+}
@@ -0,0 +1,573 @@
+/*
+ * Copyright (c) 2026 Talon Contributors
+ * Author: dark.lijin@gmail.com
+ * Licensed under the Talon Community Dual License Agreement.
+ * See the LICENSE file in the project root for full license information.
+ */
+//! 事务 — `begin` 返回的守卫在 drop 时自动回滚。
+//!
+//! 事务内的 SQL 与 KV 操作一起提交；`transaction(|tx| ...)` 在序列化冲突时
+//! 自动回滚并重试闭包。冲突由引擎返回码（嵌入式）或服务端 `code: "txn_conflict"`
+//! （远程）标识并记录在事务守卫上（[`Transaction::conflicted`]），不解析错误文本；
+//! 其余错误一律不重试。
+
+use std::ffi::{c_int, CString};
+use std::ptr;
+use std::slice;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::thread;
+use std::time::Duration;
+
+use crate::{
+    decode_rows_bin, encode_params, ffi_error, inline_sql_params, kv_cmd, raw_ffi, remote_error,
+    remote_kv_value, remote_query_result, remote_response_data, ttl_secs_arg, SqlBackend, Talon,
+    TalonError, TalonRemoteClient, TalonRemoteErrorKind, Value,
+};
+
+/// `transaction` 遇到冲突时的最大重试次数。
+const TXN_MAX_RETRIES: u32 = 8;
+
+/// 事务 FFI 返回码：序列化冲突。
+const TALON_TXN_CONFLICT: c_int = 2;
+
+/// 服务端事务冲突错误码。
+const TXN_CONFLICT_CODE: &str = "txn_conflict";
+
+/// 冲突重试退避：5ms 起指数增长，上限 160ms。
+fn conflict_backoff(attempt: u32) -> Duration {
+    Duration::from_millis(5 << attempt.min(5))
+}
+
+/// 可由 [`run_with_retry`] 驱动的事务守卫。
+trait RetryTxn {
+    /// 提交但保留守卫，以便提交失败后读取冲突标记。
+    fn commit_in_place(&mut self) -> Result<(), TalonError>;
+
+    /// 是否有操作收到过冲突返回码 / 冲突错误码。
+    fn conflicted(&self) -> bool;
+}
+
+/// 执行 `body` 并提交；失败且事务记录了冲突时回滚并重试。
+fn run_with_retry<X: RetryTxn, T>(
+    mut begin: impl FnMut() -> Result<X, TalonError>,
+    mut body: impl FnMut(&X) -> Result<T, TalonError>,
+) -> Result<T, TalonError> {
+    let mut attempt = 0;
+    loop {
+        let mut tx = begin()?;
+        let result = body(&tx).and_then(|value| tx.commit_in_place().map(|()| value));
+        let retry = result.is_err() && tx.conflicted() && attempt < TXN_MAX_RETRIES;
+        // 未提交的事务在 drop 时回滚，需在退避前释放。
+        drop(tx);
+        if !retry {
+            return result;
+        }
+        thread::sleep(conflict_backoff(attempt));
+        attempt += 1;
+    }
+}
+
+/// 嵌入式事务（`Talon::begin`）；未提交即 drop 时自动回滚。
+pub struct Transaction<'a> {
+    db: &'a Talon,
+    id: u64,
+    finished: bool,
+    conflict: AtomicBool,
+}
+
+impl Transaction<'_> {
+    /// 事务 id。
+    pub fn id(&self) -> u64 {
+        self.id
+    }
+
+    /// 本事务的操作是否遇到过序列化冲突（引擎冲突返回码）。
+    ///
+    /// 冲突后事务无法提交，回滚并重试整个事务通常可以成功。
+    pub fn conflicted(&self) -> bool {
+        self.conflict.load(Ordering::Relaxed)
+    }
+
+    /// 事务 FFI 失败：冲突返回码记录到守卫上，并在错误文本中注明。
+    fn ffi_error(&self, op: &str, rc: c_int) -> TalonError {
+        let err = ffi_error(op);
+        if rc == TALON_TXN_CONFLICT {
+            self.conflict.store(true, Ordering::Relaxed);
+            TalonError(format!("txn conflict: {}", err.0))
+        } else {
+            err
+        }
+    }
+
+    /// 在事务内执行 SQL。
+    pub fn run_sql(&self, sql: &str) -> Result<Vec<Vec<Value>>, TalonError> {
+        self.run_sql_param(sql, &[])
+    }
+
+    /// 在事务内执行参数化 SQL。
+    pub fn run_sql_param(
+        &self,
+        sql: &str,
+        params: &[Value],
+    ) -> Result<Vec<Vec<Value>>, TalonError> {
+        let c_sql = CString::new(sql)?;
+        let params_bin = encode_params(params);
+        let mut out_data: *mut u8 = ptr::null_mut();
+        let mut out_len: usize = 0;
+        let rc = unsafe {
+            raw_ffi::talon_txn_run_sql_param_bin(
+                self.db.handle,
+                self.id,
+                c_sql.as_ptr(),
+                params_bin.as_ptr(),
+                params_bin.len(),
+                &mut out_data,
+                &mut out_len,
+            )
+        };
+        if rc != 0 {
+            return Err(self.ffi_error("txn_run_sql", rc));
+        }
+        if out_data.is_null() || out_len == 0 {
+            return Ok(vec![]);
+        }
+        let data = unsafe { slice::from_raw_parts(out_data, out_len) };
+        let result = decode_rows_bin(data);
+        unsafe { raw_ffi::talon_free_bytes(out_data, out_len) };
+        result
+    }
+
+    /// 在事务内读取 key（可见本事务未提交的写入）。
+    pub fn kv_get(&self, key: &[u8]) -> Result<Option<Vec<u8>>, TalonError> {
+        let mut out_ptr: *mut u8 = ptr::null_mut();
+        let mut out_len: usize = 0;
+        let rc = unsafe {
+            raw_ffi::talon_txn_kv_get(
+                self.db.handle,
+                self.id,
+                key.as_ptr(),
+                key.len(),
+                &mut out_ptr,
+                &mut out_len,
+            )
+        };
+        if rc != 0 {
+            return Err(self.ffi_error("txn_kv_get", rc));
+        }
+        if out_ptr.is_null() {
+            return Ok(None);
+        }
+        let data = unsafe { slice::from_raw_parts(out_ptr, out_len).to_vec() };
+        unsafe { raw_ffi::talon_free_bytes(out_ptr, out_len) };
+        Ok(Some(data))
+    }
+
+    /// 在事务内写入 key-value，可选 TTL。
+    pub fn kv_set(
+        &self,
+        key: &[u8],
+        value: &[u8],
+        ttl_secs: Option<u64>,
+    ) -> Result<(), TalonError> {
+        let ttl_secs = ttl_secs_arg(ttl_secs)?;
+        let rc = unsafe {
+            raw_ffi::talon_txn_kv_set(
+                self.db.handle,
+                self.id,
+                key.as_ptr(),
+                key.len(),
+                value.as_ptr(),
+                value.len(),
+                ttl_secs,
+            )
+        };
+        if rc != 0 {
+            return Err(self.ffi_error("txn_kv_set", rc));
+        }
+        Ok(())
+    }
+
+    /// 在事务内删除 key。
+    pub fn kv_del(&self, key: &[u8]) -> Result<(), TalonError> {
+        let rc =
+            unsafe { raw_ffi::talon_txn_kv_del(self.db.handle, self.id, key.as_ptr(), key.len()) };
+        if rc != 0 {
+            return Err(self.ffi_error("txn_kv_del", rc));
+        }
+        Ok(())
+    }
+
+    /// 提交事务；提交失败时事务随守卫 drop 回滚。
+    ///
+    /// 因冲突失败时错误以 `txn conflict:` 开头；需要自动重试请使用
+    /// [`Talon::transaction`]。
+    pub fn commit(mut self) -> Result<(), TalonError> {
+        self.commit_in_place()
+    }
+
+    /// 回滚事务。
+    pub fn rollback(mut self) -> Result<(), TalonError> {
+        self.finished = true;
+        self.rollback_inner()
+    }
+
+    fn rollback_inner(&self) -> Result<(), TalonError> {
+        let rc = unsafe { raw_ffi::talon_txn_rollback(self.db.handle, self.id) };
+        if rc != 0 {
+            return Err(ffi_error("txn_rollback"));
+        }
+        Ok(())
+    }
+}
+
+impl RetryTxn for Transaction<'_> {
+    fn commit_in_place(&mut self) -> Result<(), TalonError> {
+        let rc = unsafe { raw_ffi::talon_txn_commit(self.db.handle, self.id) };
+        if rc != 0 {
+            return Err(self.ffi_error("txn_commit", rc));
+        }
+        self.finished = true;
+        Ok(())
+    }
+
+    fn conflicted(&self) -> bool {
+        Transaction::conflicted(self)
+    }
+}
+
+impl Drop for Transaction<'_> {
+    fn drop(&mut self) {
+        if !self.finished {
+            let _ = self.rollback_inner();
+        }
+    }
+}
+
+impl SqlBackend for Transaction<'_> {
+    fn sql_run(&self, sql: &str, params: &[Value]) -> Result<Vec<Vec<Value>>, TalonError> {
+        self.run_sql_param(sql, params)
+    }
+
+    /// 已在事务中，直接执行 `f`。
+    fn sql_transaction(
+        &self,
+        f: &mut dyn FnMut(&dyn SqlBackend) -> Result<(), TalonError>,
+    ) -> Result<(), TalonError> {
+        f(self)
+    }
+
+    /// 在本事务内自省，可见本事务未提交的建表。
+    fn sql_table_exists(&self, table: &str) -> Result<bool, TalonError> {
+        Ok(self.db.schema_in(Some(self.id))?.table(table).is_some())
+    }
+}
+
+impl Talon {
+    /// 开启事务。
+    pub fn begin(&self) -> Result<Transaction<'_>, TalonError> {
+        let mut id: u64 = 0;
+        let rc = unsafe { raw_ffi::talon_txn_begin(self.handle, &mut id) };
+        if rc != 0 {
+            return Err(ffi_error("txn_begin"));
+        }
+        Ok(Transaction {
+            db: self,
+            id,
+            finished: false,
+            conflict: AtomicBool::new(false),
+        })
+    }
+
+    /// 在事务中执行闭包：成功则提交，出错则回滚；序列化冲突时自动重试。
+    ///
+    /// 闭包可能被执行多次，不应包含事务外的副作用。
+    pub fn transaction<T>(
+        &self,
+        f: impl FnMut(&Transaction<'_>) -> Result<T, TalonError>,
+    ) -> Result<T, TalonError> {
+        run_with_retry(|| self.begin(), f)
+    }
+}
+
+/// Remote transaction (`TalonRemoteClient::begin`); rolled back on drop
+/// unless committed.
+pub struct RemoteTransaction<'a> {
+    client: &'a TalonRemoteClient,
+    id: u64,
+    finished: bool,
+    conflict: AtomicBool,
+}
+
+impl RemoteTransaction<'_> {
+    /// Server-side transaction id.
+    pub fn id(&self) -> u64 {
+        self.id
+    }
+
+    /// Whether any operation in this transaction was rejected with the
+    /// server's `"code":"txn_conflict"`. A conflicted transaction cannot
+    /// commit; retrying the whole transaction usually succeeds.
+    pub fn conflicted(&self) -> bool {
+        self.conflict.load(Ordering::Relaxed)
+    }
+
+    /// Send a command for this transaction, recording a conflict code
+    /// before the caller checks the reply.
+    fn exec_json(&self, cmd: &serde_json::Value) -> Result<serde_json::Value, TalonError> {
+        let resp = self.client.exec_cmd_json(cmd)?;
+        if resp.get("code").and_then(|v| v.as_str()) == Some(TXN_CONFLICT_CODE) {
+            self.conflict.store(true, Ordering::Relaxed);
+        }
+        Ok(resp)
+    }
+
+    fn exec(&self, cmd: &serde_json::Value) -> Result<(), TalonError> {
+        remote_response_data(&self.exec_json(cmd)?)?;
+        Ok(())
+    }
+
+    /// Run SQL inside the transaction.
+    pub fn run_sql(&self, sql: &str) -> Result<Vec<Vec<Value>>, TalonError> {
+        let resp = self.exec_json(&serde_json::json!({
+            "module": "sql",
+            "action": "",
+            "params": { "sql": sql, "txn_id": self.id }
+        }))?;
+        let data = remote_response_data(&resp)?.ok_or_else(|| {
+            remote_error(
+                TalonRemoteErrorKind::Protocol,
+                format!("SQL response missing data: {resp}"),
+            )
+        })?;
+        Ok(remote_query_result(data)?.rows)
+    }
+
+    /// Run parameterized SQL inside the transaction.
+    pub fn run_sql_param(
+        &self,
+        sql: &str,
+        params: &[Value],
+    ) -> Result<Vec<Vec<Value>>, TalonError> {
+        if params.is_empty() {
+            return self.run_sql(sql);
+        }
+        self.run_sql(&inline_sql_params(sql, params)?)
+    }
+
+    /// Read a key inside the transaction.
+    pub fn kv_get(&self, key: &[u8]) -> Result<Option<Vec<u8>>, TalonError> {
+        let enc = self.client.kv_encoding()?;
+        let key = enc.encode("KV key", key)?;
+        let cmd = kv_cmd(
+            enc,
+            "get",
+            serde_json::json!({ "key": key, "txn_id": self.id }),
+        );
+        let resp = self.exec_json(&cmd)?;
+        let data = remote_response_data(&resp)?.ok_or_else(|| {
+            remote_error(
+                TalonRemoteErrorKind::Protocol,
+                format!("KV get response missing data: {resp}"),
+            )
+        })?;
+        remote_kv_value(enc, "get", data.get("value"), &resp)
+    }
+
+    /// Write a key inside the transaction.
+    pub fn kv_set(
+        &self,
+        key: &[u8],
+        value: &[u8],
+        ttl_secs: Option<u64>,
+    ) -> Result<(), TalonError> {
+        let enc = self.client.kv_encoding()?;
+        let key = enc.encode("KV key", key)?;
+        let value = enc.encode("KV value", value)?;
+        let mut params = serde_json::json!({ "key": key, "value": value, "txn_id": self.id });
+        if let Some(ttl) = ttl_secs {
+            params["ttl"] = serde_json::json!(ttl);
+        }
+        self.exec(&kv_cmd(enc, "set", params))
+    }
+
+    /// Delete a key inside the transaction.
+    pub fn kv_del(&self, key: &[u8]) -> Result<(), TalonError> {
+        let enc = self.client.kv_encoding()?;
+        let key = enc.encode("KV key", key)?;
+        let cmd = kv_cmd(
+            enc,
+            "del",
+            serde_json::json!({ "key": key, "txn_id": self.id }),
+        );
+        self.exec(&cmd)
+    }
+
+    /// Commit the transaction. On failure the transaction is rolled back
+    /// when the guard drops; a conflict surfaces as a
+    /// [`TalonRemoteErrorKind::Conflict`] error. Use
+    /// [`TalonRemoteClient::transaction`] to retry automatically.
+    pub fn commit(mut self) -> Result<(), TalonError> {
+        self.commit_in_place()
+    }
+
+    /// Roll the transaction back.
+    pub fn rollback(mut self) -> Result<(), TalonError> {
+        self.finished = true;
+        self.finish("rollback")
+    }
+
+    fn finish(&self, action: &str) -> Result<(), TalonError> {
+        self.exec(&serde_json::json!({
+            "module": "txn",
+            "action": action,
+            "params": { "txn_id": self.id }
+        }))
+    }
+}
+
+impl RetryTxn for RemoteTransaction<'_> {
+    fn commit_in_place(&mut self) -> Result<(), TalonError> {
+        self.finish("commit")?;
+        self.finished = true;
+        Ok(())
+    }
+
+    fn conflicted(&self) -> bool {
+        RemoteTransaction::conflicted(self)
+    }
+}
+
+impl Drop for RemoteTransaction<'_> {
+    fn drop(&mut self) {
+        if !self.finished {
+            let _ = self.finish("rollback");
+        }
+    }
+}
+
+impl SqlBackend for RemoteTransaction<'_> {
+    fn sql_run(&self, sql: &str, params: &[Value]) -> Result<Vec<Vec<Value>>, TalonError> {
+        self.run_sql_param(sql, params)
+    }
+
+    /// Already inside a transaction: run `f` directly.
+    fn sql_transaction(
+        &self,
+        f: &mut dyn FnMut(&dyn SqlBackend) -> Result<(), TalonError>,
+    ) -> Result<(), TalonError> {
+        f(self)
+    }
+
+    /// Introspects inside this transaction, so uncommitted DDL is visible.
+    fn sql_table_exists(&self, table: &str) -> Result<bool, TalonError> {
+        Ok(self.client.schema_in(Some(self.id))?.table(table).is_some())
+    }
+}
+
+impl TalonRemoteClient {
+    /// Begin a server-side transaction.
+    pub fn begin(&self) -> Result<RemoteTransaction<'_>, TalonError> {
+        let resp = self.exec_cmd_json(&serde_json::json!({
+            "module": "txn",
+            "action": "begin",
+            "params": {}
+        }))?;
+        let id = remote_response_data(&resp)?
+            .and_then(|d| d.get("txn_id"))
+            .and_then(|v| v.as_u64())
+            .ok_or_else(|| {
+                remote_error(
+                    TalonRemoteErrorKind::Protocol,
+                    format!("txn begin response missing txn_id: {resp}"),
+                )
+            })?;
+        Ok(RemoteTransaction {
+            client: self,
+            id,
+            finished: false,
+            conflict: AtomicBool::new(false),
+        })
+    }
+
+    /// Run `f` in a transaction, committing on success and retrying on
+    /// serialization conflicts. `f` may run several times.
+    pub fn transaction<T>(
+        &self,
+        f: impl FnMut(&RemoteTransaction<'_>) -> Result<T, TalonError>,
+    ) -> Result<T, TalonError> {
+        run_with_retry(|| self.begin(), f)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::Cell;
+
+    /// 按脚本标记冲突的假事务。
+    struct FakeTxn {
+        conflict: bool,
+    }
+
+    impl RetryTxn for FakeTxn {
+        fn commit_in_place(&mut self) -> Result<(), TalonError> {
+            Ok(())
+        }
+
+        fn conflicted(&self) -> bool {
+            self.conflict
+        }
+    }
+
+    #[test]
+    fn retries_only_on_recorded_conflict() {
+        let attempts = Cell::new(0);
+        let value = run_with_retry(
+            || {
+                Ok(FakeTxn {
+                    conflict: attempts.get() < 2,
+                })
+            },
+            |tx| {
+                attempts.set(attempts.get() + 1);
+                if tx.conflict {
+                    Err(TalonError("txn_commit FFI failed: write skew".into()))
+                } else {
+                    Ok(7)
+                }
+            },
+        );
+        assert_eq!(value.unwrap(), 7);
+        assert_eq!(attempts.get(), 3);
+    }
+
+    #[test]
+    fn conflict_like_error_text_is_not_retried() {
+        let attempts = Cell::new(0);
+        let err = run_with_retry(
+            || Ok(FakeTxn { conflict: false }),
+            |_| -> Result<(), TalonError> {
+                attempts.set(attempts.get() + 1);
+                Err(TalonError("txn conflict: raised by user code".into()))
+            },
+        )
+        .unwrap_err();
+        assert_eq!(err.0, "txn conflict: raised by user code");
+        assert_eq!(attempts.get(), 1);
+    }
+
+    #[test]
+    fn retries_stop_after_the_limit() {
+        let attempts = Cell::new(0);
+        let err = run_with_retry(
+            || Ok(FakeTxn { conflict: true }),
+            |_| -> Result<(), TalonError> {
+                attempts.set(attempts.get() + 1);
+                Err(TalonError("txn conflict".into()))
+            },
+        )
+        .unwrap_err();
+        assert_eq!(err.0, "txn conflict");
+        assert_eq!(attempts.get(), TXN_MAX_RETRIES + 1);
+    }
+}
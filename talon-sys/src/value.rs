@@ -0,0 +1,519 @@
+/*
+ * Copyright (c) 2026 Talon Contributors
+ * Author: dark.lijin@gmail.com
+ * Licensed under the Talon Community Dual License Agreement.
+ * See the LICENSE file in the project root for full license information.
+ */
+//! `Value` 与 Rust 类型之间的转换。
+//!
+//! - 构造：`Value::from(42)`、`"x".into()`、`Some(1.5).into()`（`None` 为 `Null`）；
+//! - 提取：`i64::try_from(value)?`，失败时返回带期望 / 实际类型的 [`TalonError`]；
+//! - [`ToValue`] / [`FromValue`] 供参数（[`Params`](crate::Params)）与行映射
+//!   （[`FromRow`](crate::FromRow)）使用。
+//!
+//! `Value::Timestamp` 为 Unix 毫秒时间戳（UTC）。`chrono` / `time` feature 下的日期时间
+//! 类型按此换算，`uuid` feature 下 `Uuid` 存为带连字符的 `Text`（也可从 16 字节 `Blob` 读取）。
+
+use crate::{TalonError, Value};
+
+/// 转换为 [`Value`]，可作为 `&dyn ToValue` 混合类型传参。
+///
+/// 所有 `Clone + Into<Value>` 类型自动实现；自定义类型实现 `From<T> for Value` 即可。
+pub trait ToValue {
+    fn to_value(&self) -> Value;
+}
+
+impl<T: Clone + Into<Value>> ToValue for T {
+    fn to_value(&self) -> Value {
+        self.clone().into()
+    }
+}
+
+impl From<&dyn ToValue> for Value {
+    fn from(v: &dyn ToValue) -> Self {
+        v.to_value()
+    }
+}
+
+/// 从单个 [`Value`] 提取 Rust 类型，供行映射（[`FromRow`](crate::FromRow)）使用。
+pub trait FromValue: Sized {
+    /// 目标类型名，用于转换失败时的错误信息。
+    const TYPE_NAME: &'static str;
+
+    /// 类型不匹配时返回 `Err(原值)`，由调用方补充列位置等上下文。
+    fn from_value(value: Value) -> Result<Self, Value>;
+}
+
+impl Value {
+    /// 值的类型名（与 `Value` 变体同名），用于错误信息。
+    pub fn type_name(&self) -> &'static str {
+        match self {
+            Value::Null => "Null",
+            Value::Integer(_) => "Integer",
+            Value::Float(_) => "Float",
+            Value::Text(_) => "Text",
+            Value::Blob(_) => "Blob",
+            Value::Boolean(_) => "Boolean",
+            Value::Jsonb(_) => "Jsonb",
+            Value::Vector(_) => "Vector",
+            Value::Timestamp(_) => "Timestamp",
+            Value::GeoPoint(_, _) => "GeoPoint",
+        }
+    }
+}
+
+impl FromValue for Value {
+    const TYPE_NAME: &'static str = "Value";
+
+    fn from_value(value: Value) -> Result<Self, Value> {
+        Ok(value)
+    }
+}
+
+impl<T: FromValue> FromValue for Option<T> {
+    const TYPE_NAME: &'static str = T::TYPE_NAME;
+
+    fn from_value(value: Value) -> Result<Self, Value> {
+        match value {
+            Value::Null => Ok(None),
+            other => T::from_value(other).map(Some),
+        }
+    }
+}
+
+impl FromValue for i64 {
+    const TYPE_NAME: &'static str = "i64";
+
+    fn from_value(value: Value) -> Result<Self, Value> {
+        match value {
+            Value::Integer(v) | Value::Timestamp(v) => Ok(v),
+            other => Err(other),
+        }
+    }
+}
+
+macro_rules! from_value_int {
+    ($($ty:ty),*) => {$(
+        impl FromValue for $ty {
+            const TYPE_NAME: &'static str = stringify!($ty);
+
+            fn from_value(value: Value) -> Result<Self, Value> {
+                match value {
+                    Value::Integer(v) => <$ty>::try_from(v).map_err(|_| Value::Integer(v)),
+                    other => Err(other),
+                }
+            }
+        }
+    )*};
+}
+
+from_value_int!(i8, i16, i32, u8, u16, u32, u64, usize);
+
+impl FromValue for f64 {
+    const TYPE_NAME: &'static str = "f64";
+
+    fn from_value(value: Value) -> Result<Self, Value> {
+        match value {
+            Value::Float(v) => Ok(v),
+            Value::Integer(v) => Ok(v as f64),
+            other => Err(other),
+        }
+    }
+}
+
+impl FromValue for f32 {
+    const TYPE_NAME: &'static str = "f32";
+
+    fn from_value(value: Value) -> Result<Self, Value> {
+        f64::from_value(value).map(|v| v as f32)
+    }
+}
+
+impl FromValue for bool {
+    const TYPE_NAME: &'static str = "bool";
+
+    fn from_value(value: Value) -> Result<Self, Value> {
+        match value {
+            Value::Boolean(v) => Ok(v),
+            other => Err(other),
+        }
+    }
+}
+
+impl FromValue for String {
+    const TYPE_NAME: &'static str = "String";
+
+    fn from_value(value: Value) -> Result<Self, Value> {
+        match value {
+            Value::Text(v) => Ok(v),
+            other => Err(other),
+        }
+    }
+}
+
+impl FromValue for Vec<u8> {
+    const TYPE_NAME: &'static str = "Vec<u8>";
+
+    fn from_value(value: Value) -> Result<Self, Value> {
+        match value {
+            Value::Blob(v) => Ok(v),
+            other => Err(other),
+        }
+    }
+}
+
+impl FromValue for Vec<f32> {
+    const TYPE_NAME: &'static str = "Vec<f32>";
+
+    fn from_value(value: Value) -> Result<Self, Value> {
+        match value {
+            Value::Vector(v) => Ok(v),
+            other => Err(other),
+        }
+    }
+}
+
+impl FromValue for serde_json::Value {
+    const TYPE_NAME: &'static str = "serde_json::Value";
+
+    fn from_value(value: Value) -> Result<Self, Value> {
+        match value {
+            Value::Jsonb(v) => Ok(v),
+            other => Err(other),
+        }
+    }
+}
+
+// ── From<T> for Value ─────────────────────────────────────────────────────
+
+macro_rules! value_from {
+    ($($ty:ty => $variant:ident),* $(,)?) => {$(
+        impl From<$ty> for Value {
+            fn from(v: $ty) -> Self {
+                Value::$variant(v.into())
+            }
+        }
+    )*};
+}
+
+value_from!(
+    i8 => Integer,
+    i16 => Integer,
+    i32 => Integer,
+    i64 => Integer,
+    u8 => Integer,
+    u16 => Integer,
+    u32 => Integer,
+    f32 => Float,
+    f64 => Float,
+    bool => Boolean,
+    String => Text,
+    &str => Text,
+    char => Text,
+    Vec<u8> => Blob,
+    &[u8] => Blob,
+    Vec<f32> => Vector,
+    &[f32] => Vector,
+    serde_json::Value => Jsonb,
+);
+
+impl<T: Into<Value>> From<Option<T>> for Value {
+    fn from(v: Option<T>) -> Self {
+        v.map_or(Value::Null, Into::into)
+    }
+}
+
+macro_rules! value_try_from_unsigned {
+    ($($ty:ty),*) => {$(
+        /// 超出 `i64` 范围时失败。
+        impl TryFrom<$ty> for Value {
+            type Error = TalonError;
+
+            fn try_from(v: $ty) -> Result<Self, TalonError> {
+                i64::try_from(v)
+                    .map(Value::Integer)
+                    .map_err(|_| TalonError(format!("{v} does not fit in Integer")))
+            }
+        }
+    )*};
+}
+
+value_try_from_unsigned!(u64, usize);
+
+// ── TryFrom<Value> for T ──────────────────────────────────────────────────
+
+/// 按 [`FromValue`] 提取，失败时返回 `expected <T>, found <Variant>`。
+pub(crate) fn value_into<T: FromValue>(value: Value) -> Result<T, TalonError> {
+    T::from_value(value).map_err(|found| {
+        TalonError(format!(
+            "expected {}, found {}",
+            T::TYPE_NAME,
+            found.type_name()
+        ))
+    })
+}
+
+macro_rules! try_from_value {
+    ($($ty:ty),* $(,)?) => {$(
+        impl TryFrom<Value> for $ty {
+            type Error = TalonError;
+
+            fn try_from(value: Value) -> Result<Self, TalonError> {
+                value_into(value)
+            }
+        }
+
+        impl TryFrom<Value> for Option<$ty> {
+            type Error = TalonError;
+
+            fn try_from(value: Value) -> Result<Self, TalonError> {
+                value_into(value)
+            }
+        }
+    )*};
+}
+
+try_from_value!(
+    i8,
+    i16,
+    i32,
+    i64,
+    u8,
+    u16,
+    u32,
+    u64,
+    usize,
+    f32,
+    f64,
+    bool,
+    String,
+    Vec<u8>,
+    Vec<f32>,
+    serde_json::Value,
+);
+
+// ── chrono ────────────────────────────────────────────────────────────────
+
+#[cfg(feature = "chrono")]
+mod chrono_impls {
+    use chrono::{DateTime, NaiveDateTime, Utc};
+
+    use super::{value_into, FromValue};
+    use crate::{TalonError, Value};
+
+    impl From<DateTime<Utc>> for Value {
+        fn from(v: DateTime<Utc>) -> Self {
+            Value::Timestamp(v.timestamp_millis())
+        }
+    }
+
+    impl From<NaiveDateTime> for Value {
+        fn from(v: NaiveDateTime) -> Self {
+            Value::Timestamp(v.and_utc().timestamp_millis())
+        }
+    }
+
+    /// 读取 `Timestamp` / `Integer`（毫秒）或 RFC 3339 文本。
+    impl FromValue for DateTime<Utc> {
+        const TYPE_NAME: &'static str = "DateTime<Utc>";
+
+        fn from_value(value: Value) -> Result<Self, Value> {
+            match value {
+                Value::Timestamp(ms) | Value::Integer(ms) => {
+                    DateTime::from_timestamp_millis(ms).ok_or(value)
+                }
+                Value::Text(ref s) => DateTime::parse_from_rfc3339(s)
+                    .map(|dt| dt.with_timezone(&Utc))
+                    .map_err(|_| value),
+                other => Err(other),
+            }
+        }
+    }
+
+    impl FromValue for NaiveDateTime {
+        const TYPE_NAME: &'static str = "NaiveDateTime";
+
+        fn from_value(value: Value) -> Result<Self, Value> {
+            DateTime::<Utc>::from_value(value).map(|dt| dt.naive_utc())
+        }
+    }
+
+    try_from_value!(DateTime<Utc>, NaiveDateTime);
+}
+
+// ── time ──────────────────────────────────────────────────────────────────
+
+#[cfg(feature = "time")]
+mod time_impls {
+    use time::format_description::well_known::Rfc3339;
+    use time::{OffsetDateTime, PrimitiveDateTime, UtcOffset};
+
+    use super::{value_into, FromValue};
+    use crate::{TalonError, Value};
+
+    /// 向下取整到毫秒，与 chrono 的 `timestamp_millis` 一致（1970 年前同样向负无穷取整）。
+    fn unix_millis(v: OffsetDateTime) -> i64 {
+        v.unix_timestamp_nanos().div_euclid(1_000_000) as i64
+    }
+
+    impl From<OffsetDateTime> for Value {
+        fn from(v: OffsetDateTime) -> Self {
+            Value::Timestamp(unix_millis(v))
+        }
+    }
+
+    impl From<PrimitiveDateTime> for Value {
+        fn from(v: PrimitiveDateTime) -> Self {
+            Value::Timestamp(unix_millis(v.assume_utc()))
+        }
+    }
+
+    /// 读取 `Timestamp` / `Integer`（毫秒）或 RFC 3339 文本，结果为 UTC 偏移。
+    impl FromValue for OffsetDateTime {
+        const TYPE_NAME: &'static str = "OffsetDateTime";
+
+        fn from_value(value: Value) -> Result<Self, Value> {
+            match value {
+                Value::Timestamp(ms) | Value::Integer(ms) => {
+                    OffsetDateTime::from_unix_timestamp_nanos(i128::from(ms) * 1_000_000)
+                        .map_err(|_| value)
+                }
+                Value::Text(ref s) => OffsetDateTime::parse(s, &Rfc3339)
+                    .map(|dt| dt.to_offset(UtcOffset::UTC))
+                    .map_err(|_| value),
+                other => Err(other),
+            }
+        }
+    }
+
+    impl FromValue for PrimitiveDateTime {
+        const TYPE_NAME: &'static str = "PrimitiveDateTime";
+
+        fn from_value(value: Value) -> Result<Self, Value> {
+            OffsetDateTime::from_value(value).map(|dt| PrimitiveDateTime::new(dt.date(), dt.time()))
+        }
+    }
+
+    try_from_value!(OffsetDateTime, PrimitiveDateTime);
+}
+
+// ── uuid ──────────────────────────────────────────────────────────────────
+
+#[cfg(feature = "uuid")]
+mod uuid_impls {
+    use uuid::Uuid;
+
+    use super::{value_into, FromValue};
+    use crate::{TalonError, Value};
+
+    impl From<Uuid> for Value {
+        fn from(v: Uuid) -> Self {
+            Value::Text(v.hyphenated().to_string())
+        }
+    }
+
+    /// 读取 UUID 文本或 16 字节 `Blob`。
+    impl FromValue for Uuid {
+        const TYPE_NAME: &'static str = "Uuid";
+
+        fn from_value(value: Value) -> Result<Self, Value> {
+            match value {
+                Value::Text(ref s) => Uuid::parse_str(s).map_err(|_| value),
+                Value::Blob(ref b) => Uuid::from_slice(b).map_err(|_| value),
+                other => Err(other),
+            }
+        }
+    }
+
+    try_from_value!(Uuid);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rust_types_convert_into_value() {
+        assert_eq!(Value::from(7u8), Value::Integer(7));
+        assert_eq!(Value::from("x"), Value::Text("x".into()));
+        assert_eq!(Value::from(1.5f32), Value::Float(1.5));
+        assert_eq!(Value::from(&[1u8, 2][..]), Value::Blob(vec![1, 2]));
+        assert_eq!(Value::from(None::<i64>), Value::Null);
+        assert_eq!(Value::from(Some(true)), Value::Boolean(true));
+        assert_eq!(Value::try_from(5u64).unwrap(), Value::Integer(5));
+        assert!(Value::try_from(u64::MAX).is_err());
+        assert_eq!("y".to_value(), Value::Text("y".into()));
+    }
+
+    #[test]
+    fn values_convert_into_rust_types() {
+        assert_eq!(i32::try_from(Value::Integer(3)).unwrap(), 3);
+        assert_eq!(Option::<String>::try_from(Value::Null).unwrap(), None);
+        assert_eq!(
+            Vec::<f32>::try_from(Value::Vector(vec![0.5])).unwrap(),
+            vec![0.5]
+        );
+        let err = bool::try_from(Value::Text("no".into())).unwrap_err();
+        assert_eq!(err.0, "expected bool, found Text");
+        assert!(u8::try_from(Value::Integer(300)).is_err());
+    }
+
+    #[cfg(feature = "chrono")]
+    #[test]
+    fn chrono_round_trip() {
+        let dt = chrono::DateTime::from_timestamp_millis(1_700_000_000_123).unwrap();
+        let value = Value::from(dt);
+        assert_eq!(value, Value::Timestamp(1_700_000_000_123));
+        assert_eq!(
+            chrono::DateTime::<chrono::Utc>::try_from(value).unwrap(),
+            dt
+        );
+        let parsed = chrono::DateTime::<chrono::Utc>::try_from(Value::Text(
+            "2023-11-14T22:13:20.123Z".into(),
+        ))
+        .unwrap();
+        assert_eq!(parsed, dt);
+    }
+
+    #[cfg(feature = "time")]
+    #[test]
+    fn time_round_trip() {
+        let dt = time::OffsetDateTime::from_unix_timestamp(1_700_000_000).unwrap();
+        let value = Value::from(dt);
+        assert_eq!(value, Value::Timestamp(1_700_000_000_000));
+        assert_eq!(time::OffsetDateTime::try_from(value).unwrap(), dt);
+        let parsed =
+            time::OffsetDateTime::try_from(Value::Text("2023-11-14T23:13:20+01:00".into()))
+                .unwrap();
+        assert_eq!(parsed, dt);
+        assert_eq!(parsed.offset(), time::UtcOffset::UTC);
+    }
+
+    #[cfg(all(feature = "chrono", feature = "time"))]
+    #[test]
+    fn chrono_and_time_agree() {
+        // 1970 年前的亚毫秒时间：两者都向下取整。
+        let nanos = -1_500_000i64;
+        let chrono_dt = chrono::DateTime::from_timestamp_nanos(nanos);
+        let time_dt = time::OffsetDateTime::from_unix_timestamp_nanos(i128::from(nanos)).unwrap();
+        assert_eq!(Value::from(chrono_dt), Value::Timestamp(-2));
+        assert_eq!(Value::from(time_dt), Value::Timestamp(-2));
+
+        let text = Value::Text("1969-12-31T23:59:59.998Z".into());
+        let chrono_ms = chrono::DateTime::<chrono::Utc>::try_from(text.clone())
+            .unwrap()
+            .timestamp_millis();
+        let time_ms = time::OffsetDateTime::try_from(text).unwrap();
+        assert_eq!(Value::from(time_ms), Value::Timestamp(chrono_ms));
+    }
+
+    #[cfg(feature = "uuid")]
+    #[test]
+    fn uuid_round_trip() {
+        let id = uuid::Uuid::from_u128(0x1234);
+        let value = Value::from(id);
+        assert_eq!(uuid::Uuid::try_from(value).unwrap(), id);
+        let blob = Value::Blob(id.as_bytes().to_vec());
+        assert_eq!(uuid::Uuid::try_from(blob).unwrap(), id);
+    }
+}
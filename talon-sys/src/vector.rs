@@ -0,0 +1,963 @@
+/*
+ * Copyright (c) 2026 Talon Contributors
+ * Author: dark.lijin@gmail.com
+ * Licensed under the Talon Community Dual License Agreement.
+ * See the LICENSE file in the project root for full license information.
+ */
+//! 向量扩展 — 批量写入与索引生命周期管理。
+//!
+//! ## 批量写入
+//!
+//! 一批向量拼成连续的 f32 平铺缓冲区写入，省去逐条调用开销。写入前统一校验维度。
+//! 嵌入式每 `VECTOR_BATCH_CHUNK` 条调用一次 `talon_vector_insert_batch`；远程按帧大小切块，向量以 base64 编码的小端 f32
+//! 缓冲区发送：
+//!
+//! ```text
+//! {"module":"vector","action":"insert_batch",
+//!  "params":{"index","dim","ids":[...],"vectors":"<base64>"}}
+//! ```
+//!
+//! 每块写完后回调 `progress(已写入条数, 总条数)`；某块失败时之前的块已生效。
+//!
+//! ## 索引管理
+//!
+//! `create_index` / `drop_index` / `index_stats` / `rebuild` 在嵌入式与远程均以
+//! `module:"vector"` 的同名 action 发送，`params.index` 为当前索引名。列出全部索引
+//! 与具体索引无关，由 `Talon::list_vector_indexes` / `TalonRemoteClient::list_vector_indexes`
+//! 发送 `list_indexes`。
+//!
+//! ## 元数据与过滤搜索
+//!
+//! `insert_with_payload` 为向量附带一个 JSON 对象作为元数据。`search_with` 把
+//! [`VectorFilter`] 下推到引擎，在 HNSW 遍历时跳过不匹配的候选，选择性强的过滤
+//! 不会损失召回：
+//!
+//! ```text
+//! {"module":"vector","action":"search_with",
+//!  "params":{"index","vector","k","metric","filter","with_payload"}}
+//! → {"results":[{"id","distance","payload"?}]}
+//! ```
+//!
+//! ## 按 id 读写
+//!
+//! `get` / `contains` / `upsert` / `ids` 同样以 `module:"vector"` 的同名 action 发送。
+//! `upsert` 在引擎内原子替换向量（保留元数据），`ids` 按 id 升序分页：
+//!
+//! ```text
+//! {"action":"get","params":{"index","id"}}           → {"vector":[...]|null}
+//! {"action":"contains","params":{"index","id"}}      → {"exists":bool}
+//! {"action":"ids","params":{"index","after","limit"}} → {"ids":[...],"next":u64|null}
+//! ```
+//!
+//! 响应缺少上述字段时返回错误，而不是当作不存在或空页。
+
+use std::ffi::CString;
+
+use base64::Engine as _;
+use serde::Deserialize;
+
+use crate::{
+    embedded_response_data, ffi_error, raw_ffi, remote_error, remote_response_data,
+    RemoteVectorEngine, Talon, TalonError, TalonRemoteClient, TalonRemoteErrorKind, VectorEngine,
+    VectorIndexInfo, BASE64, MAX_REMOTE_FRAME_SIZE,
+};
+
+/// 嵌入式每次 FFI 调用写入的向量数。
+const VECTOR_BATCH_CHUNK: usize = 8192;
+
+/// 一批维度一致的向量：`data` 为按 `ids` 顺序平铺的 `ids.len() * dim` 个分量。
+#[derive(Debug)]
+struct FlatBatch {
+    ids: Vec<u64>,
+    data: Vec<f32>,
+    dim: usize,
+}
+
+fn flatten_batch(items: &[(u64, &[f32])]) -> Result<FlatBatch, TalonError> {
+    let dim = items.first().map_or(0, |(_, v)| v.len());
+    if !items.is_empty() && dim == 0 {
+        return Err(TalonError("vector insert_batch: empty embedding".into()));
+    }
+    let mut ids = Vec::with_capacity(items.len());
+    let mut data = Vec::with_capacity(items.len() * dim);
+    for (i, (id, embedding)) in items.iter().enumerate() {
+        if embedding.len() != dim {
+            return Err(TalonError(format!(
+                "vector insert_batch item {i} (id {id}): dimension {}, expected {dim}",
+                embedding.len()
+            )));
+        }
+        ids.push(*id);
+        data.extend_from_slice(embedding);
+    }
+    Ok(FlatBatch { ids, data, dim })
+}
+
+impl FlatBatch {
+    /// 按 `chunk_len` 条切块依次 `send`，每块成功后回调进度。
+    fn send_chunks(
+        &self,
+        chunk_len: usize,
+        progress: &mut dyn FnMut(usize, usize),
+        mut send: impl FnMut(&[u64], &[f32]) -> Result<(), TalonError>,
+    ) -> Result<(), TalonError> {
+        let chunk_len = chunk_len.max(1);
+        let total = self.ids.len();
+        for (n, ids) in self.ids.chunks(chunk_len).enumerate() {
+            let start = n * chunk_len;
+            let data = &self.data[start * self.dim..(start + ids.len()) * self.dim];
+            send(ids, data).map_err(|e| {
+                TalonError(format!(
+                    "vector insert_batch failed after {start} of {total} vectors: {}",
+                    e.0
+                ))
+            })?;
+            progress(start + ids.len(), total);
+        }
+        Ok(())
+    }
+}
+
+/// 远程单块可容纳的向量数：为 JSON 外壳预留 64 KiB，向量按 base64 膨胀计算，
+/// id 按十进制最长 20 位加分隔符计算。
+fn remote_chunk_len(dim: usize) -> usize {
+    let budget = MAX_REMOTE_FRAME_SIZE as usize - 64 * 1024;
+    let per_vector = dim * 4 / 3 * 4 + 4 + 21;
+    (budget / per_vector).max(1)
+}
+
+impl VectorEngine<'_> {
+    /// 批量插入向量（维度须一致）。
+    pub fn insert_batch(&self, items: &[(u64, &[f32])]) -> Result<(), TalonError> {
+        self.insert_batch_with_progress(items, |_, _| {})
+    }
+
+    /// 批量插入向量，每写完一块回调 `progress(已写入条数, 总条数)`。
+    pub fn insert_batch_with_progress(
+        &self,
+        items: &[(u64, &[f32])],
+        mut progress: impl FnMut(usize, usize),
+    ) -> Result<(), TalonError> {
+        let batch = flatten_batch(items)?;
+        batch.send_chunks(VECTOR_BATCH_CHUNK, &mut progress, |ids, data| {
+            self.db
+                .raw_vector_insert_batch(&self.index, ids, data, batch.dim)
+        })
+    }
+}
+
+impl Talon {
+    fn raw_vector_insert_batch(
+        &self,
+        index: &str,
+        ids: &[u64],
+        data: &[f32],
+        dim: usize,
+    ) -> Result<(), TalonError> {
+        let c_name = CString::new(index)?;
+        let rc = unsafe {
+            raw_ffi::talon_vector_insert_batch(
+                self.handle,
+                c_name.as_ptr(),
+                ids.as_ptr(),
+                data.as_ptr(),
+                ids.len(),
+                dim,
+            )
+        };
+        if rc != 0 {
+            return Err(ffi_error("vector_insert_batch"));
+        }
+        Ok(())
+    }
+}
+
+impl RemoteVectorEngine<'_> {
+    /// Insert many embeddings of the same dimension, split into requests
+    /// that stay under the maximum frame size.
+    pub fn insert_batch(&self, items: &[(u64, &[f32])]) -> Result<(), TalonError> {
+        self.insert_batch_with_progress(items, |_, _| {})
+    }
+
+    /// Like [`insert_batch`](Self::insert_batch), calling
+    /// `progress(inserted, total)` after each request.
+    pub fn insert_batch_with_progress(
+        &self,
+        items: &[(u64, &[f32])],
+        mut progress: impl FnMut(usize, usize),
+    ) -> Result<(), TalonError> {
+        let batch = flatten_batch(items)?;
+        batch.send_chunks(remote_chunk_len(batch.dim), &mut progress, |ids, data| {
+            let bytes: Vec<u8> = data.iter().flat_map(|f| f.to_le_bytes()).collect();
+            self.client.exec_cmd(&serde_json::json!({
+                "module": "vector",
+                "action": "insert_batch",
+                "params": {
+                    "index": &self.index,
+                    "dim": batch.dim,
+                    "ids": ids,
+                    "vectors": BASE64.encode(bytes)
+                }
+            }))
+        })
+    }
+}
+
+// ── 索引管理 ──────────────────────────────────────────────────────────────
+
+/// 向量距离度量。
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum VectorMetric {
+    #[default]
+    Cosine,
+    /// 欧氏距离。
+    L2,
+    /// 内积。
+    Dot,
+}
+
+impl VectorMetric {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            VectorMetric::Cosine => "cosine",
+            VectorMetric::L2 => "l2",
+            VectorMetric::Dot => "dot",
+        }
+    }
+}
+
+/// 向量索引（HNSW）配置。
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct VectorIndexConfig {
+    pub dim: u32,
+    pub metric: VectorMetric,
+    /// 每个节点的最大邻居数。
+    pub hnsw_m: u32,
+    /// 构建时的候选队列长度。
+    pub ef_construction: u32,
+    /// 查询时的候选队列长度。
+    pub ef_search: u32,
+}
+
+impl VectorIndexConfig {
+    /// 指定维度，其余取默认值（cosine，M=16，ef_construction=200，ef_search=64）。
+    pub fn new(dim: u32) -> Self {
+        Self {
+            dim,
+            metric: VectorMetric::Cosine,
+            hnsw_m: 16,
+            ef_construction: 200,
+            ef_search: 64,
+        }
+    }
+
+    pub fn metric(mut self, metric: VectorMetric) -> Self {
+        self.metric = metric;
+        self
+    }
+}
+
+/// 索引构建状态。
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum VectorIndexState {
+    Ready,
+    Building,
+    Rebuilding,
+    /// 引擎返回了未知状态。
+    #[default]
+    #[serde(other)]
+    Unknown,
+}
+
+/// 索引统计。
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(default)]
+pub struct VectorIndexStats {
+    pub count: u64,
+    /// 索引占用内存（字节）。
+    pub memory_bytes: u64,
+    pub state: VectorIndexState,
+    pub dim: u32,
+    pub metric: String,
+}
+
+fn index_cmd(action: &str, index: &str) -> serde_json::Value {
+    serde_json::json!({
+        "module": "vector", "action": action,
+        "params": { "index": index }
+    })
+}
+
+fn create_index_cmd(index: &str, config: &VectorIndexConfig) -> serde_json::Value {
+    serde_json::json!({
+        "module": "vector", "action": "create_index",
+        "params": {
+            "index": index,
+            "dim": config.dim,
+            "metric": config.metric.as_str(),
+            "hnsw_m": config.hnsw_m,
+            "ef_construction": config.ef_construction,
+            "ef_search": config.ef_search
+        }
+    })
+}
+
+fn list_indexes_cmd() -> serde_json::Value {
+    serde_json::json!({"module": "vector", "action": "list_indexes", "params": {}})
+}
+
+fn parse_index_list(data: Option<&serde_json::Value>) -> Result<Vec<VectorIndexInfo>, TalonError> {
+    let list = data
+        .and_then(|d| d.get("indexes"))
+        .ok_or_else(|| TalonError("list_indexes response missing indexes".into()))?;
+    Vec::deserialize(list).map_err(|e| TalonError(format!("invalid list_indexes response: {e}")))
+}
+
+fn parse_index_stats(data: Option<&serde_json::Value>) -> Result<VectorIndexStats, TalonError> {
+    let data = data.ok_or_else(|| TalonError("index_stats response missing data".into()))?;
+    VectorIndexStats::deserialize(data)
+        .map_err(|e| TalonError(format!("invalid index_stats response: {e}")))
+}
+
+impl Talon {
+    /// 列出所有向量索引。
+    pub fn list_vector_indexes(&self) -> Result<Vec<VectorIndexInfo>, TalonError> {
+        let resp = self.exec_cmd_json(&list_indexes_cmd())?;
+        parse_index_list(embedded_response_data(&resp)?)
+    }
+}
+
+impl TalonRemoteClient {
+    /// List all vector indexes on the server.
+    pub fn list_vector_indexes(&self) -> Result<Vec<VectorIndexInfo>, TalonError> {
+        let resp = self.exec_cmd_json(&list_indexes_cmd())?;
+        parse_index_list(remote_response_data(&resp)?)
+            .map_err(|e| remote_error(TalonRemoteErrorKind::Protocol, e.0))
+    }
+}
+
+impl VectorEngine<'_> {
+    /// 按配置创建当前索引。
+    pub fn create_index(&self, config: &VectorIndexConfig) -> Result<(), TalonError> {
+        self.db.exec_cmd(&create_index_cmd(&self.index, config))
+    }
+
+    /// 删除当前索引及其全部向量。
+    pub fn drop_index(&self) -> Result<(), TalonError> {
+        self.db.exec_cmd(&index_cmd("drop_index", &self.index))
+    }
+
+    /// 当前索引的统计（向量数、内存、构建状态）。
+    pub fn index_stats(&self) -> Result<VectorIndexStats, TalonError> {
+        let resp = self
+            .db
+            .exec_cmd_json(&index_cmd("index_stats", &self.index))?;
+        parse_index_stats(embedded_response_data(&resp)?)
+    }
+
+    /// 重建当前索引（后台进行，期间状态为 `Rebuilding`）。
+    pub fn rebuild(&self) -> Result<(), TalonError> {
+        self.db.exec_cmd(&index_cmd("rebuild", &self.index))
+    }
+}
+
+impl RemoteVectorEngine<'_> {
+    /// Create this index with the given configuration.
+    pub fn create_index(&self, config: &VectorIndexConfig) -> Result<(), TalonError> {
+        self.client.exec_cmd(&create_index_cmd(&self.index, config))
+    }
+
+    /// Drop this index together with all of its vectors.
+    pub fn drop_index(&self) -> Result<(), TalonError> {
+        self.client.exec_cmd(&index_cmd("drop_index", &self.index))
+    }
+
+    /// Vector count, memory usage and build state of this index.
+    pub fn index_stats(&self) -> Result<VectorIndexStats, TalonError> {
+        let resp = self
+            .client
+            .exec_cmd_json(&index_cmd("index_stats", &self.index))?;
+        parse_index_stats(remote_response_data(&resp)?)
+            .map_err(|e| remote_error(TalonRemoteErrorKind::Protocol, e.0))
+    }
+
+    /// Rebuild this index in the background.
+    pub fn rebuild(&self) -> Result<(), TalonError> {
+        self.client.exec_cmd(&index_cmd("rebuild", &self.index))
+    }
+}
+
+// ── 元数据与过滤搜索 ──────────────────────────────────────────────────────
+
+/// 元数据过滤条件，`field` 为元数据对象中的顶层键。
+#[derive(Debug, Clone, PartialEq)]
+pub enum VectorFilter {
+    Eq(String, serde_json::Value),
+    Ne(String, serde_json::Value),
+    Gt(String, serde_json::Value),
+    Gte(String, serde_json::Value),
+    Lt(String, serde_json::Value),
+    Lte(String, serde_json::Value),
+    /// 字段值属于给定集合。
+    In(String, Vec<serde_json::Value>),
+    /// 字段存在（值可为 null）。
+    Exists(String),
+    /// 全部满足；空列表恒为真。
+    And(Vec<VectorFilter>),
+    /// 任一满足；空列表恒为假。
+    Or(Vec<VectorFilter>),
+    Not(Box<VectorFilter>),
+}
+
+impl VectorFilter {
+    pub fn eq(field: &str, value: impl Into<serde_json::Value>) -> Self {
+        VectorFilter::Eq(field.to_string(), value.into())
+    }
+
+    pub fn ne(field: &str, value: impl Into<serde_json::Value>) -> Self {
+        VectorFilter::Ne(field.to_string(), value.into())
+    }
+
+    pub fn gt(field: &str, value: impl Into<serde_json::Value>) -> Self {
+        VectorFilter::Gt(field.to_string(), value.into())
+    }
+
+    pub fn gte(field: &str, value: impl Into<serde_json::Value>) -> Self {
+        VectorFilter::Gte(field.to_string(), value.into())
+    }
+
+    pub fn lt(field: &str, value: impl Into<serde_json::Value>) -> Self {
+        VectorFilter::Lt(field.to_string(), value.into())
+    }
+
+    pub fn lte(field: &str, value: impl Into<serde_json::Value>) -> Self {
+        VectorFilter::Lte(field.to_string(), value.into())
+    }
+
+    pub fn is_in<V: Into<serde_json::Value>>(
+        field: &str,
+        values: impl IntoIterator<Item = V>,
+    ) -> Self {
+        VectorFilter::In(
+            field.to_string(),
+            values.into_iter().map(Into::into).collect(),
+        )
+    }
+
+    pub fn exists(field: &str) -> Self {
+        VectorFilter::Exists(field.to_string())
+    }
+
+    /// 不过滤。
+    pub fn all() -> Self {
+        VectorFilter::And(vec![])
+    }
+
+    /// 与另一条件组合为 `And`（已是 `And` 时直接追加）。
+    pub fn and(self, other: VectorFilter) -> Self {
+        match self {
+            VectorFilter::And(mut list) => {
+                list.push(other);
+                VectorFilter::And(list)
+            }
+            first => VectorFilter::And(vec![first, other]),
+        }
+    }
+
+    /// 与另一条件组合为 `Or`（已是 `Or` 时直接追加）。
+    pub fn or(self, other: VectorFilter) -> Self {
+        match self {
+            VectorFilter::Or(mut list) => {
+                list.push(other);
+                VectorFilter::Or(list)
+            }
+            first => VectorFilter::Or(vec![first, other]),
+        }
+    }
+
+    pub fn negate(self) -> Self {
+        VectorFilter::Not(Box::new(self))
+    }
+
+    /// 引擎侧的 JSON 表示：`{"op","field","value"}` / `{"op":"and","filters":[...]}`。
+    pub fn to_json(&self) -> serde_json::Value {
+        use serde_json::json;
+        let cmp = |op: &str, field: &str, value: &serde_json::Value| json!({ "op": op, "field": field, "value": value });
+        match self {
+            VectorFilter::Eq(f, v) => cmp("eq", f, v),
+            VectorFilter::Ne(f, v) => cmp("ne", f, v),
+            VectorFilter::Gt(f, v) => cmp("gt", f, v),
+            VectorFilter::Gte(f, v) => cmp("gte", f, v),
+            VectorFilter::Lt(f, v) => cmp("lt", f, v),
+            VectorFilter::Lte(f, v) => cmp("lte", f, v),
+            VectorFilter::In(f, values) => json!({ "op": "in", "field": f, "value": values }),
+            VectorFilter::Exists(f) => json!({ "op": "exists", "field": f }),
+            VectorFilter::And(list) => json!({
+                "op": "and",
+                "filters": list.iter().map(VectorFilter::to_json).collect::<Vec<_>>()
+            }),
+            VectorFilter::Or(list) => json!({
+                "op": "or",
+                "filters": list.iter().map(VectorFilter::to_json).collect::<Vec<_>>()
+            }),
+            VectorFilter::Not(inner) => json!({ "op": "not", "filter": inner.to_json() }),
+        }
+    }
+}
+
+/// 过滤搜索的一条命中。
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct VectorHit {
+    pub id: u64,
+    pub distance: f32,
+    /// 元数据（仅 `search_with_payload`，未附带元数据的向量为 `None`）。
+    #[serde(default)]
+    pub payload: Option<serde_json::Value>,
+}
+
+fn insert_with_payload_cmd(
+    index: &str,
+    id: u64,
+    embedding: &[f32],
+    payload: &serde_json::Value,
+) -> Result<serde_json::Value, TalonError> {
+    if !payload.is_object() {
+        return Err(TalonError("vector payload must be a JSON object".into()));
+    }
+    Ok(serde_json::json!({
+        "module": "vector", "action": "insert",
+        "params": { "index": index, "id": id, "vector": embedding, "payload": payload }
+    }))
+}
+
+fn search_with_cmd(
+    index: &str,
+    query: &[f32],
+    k: usize,
+    metric: VectorMetric,
+    filter: &VectorFilter,
+    with_payload: bool,
+) -> serde_json::Value {
+    serde_json::json!({
+        "module": "vector", "action": "search_with",
+        "params": {
+            "index": index,
+            "vector": query,
+            "k": k,
+            "metric": metric.as_str(),
+            "filter": filter.to_json(),
+            "with_payload": with_payload
+        }
+    })
+}
+
+fn parse_hits(data: Option<&serde_json::Value>) -> Result<Vec<VectorHit>, TalonError> {
+    let results = data
+        .and_then(|d| d.get("results"))
+        .ok_or_else(|| TalonError("search_with response missing results".into()))?;
+    Vec::deserialize(results).map_err(|e| TalonError(format!("invalid search_with response: {e}")))
+}
+
+impl VectorEngine<'_> {
+    /// 插入向量并附带元数据（JSON 对象），已存在的 id 会被覆盖。
+    pub fn insert_with_payload(
+        &self,
+        id: u64,
+        embedding: &[f32],
+        payload: &serde_json::Value,
+    ) -> Result<(), TalonError> {
+        let cmd = insert_with_payload_cmd(&self.index, id, embedding, payload)?;
+        self.db.exec_cmd(&cmd)
+    }
+
+    /// 带元数据过滤的 KNN 搜索，过滤在引擎内执行。
+    pub fn search_with(
+        &self,
+        query: &[f32],
+        k: usize,
+        metric: VectorMetric,
+        filter: &VectorFilter,
+    ) -> Result<Vec<VectorHit>, TalonError> {
+        self.raw_search_with(query, k, metric, filter, false)
+    }
+
+    /// 同 [`search_with`](Self::search_with)，并在每条命中中返回元数据。
+    pub fn search_with_payload(
+        &self,
+        query: &[f32],
+        k: usize,
+        metric: VectorMetric,
+        filter: &VectorFilter,
+    ) -> Result<Vec<VectorHit>, TalonError> {
+        self.raw_search_with(query, k, metric, filter, true)
+    }
+
+    fn raw_search_with(
+        &self,
+        query: &[f32],
+        k: usize,
+        metric: VectorMetric,
+        filter: &VectorFilter,
+        with_payload: bool,
+    ) -> Result<Vec<VectorHit>, TalonError> {
+        let cmd = search_with_cmd(&self.index, query, k, metric, filter, with_payload);
+        let resp = self.db.exec_cmd_json(&cmd)?;
+        parse_hits(embedded_response_data(&resp)?)
+    }
+}
+
+impl RemoteVectorEngine<'_> {
+    /// Insert one embedding with a JSON object as its metadata payload,
+    /// replacing any existing entry with the same id.
+    pub fn insert_with_payload(
+        &self,
+        id: u64,
+        embedding: &[f32],
+        payload: &serde_json::Value,
+    ) -> Result<(), TalonError> {
+        let cmd = insert_with_payload_cmd(&self.index, id, embedding, payload)?;
+        self.client.exec_cmd(&cmd)
+    }
+
+    /// KNN search with a metadata filter applied inside the engine.
+    pub fn search_with(
+        &self,
+        query: &[f32],
+        k: usize,
+        metric: VectorMetric,
+        filter: &VectorFilter,
+    ) -> Result<Vec<VectorHit>, TalonError> {
+        self.remote_search_with(query, k, metric, filter, false)
+    }
+
+    /// Like [`search_with`](Self::search_with), also returning each hit's payload.
+    pub fn search_with_payload(
+        &self,
+        query: &[f32],
+        k: usize,
+        metric: VectorMetric,
+        filter: &VectorFilter,
+    ) -> Result<Vec<VectorHit>, TalonError> {
+        self.remote_search_with(query, k, metric, filter, true)
+    }
+
+    fn remote_search_with(
+        &self,
+        query: &[f32],
+        k: usize,
+        metric: VectorMetric,
+        filter: &VectorFilter,
+        with_payload: bool,
+    ) -> Result<Vec<VectorHit>, TalonError> {
+        let cmd = search_with_cmd(&self.index, query, k, metric, filter, with_payload);
+        let resp = self.client.exec_cmd_json(&cmd)?;
+        parse_hits(remote_response_data(&resp)?)
+            .map_err(|e| remote_error(TalonRemoteErrorKind::Protocol, e.0))
+    }
+}
+
+// ── 按 id 读写 ────────────────────────────────────────────────────────────
+
+/// `ids` 的一页结果。
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+pub struct VectorIdPage {
+    /// 按升序排列的 id。
+    pub ids: Vec<u64>,
+    /// 下一页的 `after` 游标；`None` 表示已到末尾。
+    pub next: Option<u64>,
+}
+
+fn id_cmd(action: &str, index: &str, id: u64) -> serde_json::Value {
+    serde_json::json!({
+        "module": "vector", "action": action,
+        "params": { "index": index, "id": id }
+    })
+}
+
+fn upsert_cmd(index: &str, id: u64, embedding: &[f32]) -> serde_json::Value {
+    serde_json::json!({
+        "module": "vector", "action": "upsert",
+        "params": { "index": index, "id": id, "vector": embedding }
+    })
+}
+
+fn ids_cmd(index: &str, after: Option<u64>, limit: usize) -> serde_json::Value {
+    serde_json::json!({
+        "module": "vector", "action": "ids",
+        "params": { "index": index, "after": after, "limit": limit }
+    })
+}
+
+fn parse_embedding(data: Option<&serde_json::Value>) -> Result<Option<Vec<f32>>, TalonError> {
+    let vector = data
+        .and_then(|d| d.get("vector"))
+        .ok_or_else(|| TalonError("vector get response missing vector".into()))?;
+    if vector.is_null() {
+        return Ok(None);
+    }
+    let items = vector
+        .as_array()
+        .ok_or_else(|| TalonError(format!("invalid vector get response: {vector}")))?;
+    items
+        .iter()
+        .map(|x| {
+            x.as_f64()
+                .map(|f| f as f32)
+                .ok_or_else(|| TalonError(format!("invalid vector component: {x}")))
+        })
+        .collect::<Result<Vec<_>, _>>()
+        .map(Some)
+}
+
+fn parse_contains(data: Option<&serde_json::Value>) -> Result<bool, TalonError> {
+    data.and_then(|d| d.get("exists"))
+        .and_then(|v| v.as_bool())
+        .ok_or_else(|| TalonError("vector contains response missing exists".into()))
+}
+
+fn parse_id_page(data: Option<&serde_json::Value>) -> Result<VectorIdPage, TalonError> {
+    let data = data.ok_or_else(|| TalonError("vector ids response missing data".into()))?;
+    VectorIdPage::deserialize(data)
+        .map_err(|e| TalonError(format!("invalid vector ids response: {e}")))
+}
+
+impl VectorEngine<'_> {
+    /// 读取 `id` 对应的向量，不存在时返回 `None`。
+    pub fn get(&self, id: u64) -> Result<Option<Vec<f32>>, TalonError> {
+        let resp = self.db.exec_cmd_json(&id_cmd("get", &self.index, id))?;
+        parse_embedding(embedded_response_data(&resp)?)
+    }
+
+    /// `id` 是否存在。
+    pub fn contains(&self, id: u64) -> Result<bool, TalonError> {
+        let resp = self
+            .db
+            .exec_cmd_json(&id_cmd("contains", &self.index, id))?;
+        parse_contains(embedded_response_data(&resp)?)
+    }
+
+    /// 原子写入或替换 `id` 的向量，已有元数据保留。
+    pub fn upsert(&self, id: u64, embedding: &[f32]) -> Result<(), TalonError> {
+        self.db.exec_cmd(&upsert_cmd(&self.index, id, embedding))
+    }
+
+    /// 按 id 升序分页列出，`after` 为上一页的 `next`（首页传 `None`）。
+    pub fn ids(&self, after: Option<u64>, limit: usize) -> Result<VectorIdPage, TalonError> {
+        let resp = self.db.exec_cmd_json(&ids_cmd(&self.index, after, limit))?;
+        parse_id_page(embedded_response_data(&resp)?)
+    }
+}
+
+impl RemoteVectorEngine<'_> {
+    /// Read back the embedding stored under `id`, or `None` if absent.
+    pub fn get(&self, id: u64) -> Result<Option<Vec<f32>>, TalonError> {
+        let resp = self.client.exec_cmd_json(&id_cmd("get", &self.index, id))?;
+        parse_embedding(remote_response_data(&resp)?)
+            .map_err(|e| remote_error(TalonRemoteErrorKind::Protocol, e.0))
+    }
+
+    /// Whether an embedding is stored under `id`.
+    pub fn contains(&self, id: u64) -> Result<bool, TalonError> {
+        let resp = self
+            .client
+            .exec_cmd_json(&id_cmd("contains", &self.index, id))?;
+        parse_contains(remote_response_data(&resp)?)
+            .map_err(|e| remote_error(TalonRemoteErrorKind::Protocol, e.0))
+    }
+
+    /// Atomically insert or replace the embedding for `id`, keeping its payload.
+    pub fn upsert(&self, id: u64, embedding: &[f32]) -> Result<(), TalonError> {
+        self.client
+            .exec_cmd(&upsert_cmd(&self.index, id, embedding))
+    }
+
+    /// List ids in ascending order, one page at a time. Pass the previous
+    /// page's `next` as `after`, or `None` for the first page.
+    pub fn ids(&self, after: Option<u64>, limit: usize) -> Result<VectorIdPage, TalonError> {
+        let resp = self
+            .client
+            .exec_cmd_json(&ids_cmd(&self.index, after, limit))?;
+        parse_id_page(remote_response_data(&resp)?)
+            .map_err(|e| remote_error(TalonRemoteErrorKind::Protocol, e.0))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn batch_is_flattened_and_dimension_checked() {
+        let a = [1.0, 2.0];
+        let b = [3.0, 4.0];
+        let batch = flatten_batch(&[(7, &a), (8, &b)]).unwrap();
+        assert_eq!(batch.ids, vec![7, 8]);
+        assert_eq!(batch.data, vec![1.0, 2.0, 3.0, 4.0]);
+        assert_eq!(batch.dim, 2);
+
+        let err = flatten_batch(&[(7, &a), (8, &[1.0][..])]).unwrap_err();
+        assert_eq!(
+            err.0,
+            "vector insert_batch item 1 (id 8): dimension 1, expected 2"
+        );
+        assert!(flatten_batch(&[(1, &[][..])]).is_err());
+        assert!(flatten_batch(&[]).unwrap().ids.is_empty());
+    }
+
+    #[test]
+    fn chunks_report_progress() {
+        let vectors: Vec<[f32; 2]> = (0..5).map(|i| [i as f32, 0.0]).collect();
+        let items: Vec<(u64, &[f32])> = vectors
+            .iter()
+            .enumerate()
+            .map(|(i, v)| (i as u64, &v[..]))
+            .collect();
+        let batch = flatten_batch(&items).unwrap();
+        let mut sent = Vec::new();
+        let mut seen = Vec::new();
+        batch
+            .send_chunks(
+                2,
+                &mut |done, total| seen.push((done, total)),
+                |ids, data| {
+                    assert_eq!(data.len(), ids.len() * 2);
+                    sent.push(ids.to_vec());
+                    Ok(())
+                },
+            )
+            .unwrap();
+        assert_eq!(sent, vec![vec![0, 1], vec![2, 3], vec![4]]);
+        assert_eq!(seen, vec![(2, 5), (4, 5), (5, 5)]);
+
+        let err = batch
+            .send_chunks(2, &mut |_, _| {}, |ids, _| {
+                if ids[0] == 2 {
+                    Err(TalonError("index full".into()))
+                } else {
+                    Ok(())
+                }
+            })
+            .unwrap_err();
+        assert_eq!(
+            err.0,
+            "vector insert_batch failed after 2 of 5 vectors: index full"
+        );
+    }
+
+    #[test]
+    fn remote_chunks_fit_in_a_frame() {
+        let dim = 1536;
+        let chunk = remote_chunk_len(dim);
+        let encoded = (chunk * dim * 4).div_ceil(3) * 4 + chunk * 21;
+        assert!(encoded < MAX_REMOTE_FRAME_SIZE as usize);
+        assert!(chunk > 100);
+    }
+
+    #[test]
+    fn index_config_and_stats_round_trip() {
+        let config = VectorIndexConfig::new(384).metric(VectorMetric::L2);
+        let cmd = create_index_cmd("emb", &config);
+        assert_eq!(cmd["params"]["metric"], "l2");
+        assert_eq!(cmd["params"]["hnsw_m"], 16);
+        assert_eq!(cmd["params"]["dim"], 384);
+
+        let data = serde_json::json!({
+            "count": 10, "memory_bytes": 4096, "state": "rebuilding", "dim": 384, "metric": "l2"
+        });
+        let stats = parse_index_stats(Some(&data)).unwrap();
+        assert_eq!(stats.state, VectorIndexState::Rebuilding);
+        assert_eq!(stats.memory_bytes, 4096);
+        let odd = serde_json::json!({"state": "compacting"});
+        assert_eq!(
+            parse_index_stats(Some(&odd)).unwrap().state,
+            VectorIndexState::Unknown
+        );
+
+        let list = serde_json::json!({"indexes": [{"name": "emb", "dim": 384, "metric": "l2"}]});
+        let indexes = parse_index_list(Some(&list)).unwrap();
+        assert_eq!(indexes[0].name, "emb");
+        assert_eq!(indexes[0].count, 0);
+        let empty = serde_json::json!({"indexes": []});
+        assert!(parse_index_list(Some(&empty)).unwrap().is_empty());
+        assert!(parse_index_list(None).is_err());
+        assert!(parse_index_list(Some(&serde_json::json!({}))).is_err());
+    }
+
+    #[test]
+    fn filters_serialize_for_the_engine() {
+        let filter = VectorFilter::eq("lang", "en")
+            .and(VectorFilter::gte("year", 2020))
+            .and(VectorFilter::is_in("tag", ["ai", "db"]).negate());
+        assert_eq!(
+            filter.to_json(),
+            serde_json::json!({"op": "and", "filters": [
+                {"op": "eq", "field": "lang", "value": "en"},
+                {"op": "gte", "field": "year", "value": 2020},
+                {"op": "not", "filter": {"op": "in", "field": "tag", "value": ["ai", "db"]}}
+            ]})
+        );
+        let either = VectorFilter::exists("a").or(VectorFilter::ne("b", 1));
+        assert_eq!(either.to_json()["filters"][0]["op"], "exists");
+        assert_eq!(
+            VectorFilter::all().to_json(),
+            serde_json::json!({"op": "and", "filters": []})
+        );
+    }
+
+    #[test]
+    fn payload_must_be_object_and_hits_carry_it() {
+        let err = insert_with_payload_cmd("emb", 1, &[1.0], &serde_json::json!("x")).unwrap_err();
+        assert!(err.0.contains("JSON object"));
+        let cmd = insert_with_payload_cmd("emb", 1, &[1.0], &serde_json::json!({"k": 1})).unwrap();
+        assert_eq!(cmd["params"]["payload"]["k"], 1);
+
+        let data = serde_json::json!({"results": [
+            {"id": 1, "distance": 0.25, "payload": {"lang": "en"}},
+            {"id": 2, "distance": 0.5}
+        ]});
+        let hits = parse_hits(Some(&data)).unwrap();
+        assert_eq!(hits[0].payload, Some(serde_json::json!({"lang": "en"})));
+        assert_eq!(hits[1].payload, None);
+        assert!(parse_hits(None).is_err());
+        let cmd = search_with_cmd(
+            "emb",
+            &[1.0],
+            3,
+            VectorMetric::L2,
+            &VectorFilter::exists("k"),
+            false,
+        );
+        assert_eq!(cmd["params"]["metric"], "l2");
+    }
+
+    #[test]
+    fn get_and_ids_responses_are_parsed() {
+        let data = serde_json::json!({"vector": [0.5, -1.0]});
+        assert_eq!(parse_embedding(Some(&data)).unwrap(), Some(vec![0.5, -1.0]));
+        assert_eq!(
+            parse_embedding(Some(&serde_json::json!({"vector": null}))).unwrap(),
+            None
+        );
+        assert!(parse_embedding(None).is_err());
+        assert!(parse_embedding(Some(&serde_json::json!({}))).is_err());
+        assert!(parse_embedding(Some(&serde_json::json!({"vector": ["x"]}))).is_err());
+
+        assert!(parse_contains(Some(&serde_json::json!({"exists": true}))).unwrap());
+        assert!(!parse_contains(Some(&serde_json::json!({"exists": false}))).unwrap());
+        assert!(parse_contains(None).is_err());
+        assert!(parse_contains(Some(&serde_json::json!({"exists": 1}))).is_err());
+
+        let page = parse_id_page(Some(&serde_json::json!({"ids": [3, 5], "next": 5}))).unwrap();
+        assert_eq!(page.ids, vec![3, 5]);
+        assert_eq!(page.next, Some(5));
+        let last = parse_id_page(Some(&serde_json::json!({"ids": [9]}))).unwrap();
+        assert_eq!(last.next, None);
+        assert!(parse_id_page(None).is_err());
+        assert!(parse_id_page(Some(&serde_json::json!({"next": 5}))).is_err());
+        assert_eq!(
+            ids_cmd("emb", None, 100)["params"]["after"],
+            serde_json::Value::Null
+        );
+    }
+}